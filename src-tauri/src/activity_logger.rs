@@ -21,6 +21,10 @@ use aes_gcm::{
 };
 use aes_gcm::AeadCore;
 use sha2::{Sha256, Digest};
+use tauri::State;
+use crate::database::Database;
+use crate::error::XPassError;
+use crate::secrets::SecretString;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ActivityLog {
@@ -74,75 +78,6 @@ fn get_encryption_key(user_id: i64) -> [u8; 32] {
     key
 }
 
-/// Initializes security-related tables
-pub fn init_security_tables(conn: &Connection) -> RusqliteResult<()> {
-    // Table for deferred deletions
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS pending_deletions (
-            user_id INTEGER PRIMARY KEY,
-            scheduled_at TEXT NOT NULL,
-            FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
-        )",
-        [],
-    )?;
-    
-    // ✅ UPDATED table: storing encrypted photos in the database
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS failed_login_photos (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            user_id INTEGER NOT NULL,
-            encrypted_photo BLOB NOT NULL,
-            photo_nonce BLOB NOT NULL,
-            timestamp TEXT NOT NULL,
-            username_attempt TEXT NOT NULL,
-            FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
-        )",
-        [],
-    )?;
-    
-    // Security settings table
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS security_settings (
-            user_id INTEGER PRIMARY KEY,
-            photo_on_failed_login BOOLEAN DEFAULT 0,
-            FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
-        )",
-        [],
-    )?;
-    
-    Ok(())
-}
-
-/// Creates the activity_logs table with optimization
-pub fn init_activity_table(conn: &Connection) -> RusqliteResult<()> {
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS activity_logs (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            user_id INTEGER NOT NULL,
-            action_type TEXT NOT NULL,
-            details TEXT NOT NULL,
-            timestamp TEXT NOT NULL,
-            FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
-        )",
-        [],
-    )?;
-    
-    // Creating composite index to optimize queries
-    conn.execute(
-        "CREATE INDEX IF NOT EXISTS idx_activity_user_time 
-         ON activity_logs(user_id, timestamp DESC)",
-        [],
-    )?;
-    
-    conn.execute(
-        "CREATE INDEX IF NOT EXISTS idx_activity_type 
-         ON activity_logs(action_type)",
-        [],
-    )?;
-    
-    Ok(())
-}
-
 fn is_user_in_pseudo_mode(conn: &Connection, user_id: i64) -> bool {
     conn.query_row(
         "SELECT CASE 
@@ -168,21 +103,18 @@ pub fn log_activity(
 ) -> RusqliteResult<()> {
     let timestamp = Utc::now().to_rfc3339();
     
-    conn.execute(
+    conn.prepare_cached(
         "INSERT INTO activity_logs (user_id, action_type, details, timestamp)
-         VALUES (?1, ?2, ?3, ?4)",
-        params![user_id, action_type, details, timestamp],
-    )?;
+         VALUES (?1, ?2, ?3, ?4)"
+    )?.execute(params![user_id, action_type, details, timestamp])?;
     
     Ok(())
 }
 
 /// Gets the last N activity log entries
 #[tauri::command]
-pub fn get_activity_logs(user_id: i64, limit: i64) -> Result<Vec<ActivityLog>, String> {
-    use crate::password_manager::get_conn;
-    
-    let conn = get_conn().map_err(|e| e.to_string())?;
+pub fn get_activity_logs(db: State<'_, Database>, user_id: i64, limit: i64) -> Result<Vec<ActivityLog>, XPassError> {
+    let conn = db.conn()?;
     
     // PROTECTION: In pseudo-mode return empty list
     if is_user_in_pseudo_mode(&conn, user_id) {
@@ -197,7 +129,7 @@ pub fn get_activity_logs(user_id: i64, limit: i64) -> Result<Vec<ActivityLog>, S
              FROM activity_logs 
              WHERE user_id = ?1 
              ORDER BY timestamp DESC"
-        ).map_err(|e| e.to_string())?;
+        )?;
         
         let rows = stmt.query_map(params![user_id], |row| {
             Ok(ActivityLog {
@@ -207,7 +139,7 @@ pub fn get_activity_logs(user_id: i64, limit: i64) -> Result<Vec<ActivityLog>, S
                 details: row.get(3)?,
                 timestamp: row.get(4)?,
             })
-        }).map_err(|e| e.to_string())?;
+        })?;
         
         rows.filter_map(|r| r.ok()).collect()
     } else {
@@ -218,7 +150,7 @@ pub fn get_activity_logs(user_id: i64, limit: i64) -> Result<Vec<ActivityLog>, S
              WHERE user_id = ?1 
              ORDER BY timestamp DESC 
              LIMIT ?2"
-        ).map_err(|e| e.to_string())?;
+        )?;
         
         let rows = stmt.query_map(params![user_id, limit], |row| {
             Ok(ActivityLog {
@@ -228,7 +160,7 @@ pub fn get_activity_logs(user_id: i64, limit: i64) -> Result<Vec<ActivityLog>, S
                 details: row.get(3)?,
                 timestamp: row.get(4)?,
             })
-        }).map_err(|e| e.to_string())?;
+        })?;
         
         rows.filter_map(|r| r.ok()).collect()
     };
@@ -238,10 +170,8 @@ pub fn get_activity_logs(user_id: i64, limit: i64) -> Result<Vec<ActivityLog>, S
 
 /// Gets activity statistics
 #[tauri::command]
-pub fn get_activity_stats(user_id: i64) -> Result<ActivityStats, String> {
-    use crate::password_manager::get_conn;
-    
-    let conn = get_conn().map_err(|e| e.to_string())?;
+pub fn get_activity_stats(db: State<'_, Database>, user_id: i64) -> Result<ActivityStats, XPassError> {
+    let conn = db.conn()?;
     
     // PROTECTION: In pseudo-mode return empty statistics
     if is_user_in_pseudo_mode(&conn, user_id) {
@@ -297,14 +227,14 @@ pub fn get_activity_stats(user_id: i64) -> Result<ActivityStats, String> {
          WHERE user_id = ?1 
          GROUP BY action_type 
          ORDER BY count DESC"
-    ).map_err(|e| e.to_string())?;
+    )?;
     
     let actions_by_type = stmt.query_map(params![user_id], |row| {
         Ok(ActionTypeCount {
             action_type: row.get(0)?,
             count: row.get(1)?,
         })
-    }).map_err(|e| e.to_string())?
+    })?
     .filter_map(|r| r.ok())
     .collect();
     
@@ -319,11 +249,10 @@ pub fn get_activity_stats(user_id: i64) -> Result<ActivityStats, String> {
 
 /// Gets activity trend for the last N days
 #[tauri::command]
-pub fn get_activity_trend(user_id: i64, days: i64) -> Result<Vec<ActivityTrend>, String> {
-    use crate::password_manager::get_conn;
+pub fn get_activity_trend(db: State<'_, Database>, user_id: i64, days: i64) -> Result<Vec<ActivityTrend>, XPassError> {
     use chrono::Duration;
     
-    let conn = get_conn().map_err(|e| e.to_string())?;
+    let conn = db.conn()?;
     
     // PROTECTION: In pseudo-mode return empty trend
     if is_user_in_pseudo_mode(&conn, user_id) {
@@ -338,14 +267,14 @@ pub fn get_activity_trend(user_id: i64, days: i64) -> Result<Vec<ActivityTrend>,
          WHERE user_id = ?1 AND timestamp >= ?2
          GROUP BY date 
          ORDER BY date ASC"
-    ).map_err(|e| e.to_string())?;
+    )?;
     
     let trend = stmt.query_map(params![user_id, cutoff_date], |row| {
         Ok(ActivityTrend {
             date: row.get(0)?,
             count: row.get(1)?,
         })
-    }).map_err(|e| e.to_string())?
+    })?
     .filter_map(|r| r.ok())
     .collect();
     
@@ -354,84 +283,76 @@ pub fn get_activity_trend(user_id: i64, days: i64) -> Result<Vec<ActivityTrend>,
 
 /// Clears all activity logs for the user
 #[tauri::command]
-pub fn clear_activity_logs(user_id: i64) -> Result<(), String> {
-    use crate::password_manager::get_conn;
-    
-    let conn = get_conn().map_err(|e| e.to_string())?;
+pub fn clear_activity_logs(db: State<'_, Database>, user_id: i64) -> Result<(), XPassError> {
+    let conn = db.conn()?;
     
     conn.execute(
         "DELETE FROM activity_logs WHERE user_id = ?1",
         params![user_id],
-    ).map_err(|e| e.to_string())?;
+    )?;
     
     Ok(())
 }
 
 /// Deletes old logs
 #[tauri::command]
-pub fn cleanup_old_logs(user_id: i64, days: i64) -> Result<usize, String> {
-    use crate::password_manager::get_conn;
+pub fn cleanup_old_logs(db: State<'_, Database>, user_id: i64, days: i64) -> Result<usize, XPassError> {
     use chrono::Duration;
     
-    let conn = get_conn().map_err(|e| e.to_string())?;
+    let conn = db.conn()?;
     let cutoff_date = (Utc::now() - Duration::days(days)).to_rfc3339();
     
     let deleted = conn.execute(
         "DELETE FROM activity_logs WHERE user_id = ?1 AND timestamp < ?2",
         params![user_id, cutoff_date],
-    ).map_err(|e| e.to_string())?;
+    )?;
     
     Ok(deleted)
 }
 
 /// Exports logs to JSON format
 #[tauri::command]
-pub fn export_activity_logs(user_id: i64) -> Result<String, String> {
-    let logs = get_activity_logs(user_id, -1)?;  // ✅ Using -1 to load all logs
-    serde_json::to_string_pretty(&logs).map_err(|e| e.to_string())
+pub fn export_activity_logs(db: State<'_, Database>, user_id: i64) -> Result<String, XPassError> {
+    let logs = get_activity_logs(db, user_id, -1)?;  // ✅ Using -1 to load all logs
+    serde_json::to_string_pretty(&logs).map_err(XPassError::internal)
 }
 
 /// Gets the total number of logs for a user
 #[tauri::command]
-pub fn get_activity_count(user_id: i64) -> Result<i64, String> {
-    use crate::password_manager::get_conn;
-    
-    let conn = get_conn().map_err(|e| e.to_string())?;
+pub fn get_activity_count(db: State<'_, Database>, user_id: i64) -> Result<i64, XPassError> {
+    let conn = db.conn()?;
     
     let count: i64 = conn.query_row(
         "SELECT COUNT(*) FROM activity_logs WHERE user_id = ?1",
         params![user_id],
         |row| row.get(0)
-    ).map_err(|e| e.to_string())?;
+    )?;
     
     Ok(count)
 }
 
 /// Schedules log deletion on next login
 #[tauri::command]
-pub fn schedule_logs_deletion(user_id: i64, password: String) -> Result<(), String> {
-    use crate::password_manager::{verify_user_password, get_conn};
+pub fn schedule_logs_deletion(db: State<'_, Database>, user_id: i64, password: SecretString) -> Result<(), XPassError> {
+    let conn = db.conn()?;
     
     // Verify password
-    verify_user_password(user_id, password)?;
+    crate::password_manager::check_master_password(&conn, user_id, &password)?;
     
-    let conn = get_conn().map_err(|e| e.to_string())?;
     let timestamp = Utc::now().to_rfc3339();
     
     conn.execute(
         "INSERT OR REPLACE INTO pending_deletions (user_id, scheduled_at) VALUES (?1, ?2)",
         params![user_id, timestamp],
-    ).map_err(|e| e.to_string())?;
+    )?;
     
     Ok(())
 }
 
 /// Checks and executes pending log deletion
 #[tauri::command]
-pub fn check_pending_deletion(user_id: i64) -> Result<bool, String> {
-    use crate::password_manager::get_conn;
-    
-    let conn = get_conn().map_err(|e| e.to_string())?;
+pub fn check_pending_deletion(db: State<'_, Database>, user_id: i64) -> Result<bool, XPassError> {
+    let conn = db.conn()?;
     
     let pending: Option<String> = conn.query_row(
         "SELECT scheduled_at FROM pending_deletions WHERE user_id = ?1",
@@ -444,13 +365,13 @@ pub fn check_pending_deletion(user_id: i64) -> Result<bool, String> {
         conn.execute(
             "DELETE FROM activity_logs WHERE user_id = ?1",
             params![user_id],
-        ).map_err(|e| e.to_string())?;
+        )?;
         
         // Remove pending deletion record
         conn.execute(
             "DELETE FROM pending_deletions WHERE user_id = ?1",
             params![user_id],
-        ).map_err(|e| e.to_string())?;
+        )?;
         
         return Ok(true);
     }
@@ -460,15 +381,13 @@ pub fn check_pending_deletion(user_id: i64) -> Result<bool, String> {
 
 /// Cancels scheduled log deletion
 #[tauri::command]
-pub fn cancel_logs_deletion(user_id: i64) -> Result<(), String> {
-    use crate::password_manager::get_conn;
-    
-    let conn = get_conn().map_err(|e| e.to_string())?;
+pub fn cancel_logs_deletion(db: State<'_, Database>, user_id: i64) -> Result<(), XPassError> {
+    let conn = db.conn()?;
     
     conn.execute(
         "DELETE FROM pending_deletions WHERE user_id = ?1",
         params![user_id],
-    ).map_err(|e| e.to_string())?;
+    )?;
     
     Ok(())
 }
@@ -476,20 +395,19 @@ pub fn cancel_logs_deletion(user_id: i64) -> Result<(), String> {
 /// Saves encrypted photo of failed login attempt to database
 #[tauri::command]
 pub fn save_failed_login_photo(
+    db: State<'_, Database>,
     username: String,
     photo_data: String,
     username_attempt: String
-) -> Result<(), String> {
-    use crate::password_manager::get_conn;
-    
-    let conn = get_conn().map_err(|e| e.to_string())?;
+) -> Result<(), XPassError> {
+    let conn = db.conn()?;
     
     // Get user_id by username
     let user_id: i64 = conn.query_row(
         "SELECT id FROM users WHERE username = ?1",
         params![username],
         |row| row.get(0)
-    ).map_err(|_| "User not found".to_string())?;
+    ).map_err(|_| XPassError::UserNotFound)?;
     
     // Check if the feature is enabled
     let enabled: bool = conn.query_row(
@@ -505,7 +423,7 @@ pub fn save_failed_login_photo(
     // Decode base64 photo
     let photo_bytes = general_purpose::STANDARD
         .decode(photo_data.split(',').nth(1).unwrap_or(&photo_data))
-        .map_err(|e| e.to_string())?;
+        .map_err(|e| XPassError::invalid_input("photo_data", e.to_string()))?;
     
     // ✅ Encrypt photo
     let key_bytes = get_encryption_key(user_id);
@@ -514,7 +432,7 @@ pub fn save_failed_login_photo(
     
     let nonce = Aes256Gcm::generate_nonce(&mut rand::thread_rng());
     let encrypted_photo = cipher.encrypt(&nonce, photo_bytes.as_ref())
-        .map_err(|_| "Encryption failed".to_string())?;
+        .map_err(|_| XPassError::EncryptionFailed)?;
     
     let timestamp = Utc::now().to_rfc3339();
     
@@ -529,17 +447,15 @@ pub fn save_failed_login_photo(
             timestamp, 
             username_attempt
         ],
-    ).map_err(|e| e.to_string())?;
+    )?;
     
     Ok(())
 }
 
 /// Gets and decrypts photos of failed login attempts
 #[tauri::command]
-pub fn get_failed_login_photos(user_id: i64) -> Result<Vec<FailedLoginPhoto>, String> {
-    use crate::password_manager::get_conn;
-    
-    let conn = get_conn().map_err(|e| e.to_string())?;
+pub fn get_failed_login_photos(db: State<'_, Database>, user_id: i64) -> Result<Vec<FailedLoginPhoto>, XPassError> {
+    let conn = db.conn()?;
     
     // PROTECTION: In pseudo-mode return empty list
     if is_user_in_pseudo_mode(&conn, user_id) {
@@ -551,7 +467,7 @@ pub fn get_failed_login_photos(user_id: i64) -> Result<Vec<FailedLoginPhoto>, St
          FROM failed_login_photos 
          WHERE user_id = ?1 
          ORDER BY timestamp DESC"
-    ).map_err(|e| e.to_string())?;
+    )?;
     
     let key_bytes = get_encryption_key(user_id);
     let key = aes_gcm::Key::<Aes256Gcm>::from_slice(&key_bytes);
@@ -583,7 +499,7 @@ pub fn get_failed_login_photos(user_id: i64) -> Result<Vec<FailedLoginPhoto>, St
             timestamp,
             username_attempt,
         })
-    }).map_err(|e| e.to_string())?
+    })?
     .filter_map(|r| r.ok())
     .collect();
     
@@ -592,19 +508,17 @@ pub fn get_failed_login_photos(user_id: i64) -> Result<Vec<FailedLoginPhoto>, St
 
 /// Deletes a failed login attempt photo
 #[tauri::command]
-pub fn delete_failed_login_photo(photo_id: i64, user_id: i64) -> Result<(), String> {
-    use crate::password_manager::get_conn;
-    
-    let conn = get_conn().map_err(|e| e.to_string())?;
+pub fn delete_failed_login_photo(db: State<'_, Database>, photo_id: i64, user_id: i64) -> Result<(), XPassError> {
+    let conn = db.conn()?;
     
     // ✅ Simply delete the record from database
     let count = conn.execute(
         "DELETE FROM failed_login_photos WHERE id = ?1 AND user_id = ?2",
         params![photo_id, user_id],
-    ).map_err(|e| e.to_string())?;
+    )?;
     
     if count == 0 {
-        return Err("Photo not found".to_string());
+        return Err(XPassError::NotFound { what: "Photo" });
     }
     
     Ok(())
@@ -612,26 +526,22 @@ pub fn delete_failed_login_photo(photo_id: i64, user_id: i64) -> Result<(), Stri
 
 /// Updates the failed login photo setting
 #[tauri::command]
-pub fn update_photo_setting(user_id: i64, enabled: bool) -> Result<(), String> {
-    use crate::password_manager::get_conn;
-    
-    let conn = get_conn().map_err(|e| e.to_string())?;
+pub fn update_photo_setting(db: State<'_, Database>, user_id: i64, enabled: bool) -> Result<(), XPassError> {
+    let conn = db.conn()?;
     
     conn.execute(
         "INSERT OR REPLACE INTO security_settings (user_id, photo_on_failed_login)
          VALUES (?1, ?2)",
         params![user_id, enabled],
-    ).map_err(|e| e.to_string())?;
+    )?;
     
     Ok(())
 }
 
 /// Gets the failed login photo setting
 #[tauri::command]
-pub fn get_photo_setting(user_id: i64) -> Result<bool, String> {
-    use crate::password_manager::get_conn;
-    
-    let conn = get_conn().map_err(|e| e.to_string())?;
+pub fn get_photo_setting(db: State<'_, Database>, user_id: i64) -> Result<bool, XPassError> {
+    let conn = db.conn()?;
     
     let enabled: bool = conn.query_row(
         "SELECT COALESCE(photo_on_failed_login, 0) FROM security_settings WHERE user_id = ?1",
@@ -644,16 +554,14 @@ pub fn get_photo_setting(user_id: i64) -> Result<bool, String> {
 
 /// Checks if the failed login photo feature is enabled for a user by username
 #[tauri::command]
-pub fn is_photo_setting_enabled_for_username(username: String) -> Result<bool, String> {
-    use crate::password_manager::get_conn;
-    
-    let conn = get_conn().map_err(|e| e.to_string())?;
+pub fn is_photo_setting_enabled_for_username(db: State<'_, Database>, username: String) -> Result<bool, XPassError> {
+    let conn = db.conn()?;
     
     let user_id: i64 = conn.query_row(
         "SELECT id FROM users WHERE username = ?1",
         params![username],
         |row| row.get(0)
-    ).map_err(|_| "User not found".to_string())?;
+    ).map_err(|_| XPassError::UserNotFound)?;
     
     let enabled: bool = conn.query_row(
         "SELECT COALESCE(photo_on_failed_login, 0) FROM security_settings WHERE user_id = ?1",
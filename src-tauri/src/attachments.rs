@@ -0,0 +1,433 @@
+// ============================================================================
+// X-PASS Password Manager
+// Copyright (C) 2026 ar3love
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See LICENSE.
+// ============================================================================
+
+// src-tauri/src/attachments.rs
+//
+// Files attached to entries: recovery-code PDFs, key files, scanned documents.
+// A file is split into `CHUNK_SIZE` pieces, each encrypted with the data key
+// under its own nonce. The attachment id, the chunk position and the chunk
+// count are bound in as associated data, so chunks cannot be swapped between
+// files, reordered or dropped without decryption failing. The file name is
+// encrypted as well; only the size is kept in clear, for the limits.
+//
+// Files are picked and saved with the dialog plugin from the backend, so the
+// webview never hands us a path to read or write.
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+
+use aes_gcm::{
+    aead::{Aead, AeadCore, Payload},
+    Aes256Gcm,
+    Nonce,
+};
+use base64::{engine::general_purpose, Engine as _};
+use chrono::Utc;
+use log::info;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, State};
+use tauri_plugin_dialog::DialogExt;
+use zeroize::Zeroizing;
+
+use crate::database::Database;
+use crate::error::XPassError;
+use crate::keys;
+use crate::secrets::SecretString;
+use crate::session::SessionStore;
+
+const CHUNK_SIZE: usize = 512 * 1024;
+/// Largest single file, 50 MiB
+pub const MAX_FILE_SIZE: u64 = 50 * 1024 * 1024;
+/// Largest total of all attachments of a user, 500 MiB
+pub const MAX_VAULT_SIZE: u64 = 500 * 1024 * 1024;
+
+#[derive(Serialize, Debug)]
+pub struct Attachment {
+    pub id: i64,
+    pub entry_id: i64,
+    pub name: SecretString,
+    pub size: u64,
+    pub created_at: String,
+    #[serde(skip)]
+    chunk_count: u32,
+}
+
+#[derive(Serialize)]
+pub struct AttachmentUsage {
+    pub used: u64,
+    pub max_file_size: u64,
+    pub max_vault_size: u64,
+}
+
+/// An attachment inside a backup, the content base64 encoded
+#[derive(Serialize, Deserialize, Clone)]
+pub struct BackupAttachment {
+    pub name: SecretString,
+    pub data: SecretString,
+}
+
+fn chunk_aad(attachment_id: i64, position: u32, chunk_count: u32) -> [u8; 16] {
+    let mut aad = [0u8; 16];
+    aad[..8].copy_from_slice(&attachment_id.to_le_bytes());
+    aad[8..12].copy_from_slice(&position.to_le_bytes());
+    aad[12..].copy_from_slice(&chunk_count.to_le_bytes());
+    aad
+}
+
+/// Total size of the user's attachments, trashed entries included
+fn used_bytes(conn: &Connection, user_id: i64) -> Result<u64, XPassError> {
+    let used: i64 = conn.query_row(
+        "SELECT COALESCE(SUM(size), 0) FROM attachments WHERE user_id = ?1",
+        params![user_id],
+        |row| row.get(0)
+    )?;
+    Ok(used as u64)
+}
+
+fn check_limits(conn: &Connection, user_id: i64, size: u64) -> Result<(), XPassError> {
+    if size > MAX_FILE_SIZE {
+        return Err(XPassError::AttachmentTooLarge { size, limit: MAX_FILE_SIZE });
+    }
+    let used = used_bytes(conn, user_id)?;
+    if used + size > MAX_VAULT_SIZE {
+        return Err(XPassError::AttachmentQuotaExceeded { used, limit: MAX_VAULT_SIZE });
+    }
+    Ok(())
+}
+
+/// Service of a live entry of the user, for logging
+fn entry_service(conn: &Connection, user_id: i64, entry_id: i64) -> Result<String, XPassError> {
+    conn.query_row(
+        "SELECT service FROM entries WHERE id = ?1 AND user_id = ?2 AND deleted_at IS NULL",
+        params![entry_id, user_id],
+        |row| row.get(0)
+    ).optional()?.ok_or(XPassError::NotFound { what: "Entry" })
+}
+
+/// Encrypts `size` bytes from `reader` into a new attachment of the entry.
+/// Call inside a transaction so a failed read leaves nothing behind.
+pub fn store(
+    conn: &Connection,
+    user_id: i64,
+    entry_id: i64,
+    name: &SecretString,
+    size: u64,
+    mut reader: impl Read,
+    cipher: &Aes256Gcm
+) -> Result<Attachment, XPassError> {
+    check_limits(conn, user_id, size)?;
+
+    let (enc_name, name_nonce) = keys::encrypt_secret(cipher, name)?;
+    let chunk_count = size.div_ceil(CHUNK_SIZE as u64) as u32;
+    let created_at = Utc::now().to_rfc3339();
+    conn.execute(
+        "INSERT INTO attachments (entry_id, user_id, enc_name, name_nonce, size, chunk_count, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![entry_id, user_id, enc_name, name_nonce, size as i64, chunk_count, &created_at],
+    )?;
+    let id = conn.last_insert_rowid();
+
+    let mut stmt = conn.prepare_cached(
+        "INSERT INTO attachment_chunks (attachment_id, position, enc_data, nonce) VALUES (?1, ?2, ?3, ?4)"
+    )?;
+    let mut buffer = Zeroizing::new(vec![0u8; CHUNK_SIZE]);
+    let mut remaining = size;
+    for position in 0..chunk_count {
+        let len = remaining.min(CHUNK_SIZE as u64) as usize;
+        reader.read_exact(&mut buffer[..len])?;
+
+        let nonce = Aes256Gcm::generate_nonce(&mut rand::thread_rng());
+        let aad = chunk_aad(id, position, chunk_count);
+        let enc_data = cipher.encrypt(&nonce, Payload { msg: &buffer[..len], aad: &aad })
+            .map_err(|_| XPassError::EncryptionFailed)?;
+        stmt.execute(params![id, position, enc_data, nonce.as_slice()])?;
+        remaining -= len as u64;
+    }
+
+    // The size was checked up front, a file that grew since must not slip past the limits
+    if reader.read(&mut [0u8; 1])? != 0 {
+        return Err(XPassError::invalid_input("file", "the file changed while it was being attached"));
+    }
+
+    Ok(Attachment { id, entry_id, name: name.clone(), size, created_at, chunk_count })
+}
+
+/// Decrypts an attachment chunk by chunk, handing each plaintext to `sink`
+fn read_chunks(
+    conn: &Connection,
+    attachment: &Attachment,
+    cipher: &Aes256Gcm,
+    mut sink: impl FnMut(&[u8]) -> Result<(), XPassError>
+) -> Result<(), XPassError> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT position, enc_data, nonce FROM attachment_chunks WHERE attachment_id = ?1 ORDER BY position"
+    )?;
+    let rows = stmt.query_map(params![attachment.id], |row| {
+        Ok((row.get::<_, u32>(0)?, row.get::<_, Vec<u8>>(1)?, row.get::<_, Vec<u8>>(2)?))
+    })?;
+
+    let mut expected = 0u32;
+    for row in rows {
+        let (position, enc_data, nonce) = row?;
+        if position != expected || nonce.len() != 12 {
+            return Err(XPassError::DecryptionFailed);
+        }
+        let aad = chunk_aad(attachment.id, position, attachment.chunk_count);
+        let plain = Zeroizing::new(
+            cipher.decrypt(Nonce::from_slice(&nonce), Payload { msg: &enc_data, aad: &aad })
+                .map_err(|_| XPassError::DecryptionFailed)?
+        );
+        sink(&plain)?;
+        expected += 1;
+    }
+    if expected != attachment.chunk_count {
+        return Err(XPassError::DecryptionFailed);
+    }
+    Ok(())
+}
+
+fn load(conn: &Connection, user_id: i64, attachment_id: i64, cipher: &Aes256Gcm) -> Result<Attachment, XPassError> {
+    let (entry_id, enc_name, name_nonce, size, chunk_count, created_at) = conn.query_row(
+        "SELECT entry_id, enc_name, name_nonce, size, chunk_count, created_at
+         FROM attachments WHERE id = ?1 AND user_id = ?2",
+        params![attachment_id, user_id],
+        |row| Ok((
+            row.get::<_, i64>(0)?,
+            row.get::<_, Vec<u8>>(1)?,
+            row.get::<_, Vec<u8>>(2)?,
+            row.get::<_, i64>(3)?,
+            row.get::<_, u32>(4)?,
+            row.get::<_, String>(5)?,
+        ))
+    ).optional()?.ok_or(XPassError::NotFound { what: "Attachment" })?;
+
+    Ok(Attachment {
+        id: attachment_id,
+        entry_id,
+        name: keys::decrypt_secret(cipher, &enc_name, &name_nonce)?,
+        size: size as u64,
+        created_at,
+        chunk_count,
+    })
+}
+
+/// Attachments of an entry with decrypted names, oldest first
+pub fn list(conn: &Connection, user_id: i64, entry_id: i64, cipher: &Aes256Gcm) -> Result<Vec<Attachment>, XPassError> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT id FROM attachments WHERE entry_id = ?1 AND user_id = ?2 ORDER BY id"
+    )?;
+    let ids: Vec<i64> = stmt.query_map(params![entry_id, user_id], |row| row.get(0))?
+        .collect::<Result<_, _>>()?;
+    ids.into_iter().map(|id| load(conn, user_id, id, cipher)).collect()
+}
+
+/// Decrypted attachments of an entry for a backup
+pub fn export_entry(conn: &Connection, user_id: i64, entry_id: i64, cipher: &Aes256Gcm) -> Result<Vec<BackupAttachment>, XPassError> {
+    let mut exported = Vec::new();
+    for attachment in list(conn, user_id, entry_id, cipher)? {
+        let mut data = Zeroizing::new(Vec::with_capacity(attachment.size as usize));
+        read_chunks(conn, &attachment, cipher, |chunk| {
+            data.extend_from_slice(chunk);
+            Ok(())
+        })?;
+        exported.push(BackupAttachment {
+            name: attachment.name,
+            data: SecretString::new(general_purpose::STANDARD.encode(&*data)),
+        });
+    }
+    Ok(exported)
+}
+
+/// Re-encrypts attachments from a backup onto an imported entry
+pub fn import_entry(
+    conn: &Connection,
+    user_id: i64,
+    entry_id: i64,
+    attachments: &[BackupAttachment],
+    cipher: &Aes256Gcm
+) -> Result<(), XPassError> {
+    for attachment in attachments {
+        let data = Zeroizing::new(
+            general_purpose::STANDARD.decode(attachment.data.expose())
+                .map_err(|_| XPassError::InvalidBackup("malformed attachment".to_string()))?
+        );
+        store(conn, user_id, entry_id, &attachment.name, data.len() as u64, &data[..], cipher)?;
+    }
+    Ok(())
+}
+
+/// Lists the attachments of an entry
+#[tauri::command]
+pub fn list_attachments(
+    db: State<'_, Database>,
+    sessions: State<'_, SessionStore>,
+    user_id: i64,
+    session_id: String,
+    entry_id: i64
+) -> Result<Vec<Attachment>, XPassError> {
+    let cipher = sessions.cipher(&session_id, user_id)?;
+    let conn = db.conn()?;
+
+    if !keys::is_vault_key(&conn, user_id, &cipher)? {
+        return Ok(vec![]);
+    }
+    list(&conn, user_id, entry_id, &cipher)
+}
+
+/// Lets the user pick a file and attaches it to an entry. Returns None when
+/// the dialog is cancelled.
+#[tauri::command]
+pub async fn add_attachment(
+    app: AppHandle,
+    db: State<'_, Database>,
+    sessions: State<'_, SessionStore>,
+    user_id: i64,
+    session_id: String,
+    entry_id: i64
+) -> Result<Option<Attachment>, XPassError> {
+    let cipher = sessions.cipher(&session_id, user_id)?;
+
+    let Some(picked) = app.dialog().file().blocking_pick_file() else {
+        return Ok(None);
+    };
+    let path = picked.into_path().map_err(XPassError::internal)?;
+    let name = path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "attachment".to_string());
+
+    let mut conn = db.conn()?;
+    // Fake pseudo-mode entries and pseudo sessions take the file and keep
+    // nothing, like an attach that went through
+    if entry_id < 0 || !keys::is_vault_key(&conn, user_id, &cipher)? {
+        return Ok(None);
+    }
+
+    let file = File::open(&path)?;
+    let size = file.metadata()?.len();
+
+    let tx = conn.transaction()?;
+    let service = entry_service(&tx, user_id, entry_id)?;
+    let attachment = store(&tx, user_id, entry_id, &name.into(), size, BufReader::new(file), &cipher)?;
+
+    crate::activity_logger::log_activity(
+        &tx,
+        user_id,
+        "attachment_added",
+        &format!("Attached a file ({} bytes) to entry for service: {}", size, service)
+    )?;
+    tx.commit()?;
+
+    info!("Attachment {} added to entry {} for user_id {}", attachment.id, entry_id, user_id);
+    Ok(Some(attachment))
+}
+
+/// Decrypts an attachment to a location the user picks. Returns false when
+/// the dialog is cancelled.
+#[tauri::command]
+pub async fn save_attachment(
+    app: AppHandle,
+    db: State<'_, Database>,
+    sessions: State<'_, SessionStore>,
+    user_id: i64,
+    session_id: String,
+    attachment_id: i64
+) -> Result<bool, XPassError> {
+    let cipher = sessions.cipher(&session_id, user_id)?;
+
+    // The database stays unlocked for other commands while the dialog is open
+    let name = {
+        let conn = db.conn()?;
+        if !keys::is_vault_key(&conn, user_id, &cipher)? {
+            return Err(XPassError::NotFound { what: "Attachment" });
+        }
+        load(&conn, user_id, attachment_id, &cipher)?.name
+    };
+
+    let Some(picked) = app.dialog().file().set_file_name(name.expose()).blocking_save_file() else {
+        return Ok(false);
+    };
+    let path = picked.into_path().map_err(XPassError::internal)?;
+
+    let conn = db.conn()?;
+    let attachment = load(&conn, user_id, attachment_id, &cipher)?;
+
+    let mut writer = BufWriter::new(File::create(&path)?);
+    let written = read_chunks(&conn, &attachment, &cipher, |chunk| Ok(writer.write_all(chunk)?))
+        .and_then(|_| Ok(writer.flush()?));
+    if let Err(e) = written {
+        // Never leave a truncated copy behind
+        drop(writer);
+        std::fs::remove_file(&path).ok();
+        return Err(e);
+    }
+
+    let service = entry_service(&conn, user_id, attachment.entry_id).unwrap_or_default();
+    crate::activity_logger::log_activity(
+        &conn,
+        user_id,
+        "attachment_saved",
+        &format!("Saved an attached file ({} bytes) of entry for service: {}", attachment.size, service)
+    )?;
+    Ok(true)
+}
+
+/// Deletes an attachment
+#[tauri::command]
+pub fn delete_attachment(
+    db: State<'_, Database>,
+    sessions: State<'_, SessionStore>,
+    user_id: i64,
+    session_id: String,
+    attachment_id: i64
+) -> Result<(), XPassError> {
+    let cipher = sessions.cipher(&session_id, user_id)?;
+    let conn = db.conn()?;
+
+    if !keys::is_vault_key(&conn, user_id, &cipher)? {
+        return Err(XPassError::NotFound { what: "Attachment" });
+    }
+
+    let (entry_id, size): (i64, i64) = conn.query_row(
+        "DELETE FROM attachments WHERE id = ?1 AND user_id = ?2 RETURNING entry_id, size",
+        params![attachment_id, user_id],
+        |row| Ok((row.get(0)?, row.get(1)?))
+    ).optional()?.ok_or(XPassError::NotFound { what: "Attachment" })?;
+
+    let service = entry_service(&conn, user_id, entry_id).unwrap_or_default();
+    crate::activity_logger::log_activity(
+        &conn,
+        user_id,
+        "attachment_deleted",
+        &format!("Deleted an attached file ({} bytes) of entry for service: {}", size, service)
+    )?;
+    Ok(())
+}
+
+/// Space used by attachments and the limits that apply
+#[tauri::command]
+pub fn get_attachment_usage(
+    db: State<'_, Database>,
+    sessions: State<'_, SessionStore>,
+    user_id: i64,
+    session_id: String
+) -> Result<AttachmentUsage, XPassError> {
+    let cipher = sessions.cipher(&session_id, user_id)?;
+    let conn = db.conn()?;
+
+    let used = if keys::is_vault_key(&conn, user_id, &cipher)? {
+        used_bytes(&conn, user_id)?
+    } else {
+        0
+    };
+    Ok(AttachmentUsage { used, max_file_size: MAX_FILE_SIZE, max_vault_size: MAX_VAULT_SIZE })
+}
@@ -15,20 +15,61 @@
 use rusqlite::{Connection, params};
 use serde::{Serialize, Deserialize};
 use aes_gcm::{
-    aead::{Aead, AeadCore, KeyInit},
+    aead::{Aead, AeadCore},
     Aes256Gcm,
     Nonce,
 };
 use base64::{engine::general_purpose, Engine as _};
 use chrono::Utc;
-use argon2::Argon2;
+use tauri::State;
+use crate::attachments::{self, BackupAttachment};
+use crate::database::Database;
+use crate::error::XPassError;
+use crate::fields::{self, CustomField};
+use crate::folders;
+use crate::history::{self, RevisionData};
+use crate::kdf::{self, KdfParams};
+use crate::keys;
+use crate::kinds::{self, Details, EntryKind};
+use crate::secrets::{SecretBytes, SecretString};
+use crate::session::SessionStore;
+use crate::tags;
+use crate::urls::{self, EntryUrl};
+
+/// v1 backups are encrypted with the key derived from the password and
+/// `salt`; v2 backups are encrypted with the vault data key, which is stored
+/// in the backup wrapped by the password KEK (`salt` is the KEK salt)
+const BACKUP_VERSION: &str = "2.0";
+const LEGACY_BACKUP_VERSION: &str = "1.0";
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct BackupEntry {
     pub service: String,
     pub login: String,
-    pub password: String,
-    pub note: String,
+    pub password: SecretString,
+    pub note: SecretString,
+    #[serde(default)]
+    pub kind: EntryKind,
+    #[serde(default)]
+    pub details: Details,
+    #[serde(default)]
+    pub fields: Vec<CustomField>,
+    #[serde(default)]
+    pub urls: Vec<EntryUrl>,
+    /// Folder names from the top level down, empty if the entry is unfiled
+    #[serde(default)]
+    pub folder: Vec<SecretString>,
+    #[serde(default)]
+    pub tags: Vec<SecretString>,
+    #[serde(default)]
+    pub attachments: Vec<BackupAttachment>,
+    /// Previous versions, newest first; absent in backups made before entry history
+    #[serde(default)]
+    pub history: Vec<RevisionData>,
+    /// When the password last changed; absent in older backups, where the
+    /// import date is used
+    #[serde(default)]
+    pub password_changed_at: Option<String>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -39,144 +80,177 @@ pub struct EncryptedBackup {
     pub salt: String,
     pub encrypted_data: String,
     pub nonce: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wrapped_key: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_nonce: Option<String>,
+    /// Argon2 parameters for `salt`, the legacy defaults if absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kdf: Option<KdfParams>,
 }
 
 #[tauri::command]
-pub fn export_vault_encrypted(user_id: i64, username: String, enc_key: String) -> Result<String, String> {
-    let conn = crate::password_manager::get_conn().map_err(|e| e.to_string())?;
-    
-    // ✅ Get user's salt to include in the backup
-    let user_salt: String = conn.query_row(
-        "SELECT salt FROM users WHERE id = ?1",
-        params![user_id],
-        |row| row.get(0)
-    ).map_err(|_| "User not found".to_string())?;
-    
-    // Get all user's entries
-    let entries = get_user_entries(&conn, user_id, &enc_key)?;
+pub fn export_vault_encrypted(
+    db: State<'_, Database>,
+    sessions: State<'_, SessionStore>,
+    user_id: i64,
+    username: String,
+    session_id: String
+) -> Result<String, XPassError> {
+    let cipher = sessions.cipher(&session_id, user_id)?;
+    let conn = db.conn()?;
+    
+    // ✅ Get the wrapped vault key to include in the backup
+    let keys::StoredKeys { salt: user_salt, wrapped_dek, dek_nonce, kek_salt } = keys::stored_keys(&conn, user_id)?;
+    
+    let kek_params = kdf::user_params(&conn, user_id)?;
+    
+    // Get all user's entries; a pseudo session exports what it sees. Its
+    // key is random, so the file looks like any other backup but only
+    // ever opens as a wrong password.
+    let is_pseudo = !keys::is_vault_key(&conn, user_id, &cipher)?;
+    let entries = if is_pseudo {
+        pseudo_backup_entries(&conn, user_id)
+    } else {
+        get_user_entries(&conn, user_id, &cipher)?
+    };
     
     // Serialize to JSON
-    let json_data = serde_json::to_string(&entries).map_err(|e| e.to_string())?;
+    let json_data = SecretBytes::from(serde_json::to_vec(&entries).map_err(XPassError::internal)?);
     
     // Encrypt the data
-    let key_bytes = general_purpose::STANDARD.decode(&enc_key).map_err(|e| e.to_string())?;
-    if key_bytes.len() != 32 {
-        return Err("Invalid key length".to_string());
-    }
-    
-    let key = aes_gcm::Key::<Aes256Gcm>::from_slice(&key_bytes);
-    let cipher = Aes256Gcm::new(key);
     let nonce = Aes256Gcm::generate_nonce(&mut rand::thread_rng());
     
-    let encrypted = cipher.encrypt(&nonce, json_data.as_bytes())
-        .map_err(|_| "Encryption failed".to_string())?;
-    
-    let backup = EncryptedBackup {
-        version: "1.0".to_string(),
-        timestamp: Utc::now().to_rfc3339(),
-        username,
-        salt: user_salt,
-        encrypted_data: general_purpose::STANDARD.encode(&encrypted),
-        nonce: general_purpose::STANDARD.encode(&nonce),
+    let encrypted = cipher.encrypt(&nonce, json_data.expose())
+        .map_err(|_| XPassError::EncryptionFailed)?;
+    
+    let backup = match (wrapped_dek, dek_nonce, kek_salt) {
+        (Some(wrapped_dek), Some(dek_nonce), Some(kek_salt)) => EncryptedBackup {
+            version: BACKUP_VERSION.to_string(),
+            timestamp: Utc::now().to_rfc3339(),
+            username,
+            salt: kek_salt,
+            encrypted_data: general_purpose::STANDARD.encode(&encrypted),
+            nonce: general_purpose::STANDARD.encode(nonce),
+            wrapped_key: Some(general_purpose::STANDARD.encode(wrapped_dek)),
+            key_nonce: Some(general_purpose::STANDARD.encode(dek_nonce)),
+            kdf: Some(kek_params),
+        },
+        // Not migrated to a wrapped key yet: the session key is still the
+        // password-derived one, which is exactly the v1 format
+        _ => EncryptedBackup {
+            version: LEGACY_BACKUP_VERSION.to_string(),
+            timestamp: Utc::now().to_rfc3339(),
+            username,
+            salt: user_salt,
+            encrypted_data: general_purpose::STANDARD.encode(&encrypted),
+            nonce: general_purpose::STANDARD.encode(nonce),
+            wrapped_key: None,
+            key_nonce: None,
+            kdf: None,
+        },
     };
     
     // Log the export
-    crate::activity_logger::log_activity(
-        &conn,
-        user_id,
-        "vault_exported",
-        &format!("Vault exported with {} entries", entries.len())
-    ).ok();
+    if !is_pseudo {
+        crate::activity_logger::log_activity(
+            &conn,
+            user_id,
+            "vault_exported",
+            &format!("Vault exported with {} entries", entries.len())
+        ).ok();
+    }
     
-    serde_json::to_string_pretty(&backup).map_err(|e| e.to_string())
+    serde_json::to_string_pretty(&backup).map_err(XPassError::internal)
 }
 
 #[tauri::command]
 pub fn import_vault_with_password(
+    db: State<'_, Database>,
+    sessions: State<'_, SessionStore>,
     user_id: i64,
-    current_user_password: String, 
-    backup_password: String,     
+    session_id: String,
+    current_user_password: SecretString, 
+    backup_password: SecretString,     
     backup_json: String,
     merge: bool
-) -> Result<usize, String> {
-    let conn = crate::password_manager::get_conn().map_err(|e| e.to_string())?;
-    
-    // 1. Get salt of the current (new) user
-    let (current_salt_str, _): (String, String) = conn.query_row(
-        "SELECT salt, hash FROM users WHERE id = ?1",
-        params![user_id],
-        |row| Ok((row.get(0)?, row.get(1)?))
-    ).map_err(|_| "User not found".to_string())?;
-    
-    // 2. Generate encryption key from the NEW user's password
-    let current_salt = argon2::password_hash::SaltString::from_b64(&current_salt_str)
-        .map_err(|_| "Invalid salt")?;
-    let mut current_enc_key_material = [0u8; 32];
-    let argon2 = Argon2::default();
-    argon2
-        .hash_password_into(
-            current_user_password.as_bytes(), 
-            current_salt.as_str().as_bytes(), 
-            &mut current_enc_key_material
-        )
-        .map_err(|e| e.to_string())?;
-    let current_enc_key = general_purpose::STANDARD.encode(current_enc_key_material);
-    
-    // 3. Parse backup
+) -> Result<usize, XPassError> {
+    let session_cipher = sessions.cipher(&session_id, user_id)?;
+    let mut conn = db.conn()?;
+    
+    // 1. Check the password the session was opened with. A pseudo session
+    // goes through the same steps but nothing is written.
+    let is_pseudo = !keys::is_vault_key(&conn, user_id, &session_cipher)?;
+    if is_pseudo {
+        crate::password_manager::check_pseudo_password(&conn, user_id, &current_user_password)?;
+    } else {
+        crate::password_manager::check_master_password(&conn, user_id, &current_user_password)?;
+    }
+    
+    // 2. Parse backup
     let backup: EncryptedBackup = serde_json::from_str(&backup_json)
-        .map_err(|e| format!("Invalid backup format: {}", e))?;
-    
-    // 4. ✅ Use salt from backup to generate decryption key
-    let backup_salt = argon2::password_hash::SaltString::from_b64(&backup.salt)
-        .map_err(|_| "Invalid backup salt".to_string())?;
-    
-    let mut backup_enc_key_material = [0u8; 32];
-    argon2
-        .hash_password_into(
-            backup_password.as_bytes(), 
-            backup_salt.as_str().as_bytes(), 
-            &mut backup_enc_key_material
-        )
-        .map_err(|e| e.to_string())?;
-    let backup_enc_key = general_purpose::STANDARD.encode(backup_enc_key_material);
-    
-    // 5. Decrypt backup using OLD account password
-    let key_bytes = general_purpose::STANDARD.decode(&backup_enc_key)
-        .map_err(|e| e.to_string())?;
-    if key_bytes.len() != 32 {
-        return Err("Invalid key length".to_string());
+        .map_err(|e| XPassError::InvalidBackup(e.to_string()))?;
+    
+    // 3. ✅ Use salt from backup to get the backup key
+    let backup_kdf = backup.kdf.unwrap_or(KdfParams::LEGACY);
+    let mut backup_key = keys::derive_key(&backup_password, &backup.salt, &backup_kdf)?;
+    if backup.version != LEGACY_BACKUP_VERSION {
+        let wrapped_key = backup.wrapped_key.as_deref()
+            .and_then(|k| general_purpose::STANDARD.decode(k).ok())
+            .ok_or(XPassError::InvalidBackup("missing or malformed wrapped key".to_string()))?;
+        let key_nonce = backup.key_nonce.as_deref()
+            .and_then(|n| general_purpose::STANDARD.decode(n).ok())
+            .ok_or(XPassError::InvalidBackup("missing or malformed wrapped key".to_string()))?;
+        
+        backup_key = keys::unwrap_key(&backup_key, &wrapped_key, &key_nonce)
+            .map_err(|_| XPassError::WrongBackupPassword)?;
     }
     
-    let key = aes_gcm::Key::<Aes256Gcm>::from_slice(&key_bytes);
-    let cipher = Aes256Gcm::new(key);
+    // 4. Decrypt backup using OLD account password
+    let cipher = backup_key.cipher();
+    drop(backup_key);
     
     let encrypted_data = general_purpose::STANDARD.decode(&backup.encrypted_data)
-        .map_err(|_| "Invalid encrypted data".to_string())?;
+        .map_err(|_| XPassError::InvalidBackup("malformed encrypted data".to_string()))?;
     let nonce_bytes = general_purpose::STANDARD.decode(&backup.nonce)
-        .map_err(|_| "Invalid nonce".to_string())?;
+        .ok()
+        .filter(|n| n.len() == 12)
+        .ok_or(XPassError::InvalidBackup("malformed nonce".to_string()))?;
     let nonce = Nonce::from_slice(&nonce_bytes);
     
-    let decrypted = cipher.decrypt(nonce, encrypted_data.as_ref())
-        .map_err(|_| "Decryption failed - wrong backup password or corrupted backup".to_string())?;
+    let decrypted = SecretBytes::from(
+        cipher.decrypt(nonce, encrypted_data.as_ref())
+            .map_err(|_| XPassError::WrongBackupPassword)?
+    );
     
-    let json_str = String::from_utf8(decrypted)
-        .map_err(|_| "Invalid UTF-8 data".to_string())?;
+    let entries: Vec<BackupEntry> = serde_json::from_slice(decrypted.expose())
+        .map_err(|e| XPassError::InvalidBackup(e.to_string()))?;
+    drop(decrypted);
     
-    let entries: Vec<BackupEntry> = serde_json::from_str(&json_str)
-        .map_err(|e| format!("Invalid backup data: {}", e))?;
+    // 5. Check every entry the way add_entry does, before anything is deleted
+    for entry in &entries {
+        validate_entry(entry)?;
+    }
     
-    // 6. Remove existing entries if not merging
+    if is_pseudo {
+        return Ok(entries.len());
+    }
+    // Unlock the vault key of the current (new) user
+    let current_cipher = keys::unlock_dek(&conn, user_id, &current_user_password)?.cipher();
+    
+    // 6. Remove existing entries if not merging. Steps 6-7 run in one
+    // transaction: a failed entry leaves the vault as it was.
+    let tx = conn.transaction()?;
     if !merge {
-        conn.execute("DELETE FROM entries WHERE user_id = ?1", params![user_id])
-            .map_err(|e| e.to_string())?;
+        tx.execute("DELETE FROM entries WHERE user_id = ?1", params![user_id])?;
     }
     
     // 7. Import and RE-ENCRYPT with the NEW user's key
     let mut imported = 0;
     for entry in &entries {
         if merge {
-            let exists: bool = conn.query_row(
-                "SELECT EXISTS(SELECT 1 FROM entries WHERE user_id = ?1 AND service = ?2 AND login = ?3)",
+            let exists: bool = tx.query_row(
+                "SELECT EXISTS(SELECT 1 FROM entries WHERE user_id = ?1 AND service = ?2 AND login = ?3 AND deleted_at IS NULL)",
                 params![user_id, &entry.service, &entry.login],
                 |row| row.get(0)
             ).unwrap_or(false);
@@ -187,67 +261,139 @@ pub fn import_vault_with_password(
         }
         
         // ✅ Encrypt using the NEW user's key
-        add_entry_internal(&conn, user_id, entry, &current_enc_key)?;
+        let entry_id = add_entry_internal(&tx, user_id, entry, &current_cipher)?;
+        history::import_revisions(&tx, user_id, entry_id, &entry.history, &current_cipher)?;
         imported += 1;
     }
     
     crate::activity_logger::log_activity(
-        &conn,
+        &tx,
         user_id,
         "vault_imported",
         &format!("Imported {} entries (merge: {})", imported, merge)
     ).ok();
+    tx.commit()?;
     
     Ok(imported)
 }
 
 // Helper functions remain unchanged
 
-fn get_user_entries(conn: &Connection, user_id: i64, enc_key: &str) -> Result<Vec<BackupEntry>, String> {
-    let key_bytes = general_purpose::STANDARD.decode(enc_key).map_err(|e| e.to_string())?;
-    let key = aes_gcm::Key::<Aes256Gcm>::from_slice(&key_bytes);
-    let cipher = Aes256Gcm::new(key);
-    
+/// The checks add_entry runs, plus the folder and tag names
+fn validate_entry(entry: &BackupEntry) -> Result<(), XPassError> {
+    kinds::validate(entry.kind, &entry.details)?;
+    fields::validate(&entry.fields)?;
+    urls::validate(&entry.urls)?;
+    for name in entry.folder.iter().chain(&entry.tags) {
+        folders::clean_name(name)?;
+    }
+    Ok(())
+}
+
+/// The fake entries a pseudo session sees, in backup form
+fn pseudo_backup_entries(conn: &Connection, user_id: i64) -> Vec<BackupEntry> {
+    crate::password_manager::pseudo_entries(conn, user_id)
+        .into_iter()
+        .map(|entry| BackupEntry {
+            service: entry.service,
+            login: entry.login,
+            password: entry.password,
+            note: entry.note,
+            kind: entry.kind,
+            details: entry.details,
+            fields: entry.fields,
+            urls: entry.urls,
+            folder: Vec::new(),
+            tags: Vec::new(),
+            attachments: Vec::new(),
+            history: Vec::new(),
+            password_changed_at: None,
+        })
+        .collect()
+}
+
+fn get_user_entries(conn: &Connection, user_id: i64, cipher: &Aes256Gcm) -> Result<Vec<BackupEntry>, XPassError> {
     let mut stmt = conn.prepare(
-        "SELECT service, login, enc_password, password_nonce, enc_note, note_nonce 
-         FROM entries WHERE user_id = ?1"
-    ).map_err(|e| e.to_string())?;
+        "SELECT id, service, login, enc_password, password_nonce, enc_note, note_nonce, folder_id, kind, enc_details, details_nonce,
+                password_changed_at
+         FROM entries WHERE user_id = ?1 AND deleted_at IS NULL"
+    )?;
+    
+    // Folders and tags travel by name, their ids mean nothing in another vault
+    let all_folders = folders::load_folders(conn, user_id, cipher)?;
+    let all_tags = tags::load_tags(conn, user_id, cipher)?;
     
     let rows = stmt.query_map(params![user_id], |row| {
         Ok((
-            row.get::<_, String>(0)?,
+            row.get::<_, i64>(0)?,
             row.get::<_, String>(1)?,
-            row.get::<_, Vec<u8>>(2)?,
+            row.get::<_, String>(2)?,
             row.get::<_, Vec<u8>>(3)?,
             row.get::<_, Vec<u8>>(4)?,
             row.get::<_, Vec<u8>>(5)?,
+            row.get::<_, Vec<u8>>(6)?,
+            row.get::<_, Option<i64>>(7)?,
+            row.get::<_, String>(8)?,
+            row.get::<_, Option<Vec<u8>>>(9)?,
+            row.get::<_, Option<Vec<u8>>>(10)?,
+            row.get::<_, Option<String>>(11)?,
         ))
-    }).map_err(|e| e.to_string())?;
+    })?;
     
     let mut entries = Vec::new();
     for row_result in rows {
-        let (service, login, enc_password, password_nonce, enc_note, note_nonce) = 
-            row_result.map_err(|e| e.to_string())?;
+        let (id, service, login, enc_password, password_nonce, enc_note, note_nonce, folder_id, kind, enc_details, details_nonce,
+             password_changed_at) = row_result?;
         
         // Decrypt password
         let password_nonce_slice = Nonce::from_slice(&password_nonce);
         let dec_password = cipher.decrypt(password_nonce_slice, enc_password.as_ref())
-            .map_err(|_| "Decryption failed".to_string())?;
-        let password = String::from_utf8(dec_password)
-            .map_err(|_| "Invalid UTF-8".to_string())?;
+            .map_err(|_| XPassError::DecryptionFailed)?;
+        let password = SecretString::from_utf8(dec_password.into())
+            .ok_or(XPassError::DecryptionFailed)?;
         
         // Decrypt note
         let note_nonce_slice = Nonce::from_slice(&note_nonce);
         let dec_note = cipher.decrypt(note_nonce_slice, enc_note.as_ref())
-            .map_err(|_| "Decryption failed".to_string())?;
-        let note = String::from_utf8(dec_note)
-            .map_err(|_| "Invalid UTF-8".to_string())?;
+            .map_err(|_| XPassError::DecryptionFailed)?;
+        let note = SecretString::from_utf8(dec_note.into())
+            .ok_or(XPassError::DecryptionFailed)?;
+        
+        // Decrypt kind details
+        let details = kinds::open(cipher, enc_details.as_deref(), details_nonce.as_deref())?;
+        
+        let fields = fields::load_fields(conn, id, cipher)?;
+        let urls = urls::load_urls(conn, id, cipher)?;
+        let folder = folder_id
+            .map(|folder_id| folders::path_of(&all_folders, folder_id))
+            .unwrap_or_default();
+        let tag_ids = tags::entry_tag_ids(conn, id)?;
+        let tags = all_tags.iter()
+            .filter(|tag| tag_ids.contains(&tag.id))
+            .map(|tag| tag.name.clone())
+            .collect();
+        let attachments = attachments::export_entry(conn, user_id, id, cipher)?;
+        
+        // Previous versions of the entry
+        let history = history::list_revisions(conn, user_id, id, cipher)?
+            .into_iter()
+            .map(|revision| revision.data)
+            .collect();
         
         entries.push(BackupEntry {
             service,
             login,
             password,
             note,
+            kind: EntryKind::parse(&kind),
+            details,
+            fields,
+            urls,
+            folder,
+            tags,
+            attachments,
+            history,
+            password_changed_at,
         });
     }
     
@@ -258,25 +404,25 @@ fn add_entry_internal(
     conn: &Connection,
     user_id: i64,
     entry: &BackupEntry,
-    enc_key: &str
-) -> Result<(), String> {
-    let key_bytes = general_purpose::STANDARD.decode(enc_key).map_err(|e| e.to_string())?;
-    let key = aes_gcm::Key::<Aes256Gcm>::from_slice(&key_bytes);
-    let cipher = Aes256Gcm::new(key);
-    
+    cipher: &Aes256Gcm
+) -> Result<i64, XPassError> {
     // Encrypt password
     let password_nonce = Aes256Gcm::generate_nonce(&mut rand::thread_rng());
-    let enc_password = cipher.encrypt(&password_nonce, entry.password.as_bytes())
-        .map_err(|_| "Encryption failed".to_string())?;
+    let enc_password = cipher.encrypt(&password_nonce, entry.password.expose().as_bytes())
+        .map_err(|_| XPassError::EncryptionFailed)?;
     
     // Encrypt note
     let note_nonce = Aes256Gcm::generate_nonce(&mut rand::thread_rng());
-    let enc_note = cipher.encrypt(&note_nonce, entry.note.as_bytes())
-        .map_err(|_| "Encryption failed".to_string())?;
+    let enc_note = cipher.encrypt(&note_nonce, entry.note.expose().as_bytes())
+        .map_err(|_| XPassError::EncryptionFailed)?;
+    
+    // Encrypt kind details
+    let (enc_details, details_nonce) = kinds::seal(cipher, &entry.details)?;
     
     conn.execute(
-        "INSERT INTO entries (user_id, service, login, enc_password, password_nonce, enc_note, note_nonce)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        "INSERT INTO entries (user_id, service, login, enc_password, password_nonce, enc_note, note_nonce, kind, enc_details, details_nonce,
+                              password_changed_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
         params![
             user_id,
             &entry.service,
@@ -284,9 +430,24 @@ fn add_entry_internal(
             &*enc_password,
             &*password_nonce,
             &*enc_note,
-            &*note_nonce
+            &*note_nonce,
+            entry.kind.as_str(),
+            enc_details,
+            details_nonce,
+            entry.password_changed_at.clone().unwrap_or_else(|| Utc::now().to_rfc3339())
         ],
-    ).map_err(|e| e.to_string())?;
+    )?;
     
-    Ok(())
+    let entry_id = conn.last_insert_rowid();
+    fields::save_fields(conn, entry_id, &entry.fields, cipher)?;
+    urls::save_urls(conn, entry_id, &entry.urls, cipher)?;
+    
+    if !entry.folder.is_empty() {
+        let folder_id = folders::ensure_path(conn, user_id, &entry.folder, cipher)?;
+        conn.execute("UPDATE entries SET folder_id = ?1 WHERE id = ?2", params![folder_id, entry_id])?;
+    }
+    tags::assign_names(conn, user_id, entry_id, &entry.tags, cipher)?;
+    attachments::import_entry(conn, user_id, entry_id, &entry.attachments, cipher)?;
+    
+    Ok(entry_id)
 }
\ No newline at end of file
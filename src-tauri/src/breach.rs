@@ -0,0 +1,629 @@
+// ============================================================================
+// X-PASS Password Manager
+// Copyright (C) 2026 ar3love
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See LICENSE.
+// ============================================================================
+
+// src-tauri/src/breach.rs
+//
+// Offline breached-password checks against Have I Been Pwned's Pwned
+// Passwords, without network access. A downloaded SHA-1 dataset is imported
+// once into a blocked Bloom filter in the app data directory, about 1.5
+// bytes per breached password (~1.5 GB for the full set) and roughly 0.5%
+// false positives. Vault passwords are hashed and looked up in the filter
+// on disk, one 64-byte block read each; nothing is ever sent anywhere.
+//
+// Accepted datasets, ordered by hash as the official downloader writes them:
+//   - one file of `HASH:COUNT` lines, 40 hex digits per hash
+//   - a directory of range files `XXXXX.txt` holding `SUFFIX:COUNT` lines
+//   - a filter file built by this module, e.g. on another machine
+//
+// Because the input is sorted and a hash's block follows from its leading
+// bits, blocks are completed in order and written as the import streams,
+// so memory use stays flat. Progress is emitted as `breach-import-progress`.
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use chrono::{DateTime, Utc};
+use log::info;
+use serde::Serialize;
+use sha1::{Digest, Sha1};
+use tauri::{AppHandle, Emitter, State};
+use zeroize::Zeroizing;
+
+use crate::database::Database;
+use crate::error::XPassError;
+use crate::keys;
+use crate::password_manager::{self, Entry};
+use crate::session::SessionStore;
+use crate::vault;
+
+pub const PROGRESS_EVENT: &str = "breach-import-progress";
+const FILTER_FILE: &str = "breach_filter.bin";
+const MAGIC: &[u8; 8] = b"XPBREACH";
+const FORMAT_VERSION: u32 = 1;
+const HEADER_LEN: u64 = 32;
+const BLOCK_BYTES: usize = 64;
+const BLOCK_BITS: u32 = BLOCK_BYTES as u32 * 8;
+/// Filter bits per breached password and bits set per password, for about
+/// 0.5% false positives
+const BITS_PER_HASH: u64 = 12;
+const PROBES: u32 = 8;
+/// Shortest dataset lines: the hash or suffix, ':', one digit and '\n'.
+/// Sizing the filter by them can only overestimate the number of hashes.
+const MIN_FULL_LINE: u64 = 40 + 3;
+const MIN_SUFFIX_LINE: u64 = 35 + 3;
+const PROGRESS_STEP: u64 = 64 * 1024 * 1024;
+
+/// Only one import at a time writes the filter
+static IMPORTING: AtomicBool = AtomicBool::new(false);
+
+pub type Hash = Zeroizing<[u8; 20]>;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct BreachFilterInfo {
+    pub hashes: u64,
+    pub size_bytes: u64,
+    pub imported_at: String,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ImportProgress {
+    pub bytes_read: u64,
+    pub total_bytes: u64,
+    pub hashes: u64,
+}
+
+#[derive(Serialize, Debug)]
+pub struct EntryBreach {
+    pub entry_id: i64,
+    pub compromised: bool,
+    /// Times the password was seen in breaches, only known online
+    pub occurrences: Option<u64>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct BreachReport {
+    /// Entries with a password, all of them listed in `entries`
+    pub checked: usize,
+    pub compromised: usize,
+    pub entries: Vec<EntryBreach>,
+}
+
+impl BreachReport {
+    pub fn new(entries: Vec<EntryBreach>) -> BreachReport {
+        BreachReport {
+            checked: entries.len(),
+            compromised: entries.iter().filter(|entry| entry.compromised).count(),
+            entries,
+        }
+    }
+}
+
+/// The block of a hash, in the order of the hashes
+fn block_index(hash: &[u8; 20], blocks: u64) -> u64 {
+    let lead = u64::from_be_bytes(hash[..8].try_into().unwrap_or_default());
+    ((lead as u128 * blocks as u128) >> 64) as u64
+}
+
+/// Bits of a block a hash sets, taken from hash bits the block index does not use
+fn probe_bits(hash: &[u8; 20]) -> impl Iterator<Item = u32> {
+    let first = u32::from_le_bytes(hash[8..12].try_into().unwrap_or_default());
+    let step = u32::from_le_bytes(hash[12..16].try_into().unwrap_or_default()) | 1;
+    (0..PROBES).map(move |i| first.wrapping_add(i.wrapping_mul(step)) % BLOCK_BITS)
+}
+
+pub fn sha1(password: &str) -> Hash {
+    let mut digest = Sha1::digest(password.as_bytes());
+    let mut hash = Zeroizing::new([0u8; 20]);
+    hash.copy_from_slice(&digest);
+    digest.as_mut_slice().fill(0);
+    hash
+}
+
+fn parse_hex(hex: &str, out: &mut [u8]) -> bool {
+    if hex.len() != out.len() * 2 || !hex.is_ascii() {
+        return false;
+    }
+    for (byte, pair) in out.iter_mut().zip(hex.as_bytes().chunks(2)) {
+        match std::str::from_utf8(pair).ok().and_then(|pair| u8::from_str_radix(pair, 16).ok()) {
+            Some(value) => *byte = value,
+            None => return false,
+        }
+    }
+    true
+}
+
+fn filter_path(app: &AppHandle) -> Result<PathBuf, XPassError> {
+    Ok(vault::app_data_dir(app)?.join(FILTER_FILE))
+}
+
+struct Header {
+    blocks: u64,
+    hashes: u64,
+}
+
+impl Header {
+    fn write(&self, out: &mut impl Write) -> std::io::Result<()> {
+        out.write_all(MAGIC)?;
+        out.write_all(&FORMAT_VERSION.to_le_bytes())?;
+        out.write_all(&PROBES.to_le_bytes())?;
+        out.write_all(&self.blocks.to_le_bytes())?;
+        out.write_all(&self.hashes.to_le_bytes())
+    }
+
+    /// Reads and checks the header of a filter file of `len` bytes
+    fn read(input: &mut impl Read, len: u64) -> Result<Header, XPassError> {
+        let not_a_filter = || XPassError::invalid_input("filter", "not a breach filter file");
+        let mut bytes = [0u8; HEADER_LEN as usize];
+        input.read_exact(&mut bytes).map_err(|_| not_a_filter())?;
+        let word = |at: usize| u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap_or_default());
+        let long = |at: usize| u64::from_le_bytes(bytes[at..at + 8].try_into().unwrap_or_default());
+        if &bytes[..8] != MAGIC {
+            return Err(not_a_filter());
+        }
+        if word(8) != FORMAT_VERSION || word(12) != PROBES {
+            return Err(XPassError::invalid_input("filter", "unsupported breach filter version"));
+        }
+        let header = Header { blocks: long(16), hashes: long(24) };
+        let expected = header.blocks.checked_mul(BLOCK_BYTES as u64).and_then(|n| n.checked_add(HEADER_LEN));
+        if header.blocks == 0 || expected != Some(len) {
+            return Err(not_a_filter());
+        }
+        Ok(header)
+    }
+}
+
+/// Streams sorted hashes into filter blocks
+struct FilterWriter<W: Write + Seek> {
+    out: W,
+    blocks: u64,
+    current: u64,
+    block: [u8; BLOCK_BYTES],
+    hashes: u64,
+}
+
+impl<W: Write + Seek> FilterWriter<W> {
+    fn new(mut out: W, expected_hashes: u64) -> Result<Self, XPassError> {
+        let blocks = (expected_hashes.max(1) * BITS_PER_HASH).div_ceil(BLOCK_BITS as u64);
+        Header { blocks, hashes: 0 }.write(&mut out)?;
+        Ok(FilterWriter { out, blocks, current: 0, block: [0; BLOCK_BYTES], hashes: 0 })
+    }
+
+    fn add(&mut self, hash: &[u8; 20]) -> Result<(), XPassError> {
+        let index = block_index(hash, self.blocks);
+        if index < self.current {
+            return Err(XPassError::invalid_input("dataset", "hashes are not ordered"));
+        }
+        while self.current < index {
+            self.next_block()?;
+        }
+        for bit in probe_bits(hash) {
+            self.block[(bit / 8) as usize] |= 1 << (bit % 8);
+        }
+        self.hashes += 1;
+        Ok(())
+    }
+
+    fn next_block(&mut self) -> std::io::Result<()> {
+        self.out.write_all(&self.block)?;
+        self.block = [0; BLOCK_BYTES];
+        self.current += 1;
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<u64, XPassError> {
+        while self.current < self.blocks {
+            self.next_block()?;
+        }
+        self.out.seek(SeekFrom::Start(0))?;
+        Header { blocks: self.blocks, hashes: self.hashes }.write(&mut self.out)?;
+        self.out.flush()?;
+        Ok(self.hashes)
+    }
+}
+
+/// A filter file opened for lookups
+pub struct BreachFilter {
+    file: File,
+    header: Header,
+}
+
+impl BreachFilter {
+    pub fn open(path: &Path) -> Result<BreachFilter, XPassError> {
+        let mut file = File::open(path)?;
+        let len = file.metadata()?.len();
+        let header = Header::read(&mut file, len)?;
+        Ok(BreachFilter { file, header })
+    }
+
+    pub fn contains(&mut self, hash: &[u8; 20]) -> Result<bool, XPassError> {
+        let index = block_index(hash, self.header.blocks);
+        let mut block = [0u8; BLOCK_BYTES];
+        self.file.seek(SeekFrom::Start(HEADER_LEN + index * BLOCK_BYTES as u64))?;
+        self.file.read_exact(&mut block)?;
+        Ok(probe_bits(hash).all(|bit| block[(bit / 8) as usize] & (1 << (bit % 8)) != 0))
+    }
+}
+
+/// A dataset file and, for range files, the hash prefix its lines leave out
+struct Source {
+    path: PathBuf,
+    prefix: Option<String>,
+}
+
+/// Finds the files of a dataset, in hash order
+fn dataset_sources(path: &Path) -> Result<Vec<Source>, XPassError> {
+    if path.is_file() {
+        return Ok(vec![Source { path: path.to_path_buf(), prefix: None }]);
+    }
+    if !path.is_dir() {
+        return Err(XPassError::NotFound { what: "Dataset" });
+    }
+    let mut sources = Vec::new();
+    for item in fs::read_dir(path)? {
+        let item = item?;
+        let name = item.file_name().to_string_lossy().to_uppercase();
+        let Some(prefix) = name.strip_suffix(".TXT") else {
+            continue;
+        };
+        if prefix.len() == 5 && prefix.chars().all(|c| c.is_ascii_hexdigit()) && item.path().is_file() {
+            sources.push(Source { path: item.path(), prefix: Some(prefix.to_string()) });
+        }
+    }
+    if sources.is_empty() {
+        return Err(XPassError::invalid_input("dataset", "no range files (XXXXX.txt) in the directory"));
+    }
+    sources.sort_by(|a, b| a.prefix.cmp(&b.prefix));
+    Ok(sources)
+}
+
+/// Builds the filter at `out` from a dataset, reporting progress
+fn build_filter(dataset: &Path, out: &Path, progress: impl Fn(ImportProgress)) -> Result<u64, XPassError> {
+    let sources = dataset_sources(dataset)?;
+    let mut total_bytes = 0;
+    for source in &sources {
+        total_bytes += fs::metadata(&source.path)?.len();
+    }
+    let min_line = if sources[0].prefix.is_some() { MIN_SUFFIX_LINE } else { MIN_FULL_LINE };
+
+    let mut writer = FilterWriter::new(BufWriter::new(File::create(out)?), total_bytes / min_line)?;
+    let mut bytes_read = 0;
+    let mut reported = 0;
+    let mut line = String::new();
+    let mut full = String::with_capacity(40);
+    let mut hash = [0u8; 20];
+    for source in &sources {
+        let mut reader = BufReader::new(File::open(&source.path)?);
+        let mut number = 0;
+        loop {
+            line.clear();
+            let read = reader.read_line(&mut line)?;
+            if read == 0 {
+                break;
+            }
+            bytes_read += read as u64;
+            number += 1;
+            let hex = line.split(':').next().unwrap_or_default().trim();
+            if hex.is_empty() {
+                continue;
+            }
+            full.clear();
+            full.push_str(source.prefix.as_deref().unwrap_or_default());
+            full.push_str(hex);
+            if !parse_hex(&full, &mut hash) {
+                let file = source.path.file_name().unwrap_or_default().to_string_lossy();
+                return Err(XPassError::invalid_input("dataset", format!("{}, line {}: not a SHA-1 hash", file, number)));
+            }
+            writer.add(&hash)?;
+
+            if bytes_read - reported >= PROGRESS_STEP {
+                reported = bytes_read;
+                progress(ImportProgress { bytes_read, total_bytes, hashes: writer.hashes });
+            }
+        }
+    }
+    let hashes = writer.finish()?;
+    progress(ImportProgress { bytes_read, total_bytes, hashes });
+    Ok(hashes)
+}
+
+/// Copies a filter file built elsewhere after checking it
+fn copy_filter(source: &Path, out: &Path, progress: impl Fn(ImportProgress)) -> Result<u64, XPassError> {
+    let mut input = File::open(source)?;
+    let total_bytes = input.metadata()?.len();
+    let header = Header::read(&mut input, total_bytes)?;
+    input.seek(SeekFrom::Start(0))?;
+
+    let mut writer = BufWriter::new(File::create(out)?);
+    let mut buffer = vec![0u8; 1024 * 1024];
+    let mut bytes_read = 0;
+    let mut reported = 0;
+    loop {
+        let read = input.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        writer.write_all(&buffer[..read])?;
+        bytes_read += read as u64;
+        if bytes_read - reported >= PROGRESS_STEP {
+            reported = bytes_read;
+            progress(ImportProgress { bytes_read, total_bytes, hashes: 0 });
+        }
+    }
+    writer.flush()?;
+    progress(ImportProgress { bytes_read, total_bytes, hashes: header.hashes });
+    Ok(header.hashes)
+}
+
+fn is_filter_file(path: &Path) -> bool {
+    let mut magic = [0u8; 8];
+    path.is_file() && File::open(path).and_then(|mut file| file.read_exact(&mut magic)).is_ok() && &magic == MAGIC
+}
+
+fn filter_info(path: &Path) -> Result<Option<BreachFilterInfo>, XPassError> {
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let filter = BreachFilter::open(path)?;
+    let metadata = filter.file.metadata()?;
+    Ok(Some(BreachFilterInfo {
+        hashes: filter.header.hashes,
+        size_bytes: metadata.len(),
+        imported_at: DateTime::<Utc>::from(metadata.modified()?).to_rfc3339(),
+    }))
+}
+
+/// Checks entry passwords against the filter, each distinct password once
+pub fn check_entries(filter: &mut BreachFilter, entries: &[Entry]) -> Result<BreachReport, XPassError> {
+    let mut results = Vec::new();
+    let mut known: HashMap<&str, bool> = HashMap::new();
+    for entry in entries.iter().filter(|entry| !entry.password.is_empty()) {
+        let password = entry.password.expose();
+        let compromised = match known.get(password) {
+            Some(&compromised) => compromised,
+            None => {
+                let compromised = filter.contains(&sha1(password))?;
+                known.insert(password, compromised);
+                compromised
+            }
+        };
+        results.push(EntryBreach { entry_id: entry.id, compromised, occurrences: None });
+    }
+    Ok(BreachReport::new(results))
+}
+
+/// Imports a Pwned Passwords dataset (a file or a directory of range files)
+/// or a prebuilt filter, replacing the current filter. Emits
+/// `breach-import-progress` while it runs.
+#[tauri::command]
+pub async fn import_breach_dataset(
+    app: AppHandle,
+    sessions: State<'_, SessionStore>,
+    user_id: i64,
+    session_id: String,
+    path: String
+) -> Result<BreachFilterInfo, XPassError> {
+    sessions.verify(&session_id, user_id)?;
+    let dataset = PathBuf::from(path.trim());
+    if dataset.as_os_str().is_empty() {
+        return Err(XPassError::invalid_input("path", "Dataset path cannot be empty"));
+    }
+    let target = filter_path(&app)?;
+    if IMPORTING.swap(true, Ordering::SeqCst) {
+        return Err(XPassError::invalid_input("dataset", "an import is already running"));
+    }
+
+    let emitter = app.clone();
+    let partial = target.with_extension("partial");
+    let written = partial.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let progress = |progress: ImportProgress| {
+            emitter.emit(PROGRESS_EVENT, progress).ok();
+        };
+        if is_filter_file(&dataset) {
+            copy_filter(&dataset, &written, progress)
+        } else {
+            build_filter(&dataset, &written, progress)
+        }
+    })
+    .await
+    .map_err(XPassError::internal)
+    .and_then(|built| built);
+    IMPORTING.store(false, Ordering::SeqCst);
+
+    // The previous filter stays in use until the new one is complete
+    let hashes = match result {
+        Ok(hashes) => hashes,
+        Err(e) => {
+            fs::remove_file(&partial).ok();
+            return Err(e);
+        }
+    };
+    fs::rename(&partial, &target)?;
+    info!("Breach filter imported with {} hashes", hashes);
+
+    filter_info(&target)?.ok_or(XPassError::NotFound { what: "Breach filter" })
+}
+
+/// The imported filter, None before the first import
+#[tauri::command]
+pub fn get_breach_filter_info(app: AppHandle) -> Result<Option<BreachFilterInfo>, XPassError> {
+    filter_info(&filter_path(&app)?)
+}
+
+/// Removes the imported filter, returns false if there was none
+#[tauri::command]
+pub fn delete_breach_filter(
+    app: AppHandle,
+    sessions: State<'_, SessionStore>,
+    user_id: i64,
+    session_id: String
+) -> Result<bool, XPassError> {
+    sessions.verify(&session_id, user_id)?;
+    let path = filter_path(&app)?;
+    if !path.is_file() {
+        return Ok(false);
+    }
+    fs::remove_file(path)?;
+    Ok(true)
+}
+
+/// Flags every vault password found in the imported breach filter. A pseudo
+/// session gets the check for the entries it is shown.
+#[tauri::command]
+pub fn check_breached_passwords(
+    app: AppHandle,
+    db: State<'_, Database>,
+    sessions: State<'_, SessionStore>,
+    user_id: i64,
+    session_id: String
+) -> Result<BreachReport, XPassError> {
+    let cipher = sessions.cipher(&session_id, user_id)?;
+    let path = filter_path(&app)?;
+    if !path.is_file() {
+        return Err(XPassError::NotFound { what: "Breach filter" });
+    }
+    let mut filter = BreachFilter::open(&path)?;
+
+    let conn = db.conn()?;
+    let entries = if keys::is_vault_key(&conn, user_id, &cipher)? {
+        password_manager::load_entries(&conn, user_id, &cipher)?
+    } else {
+        password_manager::pseudo_entries(&conn, user_id)
+    };
+    drop(conn);
+
+    check_entries(&mut filter, &entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An empty directory under the system temp directory
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("xpass-breach-{}-{}", std::process::id(), name));
+        fs::remove_dir_all(&dir).ok();
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn hex(hash: &[u8]) -> String {
+        hash.iter().map(|b| format!("{:02X}", b)).collect()
+    }
+
+    /// SHA-1 hashes of "password0" and so on, in hash order
+    fn sorted_hashes(count: usize) -> Vec<String> {
+        let mut hashes: Vec<String> = (0..count).map(|i| hex(&*sha1(&format!("password{}", i)))).collect();
+        hashes.sort();
+        hashes
+    }
+
+    fn assert_all_found(filter_path: &Path, hashes: &[String]) {
+        let mut filter = BreachFilter::open(filter_path).unwrap();
+        assert_eq!(filter.header.hashes, hashes.len() as u64);
+        let mut hash = [0u8; 20];
+        for hex in hashes {
+            assert!(parse_hex(hex, &mut hash));
+            assert!(filter.contains(&hash).unwrap(), "{} not found", hex);
+        }
+        let false_positives = (0..1000)
+            .filter(|i| filter.contains(&sha1(&format!("not breached {}", i))).unwrap())
+            .count();
+        assert!(false_positives < 30, "{} false positives", false_positives);
+    }
+
+    #[test]
+    fn single_file_round_trip() {
+        let dir = scratch_dir("single");
+        let hashes = sorted_hashes(2000);
+        let lines: String = hashes.iter().enumerate().map(|(i, hash)| format!("{}:{}\r\n", hash, i + 1)).collect();
+        fs::write(dir.join("pwned.txt"), lines).unwrap();
+
+        let built = build_filter(&dir.join("pwned.txt"), &dir.join("filter.bin"), |_| {}).unwrap();
+        assert_eq!(built, 2000);
+        assert_all_found(&dir.join("filter.bin"), &hashes);
+
+        // A built filter can be imported as is
+        assert!(is_filter_file(&dir.join("filter.bin")));
+        assert_eq!(copy_filter(&dir.join("filter.bin"), &dir.join("copy.bin"), |_| {}).unwrap(), 2000);
+        assert_eq!(fs::read(dir.join("filter.bin")).unwrap(), fs::read(dir.join("copy.bin")).unwrap());
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn range_files_round_trip() {
+        let dir = scratch_dir("ranges");
+        let ranges = dir.join("ranges");
+        fs::create_dir(&ranges).unwrap();
+        let hashes = sorted_hashes(2000);
+        let mut files: std::collections::BTreeMap<&str, String> = Default::default();
+        for hash in &hashes {
+            files.entry(&hash[..5]).or_default().push_str(&format!("{}:3\r\n", &hash[5..]));
+        }
+        for (prefix, lines) in &files {
+            // Lowercase names are accepted too
+            fs::write(ranges.join(format!("{}.txt", prefix.to_lowercase())), lines).unwrap();
+        }
+        fs::write(ranges.join("README.md"), "not a range file").unwrap();
+
+        let built = build_filter(&ranges, &dir.join("filter.bin"), |_| {}).unwrap();
+        assert_eq!(built, 2000);
+        assert_all_found(&dir.join("filter.bin"), &hashes);
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn unordered_or_malformed_datasets_are_rejected() {
+        let dir = scratch_dir("unordered");
+        let mut hashes = sorted_hashes(500);
+        hashes.reverse();
+        let lines: String = hashes.iter().map(|hash| format!("{}:1\n", hash)).collect();
+        fs::write(dir.join("reversed.txt"), lines).unwrap();
+        assert!(build_filter(&dir.join("reversed.txt"), &dir.join("filter.bin"), |_| {}).is_err());
+
+        fs::write(dir.join("malformed.txt"), "not a hash:1\n").unwrap();
+        assert!(build_filter(&dir.join("malformed.txt"), &dir.join("filter.bin"), |_| {}).is_err());
+
+        let empty = dir.join("empty");
+        fs::create_dir(&empty).unwrap();
+        assert!(build_filter(&empty, &dir.join("filter.bin"), |_| {}).is_err());
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn header_rejects_truncated_and_foreign_files() {
+        let dir = scratch_dir("header");
+        let lines: String = sorted_hashes(100).iter().map(|hash| format!("{}:1\n", hash)).collect();
+        fs::write(dir.join("pwned.txt"), lines).unwrap();
+        build_filter(&dir.join("pwned.txt"), &dir.join("filter.bin"), |_| {}).unwrap();
+        let filter = fs::read(dir.join("filter.bin")).unwrap();
+        let read = |bytes: &[u8]| Header::read(&mut &bytes[..], bytes.len() as u64);
+
+        assert!(read(&filter).is_ok());
+        assert!(read(&filter[..filter.len() - 1]).is_err());
+        assert!(read(&filter[..HEADER_LEN as usize - 1]).is_err());
+        let mut grown = filter.clone();
+        grown.extend_from_slice(&[0; BLOCK_BYTES]);
+        assert!(read(&grown).is_err());
+        let mut newer = filter.clone();
+        newer[8..12].copy_from_slice(&(FORMAT_VERSION + 1).to_le_bytes());
+        assert!(read(&newer).is_err());
+        let mut foreign = filter.clone();
+        foreign[..8].copy_from_slice(b"PK\x03\x04\x14\x00\x00\x00");
+        assert!(read(&foreign).is_err());
+        assert!(!is_filter_file(&dir.join("pwned.txt")));
+        fs::remove_dir_all(dir).ok();
+    }
+}
@@ -0,0 +1,448 @@
+// ============================================================================
+// X-PASS Password Manager
+// Copyright (C) 2026 ar3love
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See LICENSE.
+// ============================================================================
+
+// src-tauri/src/breach_api.rs
+//
+// Online breach check through the Pwned Passwords range API, for when the
+// user has switched networking on. Only the first 5 hex digits of a
+// password's SHA-1 leave the machine (k-anonymity); the few hundred hash
+// suffixes that come back are compared locally. Responses are requested
+// with `Add-Padding`, so their size does not hint at the prefix, and the
+// padding suffixes (count 0) are dropped.
+//
+// The endpoint is a vault setting, so a mirror or a local mock server can
+// stand in for the public API; plain http is accepted for loopback
+// addresses only. Fetched ranges are public data and are cached in memory
+// per endpoint and prefix for `cache_ttl_secs`. Nothing is sent while
+// networking is off, and the check stops if it is switched off midway.
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use log::info;
+use reqwest::Client;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use tauri::State;
+use url::{Host, Url};
+use zeroize::Zeroizing;
+
+use crate::breach::{self, BreachReport, EntryBreach, Hash};
+use crate::database::Database;
+use crate::error::XPassError;
+use crate::keys;
+use crate::password_manager;
+use crate::session::SessionStore;
+
+pub const DEFAULT_ENDPOINT: &str = "https://api.pwnedpasswords.com/range/";
+const DEFAULT_CACHE_TTL_SECS: u64 = 24 * 60 * 60;
+const MAX_CACHE_TTL_SECS: u64 = 30 * 24 * 60 * 60;
+const MAX_ENDPOINT_LEN: usize = 2048;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+const PREFIX_LEN: usize = 5;
+const SUFFIX_LEN: usize = 35;
+
+/// Breach counts by hash suffix for one prefix
+type Range = Arc<HashMap<String, u64>>;
+
+struct CachedRange {
+    fetched_at: Instant,
+    range: Range,
+}
+
+lazy_static::lazy_static! {
+    /// Ranges by request URL
+    static ref RANGES: Mutex<HashMap<String, CachedRange>> = Mutex::new(HashMap::new());
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BreachApiSettings {
+    /// The hash prefix is appended to it as is
+    pub endpoint: String,
+    /// How long fetched ranges are reused, 0 to always fetch
+    pub cache_ttl_secs: u64,
+}
+
+impl BreachApiSettings {
+    fn validate(&self) -> Result<(), XPassError> {
+        if self.endpoint.len() > MAX_ENDPOINT_LEN {
+            return Err(XPassError::invalid_input("endpoint", format!("at most {} bytes", MAX_ENDPOINT_LEN)));
+        }
+        let url = Url::parse(&self.endpoint).map_err(|_| XPassError::invalid_input("endpoint", "not a valid URL"))?;
+        if url.query().is_some() || url.fragment().is_some() {
+            return Err(XPassError::invalid_input("endpoint", "must not have a query or fragment"));
+        }
+        let loopback = match url.host() {
+            Some(Host::Domain(domain)) => domain.eq_ignore_ascii_case("localhost"),
+            Some(Host::Ipv4(ip)) => IpAddr::V4(ip).is_loopback(),
+            Some(Host::Ipv6(ip)) => IpAddr::V6(ip).is_loopback(),
+            None => false,
+        };
+        match url.scheme() {
+            "https" => {}
+            "http" if loopback => {}
+            _ => return Err(XPassError::invalid_input("endpoint", "must use https, or http on a loopback address")),
+        }
+        if self.cache_ttl_secs > MAX_CACHE_TTL_SECS {
+            return Err(XPassError::invalid_input("cache_ttl_secs", "must be at most 30 days"));
+        }
+        Ok(())
+    }
+}
+
+impl Default for BreachApiSettings {
+    fn default() -> Self {
+        BreachApiSettings {
+            endpoint: DEFAULT_ENDPOINT.to_string(),
+            cache_ttl_secs: DEFAULT_CACHE_TTL_SECS,
+        }
+    }
+}
+
+/// Current vault settings, the defaults if none were saved
+pub fn load_settings(conn: &Connection) -> Result<BreachApiSettings, XPassError> {
+    let settings = conn.query_row(
+        "SELECT endpoint, cache_ttl_secs FROM breach_api_settings WHERE id = 1",
+        [],
+        |row| Ok(BreachApiSettings { endpoint: row.get(0)?, cache_ttl_secs: row.get(1)? })
+    ).optional()?;
+    Ok(settings.unwrap_or_default())
+}
+
+fn save_settings(conn: &Connection, settings: &BreachApiSettings) -> Result<(), XPassError> {
+    conn.execute(
+        "INSERT INTO breach_api_settings (id, endpoint, cache_ttl_secs, updated_at)
+         VALUES (1, ?1, ?2, ?3)
+         ON CONFLICT(id) DO UPDATE SET endpoint = ?1, cache_ttl_secs = ?2, updated_at = ?3",
+        params![settings.endpoint, settings.cache_ttl_secs, Utc::now().to_rfc3339()],
+    )?;
+    Ok(())
+}
+
+fn network_error(e: reqwest::Error) -> XPassError {
+    XPassError::Network(e.to_string())
+}
+
+fn to_hex(hash: &Hash) -> Zeroizing<String> {
+    let mut hex = Zeroizing::new(String::with_capacity(hash.len() * 2));
+    for byte in hash.iter() {
+        hex.push(char::from_digit((byte >> 4) as u32, 16).unwrap_or('0').to_ascii_uppercase());
+        hex.push(char::from_digit((byte & 0xf) as u32, 16).unwrap_or('0').to_ascii_uppercase());
+    }
+    hex
+}
+
+/// Parses `SUFFIX:COUNT` lines, leaving out padding
+fn parse_range(body: &str) -> Result<HashMap<String, u64>, XPassError> {
+    let unexpected = || XPassError::Network("unexpected response from the breach API".to_string());
+    let mut range = HashMap::new();
+    for line in body.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        let (suffix, count) = line.split_once(':').ok_or_else(unexpected)?;
+        let count: u64 = count.trim().parse().map_err(|_| unexpected())?;
+        if suffix.len() != SUFFIX_LEN || !suffix.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(unexpected());
+        }
+        if count > 0 {
+            range.insert(suffix.to_ascii_uppercase(), count);
+        }
+    }
+    Ok(range)
+}
+
+/// The range of a prefix, from the cache while it is fresh
+async fn range(client: &Client, settings: &BreachApiSettings, prefix: &str) -> Result<Range, XPassError> {
+    let url = format!("{}{}", settings.endpoint, prefix);
+    let ttl = Duration::from_secs(settings.cache_ttl_secs);
+    if let Ok(cache) = RANGES.lock() {
+        if let Some(cached) = cache.get(&url).filter(|cached| cached.fetched_at.elapsed() < ttl) {
+            return Ok(cached.range.clone());
+        }
+    }
+
+    // Networking may have been switched off since the check started
+    if !crate::network_enabled() {
+        return Err(XPassError::NetworkDisabled);
+    }
+    let response = client.get(&url).header("Add-Padding", "true").send().await.map_err(network_error)?;
+    if !response.status().is_success() {
+        return Err(XPassError::Network(format!("the breach API answered {}", response.status())));
+    }
+    let range = Arc::new(parse_range(&response.text().await.map_err(network_error)?)?);
+
+    if let Ok(mut cache) = RANGES.lock() {
+        cache.retain(|_, cached| cached.fetched_at.elapsed() < ttl);
+        if !ttl.is_zero() {
+            cache.insert(url, CachedRange { fetched_at: Instant::now(), range: range.clone() });
+        }
+    }
+    Ok(range)
+}
+
+/// Times each hash (uppercase hex SHA-1) was seen in breaches, in order
+async fn breach_counts(settings: &BreachApiSettings, hashes: &[Zeroizing<String>]) -> Result<Vec<u64>, XPassError> {
+    if !crate::network_enabled() {
+        return Err(XPassError::NetworkDisabled);
+    }
+    let client = Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .redirect(reqwest::redirect::Policy::none())
+        .user_agent(concat!("X-PASS/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .map_err(network_error)?;
+
+    let mut counts = Vec::with_capacity(hashes.len());
+    for hash in hashes {
+        let (prefix, suffix) = hash.split_at(PREFIX_LEN);
+        let range = range(&client, settings, prefix).await?;
+        counts.push(range.get(suffix).copied().unwrap_or(0));
+    }
+    Ok(counts)
+}
+
+/// Returns the online breach check settings
+#[tauri::command]
+pub fn get_breach_api_settings(db: State<'_, Database>) -> Result<BreachApiSettings, XPassError> {
+    let conn = db.conn()?;
+    load_settings(&conn)
+}
+
+/// Replaces the online breach check settings. The endpoint receives hash
+/// prefixes of every user's passwords, so a pseudo session only gets told
+/// the settings were saved.
+#[tauri::command]
+pub fn set_breach_api_settings(
+    db: State<'_, Database>,
+    sessions: State<'_, SessionStore>,
+    user_id: i64,
+    session_id: String,
+    settings: BreachApiSettings
+) -> Result<BreachApiSettings, XPassError> {
+    let cipher = sessions.cipher(&session_id, user_id)?;
+    settings.validate()?;
+
+    let conn = db.conn()?;
+    if !keys::is_vault_key(&conn, user_id, &cipher)? {
+        return Ok(settings);
+    }
+    save_settings(&conn, &settings)?;
+    crate::activity_logger::log_activity(
+        &conn,
+        user_id,
+        "breach_api_settings_changed",
+        &format!("Online breach check endpoint set to {}", settings.endpoint)
+    ).ok();
+
+    info!("Breach API settings updated by user_id {}", user_id);
+    Ok(settings)
+}
+
+/// Checks every vault password against the range API. Refused while
+/// networking is off. A pseudo session gets the check for the entries it
+/// is shown.
+#[tauri::command]
+pub async fn check_breached_passwords_online(
+    db: State<'_, Database>,
+    sessions: State<'_, SessionStore>,
+    user_id: i64,
+    session_id: String
+) -> Result<BreachReport, XPassError> {
+    if !crate::network_enabled() {
+        return Err(XPassError::NetworkDisabled);
+    }
+    let cipher = sessions.cipher(&session_id, user_id)?;
+
+    // The database is not held while requests are in flight
+    let (settings, entries) = {
+        let conn = db.conn()?;
+        let entries = if keys::is_vault_key(&conn, user_id, &cipher)? {
+            password_manager::load_entries(&conn, user_id, &cipher)?
+        } else {
+            password_manager::pseudo_entries(&conn, user_id)
+        };
+        (load_settings(&conn)?, entries)
+    };
+
+    // Each distinct password is hashed and looked up once
+    let mut positions: HashMap<&str, usize> = HashMap::new();
+    let mut hashes: Vec<Zeroizing<String>> = Vec::new();
+    for entry in entries.iter().filter(|entry| !entry.password.is_empty()) {
+        let password = entry.password.expose();
+        if !positions.contains_key(password) {
+            positions.insert(password, hashes.len());
+            hashes.push(to_hex(&breach::sha1(password)));
+        }
+    }
+
+    let counts = breach_counts(&settings, &hashes).await?;
+
+    let results = entries.iter()
+        .filter_map(|entry| {
+            let count = counts[*positions.get(entry.password.expose())?];
+            Some(EntryBreach { entry_id: entry.id, compromised: count > 0, occurrences: Some(count) })
+        })
+        .collect();
+    info!("Online breach check of {} distinct passwords for user_id {}", hashes.len(), user_id);
+    Ok(BreachReport::new(results))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    /// SHA-1 of "password"
+    const PASSWORD_HASH: &str = "5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8";
+
+    lazy_static::lazy_static! {
+        /// The network toggle is global, tests that flip it take turns
+        static ref NETWORK: Mutex<()> = Mutex::new(());
+    }
+
+    fn set_network(enabled: bool) {
+        *crate::NETWORK_ENABLED.lock().unwrap() = enabled;
+    }
+
+    /// A loopback server answering every request with `body`. Returns the
+    /// endpoint to use and the requests it received, head only.
+    fn mock_server(body: &'static str) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = format!("http://{}/range/", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let received = requests.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut head = String::new();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                loop {
+                    let mut line = String::new();
+                    if reader.read_line(&mut line).unwrap() == 0 || line == "\r\n" {
+                        break;
+                    }
+                    head.push_str(&line);
+                }
+                received.lock().unwrap().push(head);
+                write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                ).unwrap();
+            }
+        });
+        (endpoint, requests)
+    }
+
+    fn settings(endpoint: &str, cache_ttl_secs: u64) -> BreachApiSettings {
+        BreachApiSettings { endpoint: endpoint.to_string(), cache_ttl_secs }
+    }
+
+    fn hashes(values: &[&str]) -> Vec<Zeroizing<String>> {
+        values.iter().map(|value| Zeroizing::new(value.to_string())).collect()
+    }
+
+    #[test]
+    fn hex_of_sha1_matches_the_api() {
+        assert_eq!(to_hex(&breach::sha1("password")).as_str(), PASSWORD_HASH);
+    }
+
+    #[test]
+    fn sends_padding_header_and_matches_suffixes() {
+        let _network = NETWORK.lock().unwrap_or_else(|e| e.into_inner());
+        set_network(true);
+        let (endpoint, requests) = mock_server(
+            "1E4C9B93F3F0682250B6CF8331B7EE68FD8:9659365\r\n\
+             0000000000000000000000000000000000A:0\r\n\
+             011053FD0102E94D6AE2F8B83D76FAF94F6:1\r\n"
+        );
+
+        let counts = tauri::async_runtime::block_on(breach_counts(
+            &settings(&endpoint, 0),
+            &hashes(&[PASSWORD_HASH, "5BAA60000000000000000000000000000000000A", "5BAA6011053FD0102E94D6AE2F8B83D76FAF94F6"])
+        )).unwrap();
+        set_network(false);
+
+        // The padding line counts as not breached
+        assert_eq!(counts, vec![9659365, 0, 1]);
+        let requests = requests.lock().unwrap();
+        assert!(requests.iter().all(|head| head.starts_with("GET /range/5BAA6 HTTP/1.1")));
+        assert!(requests.iter().all(|head| head.to_ascii_lowercase().contains("add-padding: true")));
+    }
+
+    #[test]
+    fn padding_lines_are_dropped() {
+        let range = parse_range("0000000000000000000000000000000000A:0\n1E4C9B93F3F0682250B6CF8331B7EE68FD8:3\n").unwrap();
+        assert_eq!(range.len(), 1);
+        assert_eq!(range.get("1E4C9B93F3F0682250B6CF8331B7EE68FD8"), Some(&3));
+        assert!(parse_range("not a range").is_err());
+    }
+
+    #[test]
+    fn cached_ranges_are_not_fetched_again() {
+        let _network = NETWORK.lock().unwrap_or_else(|e| e.into_inner());
+        set_network(true);
+        let (endpoint, requests) = mock_server("1E4C9B93F3F0682250B6CF8331B7EE68FD8:2\r\n");
+        let cached = settings(&endpoint, 60);
+
+        for _ in 0..2 {
+            let counts = tauri::async_runtime::block_on(breach_counts(&cached, &hashes(&[PASSWORD_HASH]))).unwrap();
+            assert_eq!(counts, vec![2]);
+        }
+        assert_eq!(requests.lock().unwrap().len(), 1);
+
+        // Without a cache lifetime every check asks again
+        let uncached = settings(&endpoint, 0);
+        tauri::async_runtime::block_on(breach_counts(&uncached, &hashes(&[PASSWORD_HASH, PASSWORD_HASH]))).unwrap();
+        set_network(false);
+        assert_eq!(requests.lock().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn refused_while_network_is_off() {
+        let _network = NETWORK.lock().unwrap_or_else(|e| e.into_inner());
+        set_network(false);
+        let (endpoint, requests) = mock_server("");
+
+        let result = tauri::async_runtime::block_on(breach_counts(&settings(&endpoint, 0), &hashes(&[PASSWORD_HASH])));
+        assert!(matches!(result, Err(XPassError::NetworkDisabled)));
+        assert!(requests.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn endpoint_must_be_https_or_loopback() {
+        for endpoint in [
+            DEFAULT_ENDPOINT,
+            "https://mirror.example.com/range/",
+            "http://127.0.0.1:8080/range/",
+            "http://localhost/range/",
+            "http://[::1]:8080/",
+        ] {
+            assert!(settings(endpoint, 0).validate().is_ok(), "{} rejected", endpoint);
+        }
+        for endpoint in [
+            "http://example.com/range/",
+            "http://127.0.0.1.example.com/range/",
+            "http://10.0.0.1/range/",
+            "ftp://127.0.0.1/range/",
+            "https://example.com/range/?prefix=",
+            "not a url",
+        ] {
+            assert!(settings(endpoint, 0).validate().is_err(), "{} accepted", endpoint);
+        }
+        assert!(settings(DEFAULT_ENDPOINT, MAX_CACHE_TTL_SECS + 1).validate().is_err());
+    }
+}
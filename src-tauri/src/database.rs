@@ -0,0 +1,401 @@
+// ============================================================================
+// X-PASS Password Manager
+// Copyright (C) 2026 ar3love
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See LICENSE.
+// ============================================================================
+
+// src-tauri/src/database.rs
+//
+// The vault database connection. One connection is opened per active vault,
+// configured and migrated once, and shared by every command through Tauri
+// managed state.
+//
+// Vaults can optionally be encrypted as a whole with SQLCipher. The page key
+// is derived from a master password with Argon2id; its salt is the SQLCipher
+// file salt, i.e. the first 16 bytes of the file, so no side file is needed.
+// An encrypted vault starts out sealed and has to be unlocked before any
+// command can use it.
+use std::fs;
+use std::io::Read;
+use std::ops::{Deref, DerefMut};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use log::{info, warn};
+use rand::RngCore;
+use rusqlite::{params, Connection, ErrorCode};
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::error::XPassError;
+use crate::migrations::{self, SchemaError};
+use crate::secrets::{SecretKey, SecretString};
+
+/// How long a statement waits for a lock held by another connection
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+/// Number of prepared statements kept per connection
+const STATEMENT_CACHE_CAPACITY: usize = 64;
+
+/// Every plaintext SQLite file starts with this header
+const SQLITE_HEADER: &[u8; 16] = b"SQLite format 3\0";
+/// SQLCipher keeps its salt in place of the plaintext header
+const DB_SALT_LEN: usize = 16;
+/// Argon2id cost for the database key (64 MiB, 3 passes, 4 lanes)
+const DB_KDF_M_COST: u32 = 64 * 1024;
+const DB_KDF_T_COST: u32 = 3;
+const DB_KDF_P_COST: u32 = 4;
+
+/// Raw SQLCipher key together with the file salt it was derived with
+pub struct DbKey {
+    key: SecretKey,
+    salt: [u8; DB_SALT_LEN],
+}
+
+impl DbKey {
+    fn derive(password: &str, salt: [u8; DB_SALT_LEN]) -> Result<Self, XPassError> {
+        let params = Params::new(DB_KDF_M_COST, DB_KDF_T_COST, DB_KDF_P_COST, Some(32))?;
+        let argon2 = Argon2::new(Algorithm::Argon2id, Version::V0x13, params);
+
+        let mut key = SecretKey::zeroed();
+        argon2.hash_password_into(password.as_bytes(), &salt, key.expose_mut())?;
+        Ok(DbKey { key, salt })
+    }
+
+    fn generate(password: &str) -> Result<Self, XPassError> {
+        let mut salt = [0u8; DB_SALT_LEN];
+        rand::thread_rng().fill_bytes(&mut salt);
+        Self::derive(password, salt)
+    }
+
+    /// SQLCipher raw key literal: key and salt, hex encoded
+    fn sql_literal(&self) -> SecretString {
+        let hex = SecretString::new(
+            self.key.expose().iter().chain(self.salt.iter())
+                .map(|b| format!("{:02x}", b))
+                .collect()
+        );
+        SecretString::new(format!("x'{}'", hex.expose()))
+    }
+}
+
+/// Returns true if the file exists and is not a plaintext SQLite database
+pub fn is_encrypted_file(path: &Path) -> bool {
+    let mut header = [0u8; 16];
+    match fs::File::open(path).and_then(|mut f| f.read_exact(&mut header)) {
+        Ok(()) => &header != SQLITE_HEADER,
+        // Missing or empty files become fresh plaintext vaults
+        Err(_) => false,
+    }
+}
+
+fn read_file_salt(path: &Path) -> Result<[u8; DB_SALT_LEN], XPassError> {
+    let mut salt = [0u8; DB_SALT_LEN];
+    fs::File::open(path).and_then(|mut f| f.read_exact(&mut salt))?;
+    Ok(salt)
+}
+
+fn is_wrong_key(e: &SchemaError) -> bool {
+    matches!(
+        e,
+        SchemaError::Sqlite(rusqlite::Error::SqliteFailure(f, _)) if f.code == ErrorCode::NotADatabase
+    )
+}
+
+struct OpenVault {
+    path: PathBuf,
+    /// None while an encrypted vault is sealed
+    conn: Option<Connection>,
+    /// Present for encrypted vaults once they are unlocked
+    key: Option<DbKey>,
+}
+
+/// Managed state holding the connection to the active vault
+#[derive(Default)]
+pub struct Database {
+    inner: Mutex<Option<OpenVault>>,
+}
+
+/// Exclusive access to the vault connection for the duration of a command
+pub struct DbConn<'a>(MutexGuard<'a, Option<OpenVault>>);
+
+impl Deref for DbConn<'_> {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        // DbConn is only handed out when a vault is open and unlocked
+        self.0.as_ref().and_then(|v| v.conn.as_ref()).unwrap()
+    }
+}
+
+impl DerefMut for DbConn<'_> {
+    fn deref_mut(&mut self) -> &mut Connection {
+        self.0.as_mut().and_then(|v| v.conn.as_mut()).unwrap()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DatabaseStatus {
+    pub path: Option<String>,
+    pub encrypted: bool,
+    pub unlocked: bool,
+}
+
+/// Opens a connection with the pragmas every vault connection needs
+pub fn open_connection(path: &Path, key: Option<&DbKey>) -> Result<Connection, SchemaError> {
+    let mut conn = Connection::open(path)?;
+
+    if let Some(key) = key {
+        // Must be the first statement on the connection
+        let pragma = SecretString::new(format!("PRAGMA key = \"{}\";", key.sql_literal().expose()));
+        conn.execute_batch(pragma.expose())?;
+        // Fails with SQLITE_NOTADB if the key is wrong
+        conn.query_row("SELECT COUNT(*) FROM sqlite_master", [], |row| row.get::<_, i64>(0))?;
+    }
+
+    // WAL lets readers proceed while a write is in progress
+    conn.query_row("PRAGMA journal_mode = WAL", [], |row| row.get::<_, String>(0))?;
+    conn.execute_batch(
+        "PRAGMA synchronous = NORMAL;
+         PRAGMA foreign_keys = ON;"
+    )?;
+    conn.busy_timeout(BUSY_TIMEOUT)?;
+    conn.set_prepared_statement_cache_capacity(STATEMENT_CACHE_CAPACITY);
+
+    let applied = migrations::run(&mut conn)?;
+    if applied > 0 {
+        info!("Database schema upgraded to v{} ({} migrations)", migrations::LATEST_VERSION, applied);
+    }
+    Ok(conn)
+}
+
+/// Rewrites the open vault with a different key (or none) using
+/// `sqlcipher_export`, then swaps the new file in place of the old one
+fn rewrite_vault(vault: &mut OpenVault, new_key: Option<DbKey>) -> Result<(), XPassError> {
+    let conn = vault.conn.take().ok_or(XPassError::VaultLocked)?;
+
+    let mut tmp_name = vault.path.clone().into_os_string();
+    tmp_name.push(".rewrite");
+    let tmp_path = PathBuf::from(tmp_name);
+    if tmp_path.exists() {
+        fs::remove_file(&tmp_path)?;
+    }
+
+    let key_literal = new_key.as_ref().map(|k| k.sql_literal()).unwrap_or_default();
+    let export = (|| -> rusqlite::Result<()> {
+        conn.execute(
+            "ATTACH DATABASE ?1 AS rewritten KEY ?2",
+            params![tmp_path.to_string_lossy(), key_literal.expose()],
+        )?;
+        conn.query_row("SELECT sqlcipher_export('rewritten')", [], |_| Ok(()))?;
+        conn.execute("DETACH DATABASE rewritten", [])?;
+        // Fold the WAL back so nothing is left next to the old file
+        conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))?;
+        Ok(())
+    })();
+
+    if let Err(e) = export {
+        fs::remove_file(&tmp_path).ok();
+        vault.conn = Some(conn);
+        return Err(XPassError::Database(format!("Failed to rewrite vault: {}", e)));
+    }
+
+    // Close the old file before replacing it
+    drop(conn);
+    for suffix in ["-wal", "-shm"] {
+        let mut side = vault.path.clone().into_os_string();
+        side.push(suffix);
+        fs::remove_file(PathBuf::from(side)).ok();
+    }
+    fs::rename(&tmp_path, &vault.path)?;
+
+    vault.conn = Some(open_connection(&vault.path, new_key.as_ref())?);
+    vault.key = new_key;
+    Ok(())
+}
+
+impl Database {
+    /// Opens (and migrates) the vault at `path`, replacing the current connection.
+    /// Encrypted vaults are only registered and stay sealed until `unlock`.
+    /// On failure the previously open vault stays active.
+    pub fn open(&self, path: &Path) -> Result<(), SchemaError> {
+        let conn = if is_encrypted_file(path) {
+            info!("Vault {} is encrypted, waiting for unlock", path.display());
+            None
+        } else {
+            Some(open_connection(path, None)?)
+        };
+
+        *self.inner.lock().unwrap() = Some(OpenVault {
+            path: path.to_path_buf(),
+            conn,
+            key: None,
+        });
+        info!("Opened vault database {}", path.display());
+        Ok(())
+    }
+
+    /// Unlocks a sealed encrypted vault with the master password it was encrypted with
+    pub fn unlock(&self, password: &str) -> Result<(), XPassError> {
+        let mut guard = self.inner.lock().unwrap();
+        let vault = guard.as_mut().ok_or(XPassError::VaultNotOpen)?;
+        if vault.conn.is_some() {
+            return Ok(());
+        }
+
+        let key = DbKey::derive(password, read_file_salt(&vault.path)?)?;
+        let conn = open_connection(&vault.path, Some(&key)).map_err(|e| {
+            if is_wrong_key(&e) { XPassError::InvalidPassword } else { e.into() }
+        })?;
+
+        vault.conn = Some(conn);
+        vault.key = Some(key);
+        info!("Unlocked encrypted vault {}", vault.path.display());
+        Ok(())
+    }
+
+    /// Closes the connection of an encrypted vault and forgets its key.
+    /// Plaintext vaults stay open.
+    pub fn seal(&self) {
+        let mut guard = self.inner.lock().unwrap();
+        if let Some(vault) = guard.as_mut() {
+            if vault.key.is_some() {
+                vault.conn = None;
+                vault.key = None;
+                info!("Sealed encrypted vault {}", vault.path.display());
+            }
+        }
+    }
+
+    /// Path of the currently open vault
+    pub fn path(&self) -> Option<PathBuf> {
+        self.inner.lock().unwrap().as_ref().map(|v| v.path.clone())
+    }
+
+    pub fn status(&self) -> DatabaseStatus {
+        let guard = self.inner.lock().unwrap();
+        match guard.as_ref() {
+            Some(vault) => DatabaseStatus {
+                path: Some(vault.path.to_string_lossy().into_owned()),
+                encrypted: vault.key.is_some() || is_encrypted_file(&vault.path),
+                unlocked: vault.conn.is_some(),
+            },
+            None => DatabaseStatus { path: None, encrypted: false, unlocked: false },
+        }
+    }
+
+    /// Locks the shared connection for the calling command
+    pub fn conn(&self) -> Result<DbConn<'_>, XPassError> {
+        let guard = self.inner.lock().unwrap();
+        match guard.as_ref() {
+            None => return Err(XPassError::VaultNotOpen),
+            Some(vault) if vault.conn.is_none() => return Err(XPassError::VaultLocked),
+            Some(_) => {}
+        }
+        Ok(DbConn(guard))
+    }
+
+    /// Converts the open plaintext vault into an encrypted one in place
+    pub fn encrypt(&self, password: &str) -> Result<(), XPassError> {
+        let mut guard = self.inner.lock().unwrap();
+        let vault = guard.as_mut().ok_or(XPassError::VaultNotOpen)?;
+        if vault.key.is_some() {
+            return Err(XPassError::VaultAlreadyEncrypted);
+        }
+
+        rewrite_vault(vault, Some(DbKey::generate(password)?))?;
+        info!("Encrypted vault {}", vault.path.display());
+        Ok(())
+    }
+
+    /// Re-encrypts the vault under `new_password` if its key was derived from
+    /// `old_password`. Returns false when the vault is not encrypted with it.
+    pub fn rekey(&self, old_password: &str, new_password: &str) -> Result<bool, XPassError> {
+        let mut guard = self.inner.lock().unwrap();
+        let vault = guard.as_mut().ok_or(XPassError::VaultNotOpen)?;
+        let matches = match vault.key.as_ref() {
+            Some(key) => DbKey::derive(old_password, key.salt)?.key.expose() == key.key.expose(),
+            None => false,
+        };
+        if !matches {
+            return Ok(false);
+        }
+
+        rewrite_vault(vault, Some(DbKey::generate(new_password)?))?;
+        info!("Re-encrypted vault {} under a new password", vault.path.display());
+        Ok(true)
+    }
+
+    /// Converts the open encrypted vault back into a plaintext one in place
+    pub fn decrypt(&self) -> Result<(), XPassError> {
+        let mut guard = self.inner.lock().unwrap();
+        let vault = guard.as_mut().ok_or(XPassError::VaultNotOpen)?;
+        if vault.key.is_none() {
+            return Err(XPassError::VaultNotEncrypted);
+        }
+
+        rewrite_vault(vault, None)?;
+        warn!("Removed encryption from vault {}", vault.path.display());
+        Ok(())
+    }
+}
+
+/// Reports whether the active vault is encrypted and whether it is unlocked
+#[tauri::command]
+pub fn get_database_status(db: State<'_, Database>) -> DatabaseStatus {
+    db.status()
+}
+
+/// Unlocks an encrypted vault so users can be listed and log in
+#[tauri::command]
+pub fn unlock_database(db: State<'_, Database>, password: SecretString) -> Result<(), XPassError> {
+    db.unlock(password.expose())
+}
+
+/// Encrypts the whole vault file with a key derived from the user's master password
+#[tauri::command]
+pub fn encrypt_database(db: State<'_, Database>, user_id: i64, master_pass: SecretString) -> Result<(), XPassError> {
+    {
+        let conn = db.conn()?;
+        crate::password_manager::check_master_password(&conn, user_id, &master_pass)?;
+    }
+
+    db.encrypt(master_pass.expose())?;
+
+    let conn = db.conn()?;
+    crate::activity_logger::log_activity(
+        &conn,
+        user_id,
+        "database_encrypted",
+        "Vault database encrypted"
+    ).ok();
+    Ok(())
+}
+
+/// Removes whole-file encryption from the vault
+#[tauri::command]
+pub fn decrypt_database(db: State<'_, Database>, user_id: i64, master_pass: SecretString) -> Result<(), XPassError> {
+    {
+        let conn = db.conn()?;
+        crate::password_manager::check_master_password(&conn, user_id, &master_pass)?;
+    }
+
+    db.decrypt()?;
+
+    let conn = db.conn()?;
+    crate::activity_logger::log_activity(
+        &conn,
+        user_id,
+        "database_decrypted",
+        "Vault database encryption removed"
+    ).ok();
+    Ok(())
+}
@@ -0,0 +1,343 @@
+// ============================================================================
+// X-PASS Password Manager
+// Copyright (C) 2026 ar3love
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See LICENSE.
+// ============================================================================
+
+// src-tauri/src/error.rs
+//
+// The error type returned by every command. It reaches the frontend as
+//
+//     { "code": "invalid_password", "category": "auth",
+//       "message": "Invalid password", "params": {} }
+//
+// `code` is stable and is what the UI translates (`errors.<code>`), with
+// `params` available for interpolation. `message` is English and only meant
+// for logs. Codes must never be renamed once released.
+use std::fmt;
+
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use serde_json::{json, Value};
+
+use crate::migrations::SchemaError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCategory {
+    Auth,
+    Crypto,
+    Storage,
+    Validation,
+    Network,
+    Internal,
+}
+
+#[derive(Debug)]
+pub enum XPassError {
+    // --- Auth ---
+    UserNotFound,
+    InvalidPassword,
+    EmptyPassword,
+    /// A master or pseudo password scored below the required strength
+    WeakPassword { score: u8, min_score: u8 },
+    PasswordUnchanged,
+    /// The new master password equals one of the user's pseudo passwords
+    PasswordMatchesPseudo,
+    /// A pseudo password equal to the master password was requested
+    PseudoMatchesMaster,
+    SessionExpired,
+    SessionMismatch,
+    InvalidOtp,
+    OtpNotConfigured,
+    OtpRecoveryNotSetUp,
+    /// Too many failed attempts, the next one is accepted after a delay
+    TooManyAttempts { retry_after_secs: u64 },
+    AccountLocked { retry_after_secs: u64 },
+
+    // --- Crypto ---
+    EncryptionFailed,
+    DecryptionFailed,
+    /// Argon2 failed or was given unusable parameters
+    KeyDerivation(String),
+    /// Stored key material (salt, hash, nonce, wrapped key) cannot be used
+    CorruptedKeyMaterial { what: &'static str },
+    WrongBackupPassword,
+
+    // --- Storage ---
+    Database(String),
+    Io(String),
+    VaultNotOpen,
+    VaultLocked,
+    VaultNotFound,
+    VaultAlreadyExists,
+    VaultAlreadyEncrypted,
+    VaultNotEncrypted,
+    NotAVault,
+    UnknownVault,
+    NewerSchema { found: u32, supported: u32 },
+    MigrationFailed { version: u32, name: &'static str, reason: String },
+
+    // --- Validation ---
+    InvalidInput { field: &'static str, reason: String },
+    InvalidBackup(String),
+    NotFound { what: &'static str },
+    /// A single attachment over the per-file limit
+    AttachmentTooLarge { size: u64, limit: u64 },
+    /// Attaching the file would take the user over the per-vault limit
+    AttachmentQuotaExceeded { used: u64, limit: u64 },
+
+    // --- Network ---
+    /// Networking is switched off (offline mode)
+    NetworkDisabled,
+    /// A request failed or got an unusable response
+    Network(String),
+
+    // --- Internal ---
+    Internal(String),
+}
+
+impl XPassError {
+    /// Stable machine-readable code
+    pub fn code(&self) -> &'static str {
+        match self {
+            XPassError::UserNotFound => "user_not_found",
+            XPassError::InvalidPassword => "invalid_password",
+            XPassError::EmptyPassword => "empty_password",
+            XPassError::WeakPassword { .. } => "weak_password",
+            XPassError::PasswordUnchanged => "password_unchanged",
+            XPassError::PasswordMatchesPseudo => "password_matches_pseudo",
+            XPassError::PseudoMatchesMaster => "pseudo_matches_master",
+            XPassError::SessionExpired => "session_expired",
+            XPassError::SessionMismatch => "session_mismatch",
+            XPassError::InvalidOtp => "invalid_otp",
+            XPassError::OtpNotConfigured => "otp_not_configured",
+            XPassError::OtpRecoveryNotSetUp => "otp_recovery_not_set_up",
+            XPassError::TooManyAttempts { .. } => "too_many_attempts",
+            XPassError::AccountLocked { .. } => "account_locked",
+            XPassError::EncryptionFailed => "encryption_failed",
+            XPassError::DecryptionFailed => "decryption_failed",
+            XPassError::KeyDerivation(_) => "key_derivation_failed",
+            XPassError::CorruptedKeyMaterial { .. } => "corrupted_key_material",
+            XPassError::WrongBackupPassword => "wrong_backup_password",
+            XPassError::Database(_) => "database_error",
+            XPassError::Io(_) => "io_error",
+            XPassError::VaultNotOpen => "vault_not_open",
+            XPassError::VaultLocked => "vault_locked",
+            XPassError::VaultNotFound => "vault_not_found",
+            XPassError::VaultAlreadyExists => "vault_already_exists",
+            XPassError::VaultAlreadyEncrypted => "vault_already_encrypted",
+            XPassError::VaultNotEncrypted => "vault_not_encrypted",
+            XPassError::NotAVault => "not_a_vault",
+            XPassError::UnknownVault => "unknown_vault",
+            XPassError::NewerSchema { .. } => "newer_schema",
+            XPassError::MigrationFailed { .. } => "migration_failed",
+            XPassError::InvalidInput { .. } => "invalid_input",
+            XPassError::InvalidBackup(_) => "invalid_backup",
+            XPassError::NotFound { .. } => "not_found",
+            XPassError::AttachmentTooLarge { .. } => "attachment_too_large",
+            XPassError::AttachmentQuotaExceeded { .. } => "attachment_quota_exceeded",
+            XPassError::NetworkDisabled => "network_disabled",
+            XPassError::Network(_) => "network_error",
+            XPassError::Internal(_) => "internal_error",
+        }
+    }
+
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            XPassError::UserNotFound
+            | XPassError::InvalidPassword
+            | XPassError::EmptyPassword
+            | XPassError::WeakPassword { .. }
+            | XPassError::PasswordUnchanged
+            | XPassError::PasswordMatchesPseudo
+            | XPassError::PseudoMatchesMaster
+            | XPassError::SessionExpired
+            | XPassError::SessionMismatch
+            | XPassError::InvalidOtp
+            | XPassError::OtpNotConfigured
+            | XPassError::OtpRecoveryNotSetUp
+            | XPassError::TooManyAttempts { .. }
+            | XPassError::AccountLocked { .. } => ErrorCategory::Auth,
+
+            XPassError::EncryptionFailed
+            | XPassError::DecryptionFailed
+            | XPassError::KeyDerivation(_)
+            | XPassError::CorruptedKeyMaterial { .. }
+            | XPassError::WrongBackupPassword => ErrorCategory::Crypto,
+
+            XPassError::Database(_)
+            | XPassError::Io(_)
+            | XPassError::VaultNotOpen
+            | XPassError::VaultLocked
+            | XPassError::VaultNotFound
+            | XPassError::VaultAlreadyExists
+            | XPassError::VaultAlreadyEncrypted
+            | XPassError::VaultNotEncrypted
+            | XPassError::NotAVault
+            | XPassError::UnknownVault
+            | XPassError::NewerSchema { .. }
+            | XPassError::MigrationFailed { .. } => ErrorCategory::Storage,
+
+            XPassError::InvalidInput { .. }
+            | XPassError::InvalidBackup(_)
+            | XPassError::NotFound { .. }
+            | XPassError::AttachmentTooLarge { .. }
+            | XPassError::AttachmentQuotaExceeded { .. } => ErrorCategory::Validation,
+
+            XPassError::NetworkDisabled | XPassError::Network(_) => ErrorCategory::Network,
+
+            XPassError::Internal(_) => ErrorCategory::Internal,
+        }
+    }
+
+    /// Values the frontend can interpolate into the translated message
+    pub fn params(&self) -> Value {
+        match self {
+            XPassError::KeyDerivation(reason)
+            | XPassError::Database(reason)
+            | XPassError::Io(reason)
+            | XPassError::InvalidBackup(reason)
+            | XPassError::Network(reason)
+            | XPassError::Internal(reason) => json!({ "reason": reason }),
+            XPassError::TooManyAttempts { retry_after_secs }
+            | XPassError::AccountLocked { retry_after_secs } => json!({ "retry_after_secs": retry_after_secs }),
+            XPassError::CorruptedKeyMaterial { what } => json!({ "what": what }),
+            XPassError::WeakPassword { score, min_score } => json!({ "score": score, "min_score": min_score }),
+            XPassError::NewerSchema { found, supported } => json!({ "found": found, "supported": supported }),
+            XPassError::MigrationFailed { version, name, reason } => {
+                json!({ "version": version, "name": name, "reason": reason })
+            }
+            XPassError::InvalidInput { field, reason } => json!({ "field": field, "reason": reason }),
+            XPassError::NotFound { what } => json!({ "what": what }),
+            XPassError::AttachmentTooLarge { size, limit } => json!({ "size": size, "limit": limit }),
+            XPassError::AttachmentQuotaExceeded { used, limit } => json!({ "used": used, "limit": limit }),
+            _ => json!({}),
+        }
+    }
+
+    pub fn invalid_input(field: &'static str, reason: impl Into<String>) -> Self {
+        XPassError::InvalidInput { field, reason: reason.into() }
+    }
+
+    pub fn internal(e: impl fmt::Display) -> Self {
+        XPassError::Internal(e.to_string())
+    }
+}
+
+impl fmt::Display for XPassError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            XPassError::UserNotFound => write!(f, "User not found"),
+            XPassError::InvalidPassword => write!(f, "Invalid password"),
+            XPassError::EmptyPassword => write!(f, "Password cannot be empty"),
+            XPassError::WeakPassword { score, min_score } => {
+                write!(f, "Password is too weak (strength {} of 4, at least {} required)", score, min_score)
+            }
+            XPassError::PasswordUnchanged => write!(f, "New password must differ from the current one"),
+            XPassError::PasswordMatchesPseudo => write!(f, "New password matches an existing pseudo password"),
+            XPassError::PseudoMatchesMaster => write!(f, "Cannot use main password as pseudo password"),
+            XPassError::SessionExpired => write!(f, "Session expired or vault is locked"),
+            XPassError::SessionMismatch => write!(f, "Session does not belong to this user"),
+            XPassError::InvalidOtp => write!(f, "Invalid OTP code"),
+            XPassError::OtpNotConfigured => write!(f, "OTP secret not found. Please generate OTP first."),
+            XPassError::OtpRecoveryNotSetUp => write!(f, "OTP recovery not set up"),
+            XPassError::TooManyAttempts { retry_after_secs } => {
+                write!(f, "Too many failed attempts, try again in {} s", retry_after_secs)
+            }
+            XPassError::AccountLocked { retry_after_secs } => {
+                write!(f, "Account locked after too many failed attempts, try again in {} s", retry_after_secs)
+            }
+            XPassError::EncryptionFailed => write!(f, "Encryption failed"),
+            XPassError::DecryptionFailed => write!(f, "Decryption failed"),
+            XPassError::KeyDerivation(reason) => write!(f, "Key derivation failed: {}", reason),
+            XPassError::CorruptedKeyMaterial { what } => write!(f, "Invalid {}", what),
+            XPassError::WrongBackupPassword => write!(f, "Decryption failed - wrong backup password or corrupted backup"),
+            XPassError::Database(reason) => write!(f, "Database error: {}", reason),
+            XPassError::Io(reason) => write!(f, "I/O error: {}", reason),
+            XPassError::VaultNotOpen => write!(f, "No vault is open"),
+            XPassError::VaultLocked => write!(f, "Vault database is locked"),
+            XPassError::VaultNotFound => write!(f, "Vault file not found"),
+            XPassError::VaultAlreadyExists => write!(f, "A file already exists at this location"),
+            XPassError::VaultAlreadyEncrypted => write!(f, "Vault is already encrypted"),
+            XPassError::VaultNotEncrypted => write!(f, "Vault is not encrypted"),
+            XPassError::NotAVault => write!(f, "File is not an x-pass vault"),
+            XPassError::UnknownVault => write!(f, "Unknown vault, open it first"),
+            XPassError::NewerSchema { found, supported } => write!(
+                f,
+                "This vault was created by a newer version of X-PASS (schema v{}, this version supports up to v{}). Please update X-PASS to open it.",
+                found, supported
+            ),
+            XPassError::MigrationFailed { version, name, reason } => {
+                write!(f, "Database migration v{} ({}) failed: {}", version, name, reason)
+            }
+            XPassError::InvalidInput { field, reason } => write!(f, "Invalid {}: {}", field, reason),
+            XPassError::InvalidBackup(reason) => write!(f, "Invalid backup: {}", reason),
+            XPassError::NotFound { what } => write!(f, "{} not found", what),
+            XPassError::AttachmentTooLarge { size, limit } => {
+                write!(f, "File is too large ({} bytes, at most {} bytes)", size, limit)
+            }
+            XPassError::AttachmentQuotaExceeded { used, limit } => {
+                write!(f, "Attachment storage is full ({} of {} bytes used)", used, limit)
+            }
+            XPassError::NetworkDisabled => write!(f, "Network access is disabled"),
+            XPassError::Network(reason) => write!(f, "Network request failed: {}", reason),
+            XPassError::Internal(reason) => write!(f, "{}", reason),
+        }
+    }
+}
+
+impl std::error::Error for XPassError {}
+
+impl Serialize for XPassError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("XPassError", 4)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("category", &self.category())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.serialize_field("params", &self.params())?;
+        state.end()
+    }
+}
+
+impl From<rusqlite::Error> for XPassError {
+    fn from(e: rusqlite::Error) -> Self {
+        XPassError::Database(e.to_string())
+    }
+}
+
+impl From<std::io::Error> for XPassError {
+    fn from(e: std::io::Error) -> Self {
+        XPassError::Io(e.to_string())
+    }
+}
+
+impl From<argon2::Error> for XPassError {
+    fn from(e: argon2::Error) -> Self {
+        XPassError::KeyDerivation(e.to_string())
+    }
+}
+
+impl From<argon2::password_hash::Error> for XPassError {
+    fn from(e: argon2::password_hash::Error) -> Self {
+        XPassError::KeyDerivation(e.to_string())
+    }
+}
+
+impl From<SchemaError> for XPassError {
+    fn from(e: SchemaError) -> Self {
+        match e {
+            SchemaError::Sqlite(e) => e.into(),
+            SchemaError::NewerSchema { found, supported } => XPassError::NewerSchema { found, supported },
+            SchemaError::Failed { version, name, source } => {
+                XPassError::MigrationFailed { version, name, reason: source.to_string() }
+            }
+        }
+    }
+}
@@ -12,89 +12,165 @@
 // ============================================================================
 
 // src-tauri/src/fake_data_generator.rs
+use crate::kinds::{self, Details, EntryKind};
 use crate::password_manager::Entry;
+use crate::secrets::SecretString;
+use crate::strength;
+use crate::urls::{EntryUrl, MatchMode};
 use rand::Rng;
 
+fn random_digits(rng: &mut impl Rng, len: usize) -> String {
+    (0..len).map(|_| char::from(b'0' + rng.gen_range(0..10))).collect()
+}
+
+/// Card number that passes the Luhn check, in groups of four
+fn fake_card_number(rng: &mut impl Rng, prefix: &str, len: usize) -> String {
+    let mut digits = format!("{}{}", prefix, random_digits(rng, len - prefix.len() - 1));
+    digits.push_str(&kinds::luhn_check_digit(&digits).to_string());
+    digits.as_bytes()
+        .chunks(4)
+        .map(|chunk| String::from_utf8_lossy(chunk).into_owned())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn fake_details(values: Vec<(&str, String)>) -> Details {
+    values.into_iter().map(|(key, value)| (key.to_string(), value.into())).collect()
+}
+
+/// Cards, accounts and other non-login entries, so a fake vault does not
+/// consist of website logins only
+fn fake_other_entries(rng: &mut impl Rng) -> Vec<(&'static str, EntryKind, &'static str, Details)> {
+    let expiry = format!("{:02}/{}", rng.gen_range(1..=12), rng.gen_range(27..=31));
+    let amex_expiry = format!("{:02}/{}", rng.gen_range(1..=12), rng.gen_range(27..=31));
+    let license_key = (0..5)
+        .map(|_| (0..5).map(|_| char::from(b"BCDFGHJKMPQRTVWXY2346789"[rng.gen_range(0..24)])).collect::<String>())
+        .collect::<Vec<_>>()
+        .join("-");
+
+    vec![
+        ("Chase Sapphire", EntryKind::CreditCard, "Travel card", fake_details(vec![
+            ("cardholder_name", "MICHAEL J RIVERS".to_string()),
+            ("card_number", fake_card_number(rng, "4", 16)),
+            ("expiry", expiry),
+            ("cvv", random_digits(rng, 3)),
+            ("brand", "Visa".to_string()),
+        ])),
+        ("Amex Platinum", EntryKind::CreditCard, "Lounge access", fake_details(vec![
+            ("cardholder_name", "MICHAEL RIVERS".to_string()),
+            ("card_number", fake_card_number(rng, "37", 15)),
+            ("expiry", amex_expiry),
+            ("cvv", random_digits(rng, 4)),
+            ("brand", "American Express".to_string()),
+        ])),
+        ("Chase Checking", EntryKind::BankAccount, "Direct deposit", fake_details(vec![
+            ("bank_name", "JPMorgan Chase".to_string()),
+            ("account_holder", "Michael J. Rivers".to_string()),
+            ("account_number", random_digits(rng, 10)),
+            ("routing_number", "021000021".to_string()),
+        ])),
+        ("Home Wi-Fi", EntryKind::WifiNetwork, "Router in the hallway closet", fake_details(vec![
+            ("ssid", "Rivers_Home_5G".to_string()),
+            ("security", "wpa2".to_string()),
+            ("passphrase", "BlueCanoe!Lake2024".to_string()),
+        ])),
+        ("Driver's License", EntryKind::Identity, "NY state", fake_details(vec![
+            ("first_name", "Michael".to_string()),
+            ("last_name", "Rivers".to_string()),
+            ("date_of_birth", "1987-06-14".to_string()),
+            ("driver_license", format!("{} {} {}", random_digits(rng, 3), random_digits(rng, 3), random_digits(rng, 3))),
+        ])),
+        ("Microsoft 365", EntryKind::SoftwareLicense, "Family plan, 6 seats", fake_details(vec![
+            ("product", "Microsoft 365 Family".to_string()),
+            ("license_key", license_key),
+            ("licensed_to", "michael.rivers@outlook.com".to_string()),
+        ])),
+        ("Garage Door", EntryKind::SecureNote, "Keypad code 4471, reset button is behind the motor", Details::new()),
+    ]
+}
+
 /// Generates realistic fake entries for pseudo-mode / panic mode
 pub fn generate_fake_entries(count: usize) -> Vec<Entry> {
     let services = vec![
         // Major tech & email
-        ("Google", "michael.j.rivers87@gmail.com", "SunnyHills2023!", "Main email"),
-        ("Apple ID", "michael.rivers@icloud.com", "iCloud!Vault2024", "iPhone & Mac"),
-        ("Microsoft", "m.rivers@outlook.com", "Office365!2025", "Work & personal"),
-        ("Yahoo Mail", "mike_rivers2000@yahoo.com", "OldMail!Secure22", "Backup email"),
+        ("Google", "michael.j.rivers87@gmail.com", "SunnyHills2023!", "Main email", "https://accounts.google.com"),
+        ("Apple ID", "michael.rivers@icloud.com", "iCloud!Vault2024", "iPhone & Mac", "https://appleid.apple.com"),
+        ("Microsoft", "m.rivers@outlook.com", "Office365!2025", "Work & personal", "https://login.live.com"),
+        ("Yahoo Mail", "mike_rivers2000@yahoo.com", "OldMail!Secure22", "Backup email", "https://login.yahoo.com"),
 
         // Social networks & messengers
-        ("Facebook", "michael.rivers.39", "FamilyMoments2024", "Friends & family"),
-        ("Instagram", "mike.rivers.adventures", "InstaVibes!2025", "Travel photos"),
-        ("TikTok", "@mikeriverslife", "TikTokTrendz24!", "Short videos"),
-        ("Snapchat", "m.rivers.snap", "SnapBack2024!", "Daily snaps"),
-        ("WhatsApp", "+1 (716) 555-0192", "ChatSecure!2025", "Primary messenger"),
-        ("Telegram", "@MikeRiversReal", "TG!Secret2024", "Private groups"),
-        ("Discord", "MikeRivers#7482", "Discord!Gaming25", "Gaming & friends"),
+        ("Facebook", "michael.rivers.39", "FamilyMoments2024", "Friends & family", "https://www.facebook.com"),
+        ("Instagram", "mike.rivers.adventures", "InstaVibes!2025", "Travel photos", "https://www.instagram.com"),
+        ("TikTok", "@mikeriverslife", "TikTokTrendz24!", "Short videos", "https://www.tiktok.com"),
+        ("Snapchat", "m.rivers.snap", "SnapBack2024!", "Daily snaps", "https://accounts.snapchat.com"),
+        ("WhatsApp", "+1 (716) 555-0192", "ChatSecure!2025", "Primary messenger", "https://web.whatsapp.com"),
+        ("Telegram", "@MikeRiversReal", "TG!Secret2024", "Private groups", "https://web.telegram.org"),
+        ("Discord", "MikeRivers#7482", "Discord!Gaming25", "Gaming & friends", "https://discord.com"),
 
         // Banking & finance (US popular)
-        ("Chase", "m.rivers85", "ChaseSecure!2025", "Checking + Savings"),
-        ("Bank of America", "mriversboa", "BoA!Vault2024", "Main account"),
-        ("Wells Fargo", "michael.wf", "Wells!Safe2023", "Mortgage"),
-        ("Capital One", "mikerivers.cap", "VentureX!2025", "Credit card"),
-        ("American Express", "m.rivers.amex", "Platinum!Rewards24", "Premium card"),
-        ("PayPal", "michael.rivers@paypal.com", "PaySafe!2025", "Online payments"),
-        ("Venmo", "mikerivers", "Venmo!Quick24", "Friends payments"),
-        ("Cash App", "$MikeRivers87", "CashTag!2025", "Quick transfers"),
+        ("Chase", "m.rivers85", "ChaseSecure!2025", "Checking + Savings", "https://secure.chase.com"),
+        ("Bank of America", "mriversboa", "BoA!Vault2024", "Main account", "https://secure.bankofamerica.com"),
+        ("Wells Fargo", "michael.wf", "Wells!Safe2023", "Mortgage", "https://connect.secure.wellsfargo.com"),
+        ("Capital One", "mikerivers.cap", "VentureX!2025", "Credit card", "https://verified.capitalone.com"),
+        ("American Express", "m.rivers.amex", "Platinum!Rewards24", "Premium card", "https://www.americanexpress.com"),
+        ("PayPal", "michael.rivers@paypal.com", "PaySafe!2025", "Online payments", "https://www.paypal.com"),
+        ("Venmo", "mikerivers", "Venmo!Quick24", "Friends payments", "https://account.venmo.com"),
+        ("Cash App", "$MikeRivers87", "CashTag!2025", "Quick transfers", "https://cash.app"),
 
         // Shopping & delivery
-        ("Amazon", "mikeriversprime", "PrimeMember!2026", "Prime account"),
-        ("Walmart", "m.rivers.walmart", "Walmart+2025", "Online grocery"),
-        ("Target", "michael.target", "RedCard!Rewards24", "Circle rewards"),
-        ("eBay", "mikerivers.seller", "eBayPro!2024", "Selling account"),
-        ("Etsy", "rivershandmade", "EtsyShop!2025", "Handmade purchases"),
-        ("DoorDash", "m.rivers.dash", "DashPass!2025", "Food delivery"),
-        ("Uber Eats", "michael.ubereats", "EatsPass!2024", "Food orders"),
+        ("Amazon", "mikeriversprime", "PrimeMember!2026", "Prime account", "https://www.amazon.com"),
+        ("Walmart", "m.rivers.walmart", "Walmart+2025", "Online grocery", "https://www.walmart.com"),
+        ("Target", "michael.target", "RedCard!Rewards24", "Circle rewards", "https://www.target.com"),
+        ("eBay", "mikerivers.seller", "eBayPro!2024", "Selling account", "https://signin.ebay.com"),
+        ("Etsy", "rivershandmade", "EtsyShop!2025", "Handmade purchases", "https://www.etsy.com"),
+        ("DoorDash", "m.rivers.dash", "DashPass!2025", "Food delivery", "https://www.doordash.com"),
+        ("Uber Eats", "michael.ubereats", "EatsPass!2024", "Food orders", "https://www.ubereats.com"),
 
         // Streaming & entertainment
-        ("Netflix", "rivers.family", "WatchParty2025!", "Family profile"),
-        ("Disney+", "mikerivers.disney", "MagicKingdom!24", "Disney content"),
-        ("Hulu", "michael.hulu", "NoAds2025!", "Live TV + streaming"),
-        ("YouTube Premium", "m.rivers.yt", "NoAdsTube!2025", "Ad-free viewing"),
-        ("Spotify", "michaelrivers.music", "FamilyPlan!2026", "Music + podcasts"),
-        ("Twitch", "mikerivers_tv", "Sub4Life!2024", "Streaming follows"),
+        ("Netflix", "rivers.family", "WatchParty2025!", "Family profile", "https://www.netflix.com"),
+        ("Disney+", "mikerivers.disney", "MagicKingdom!24", "Disney content", "https://www.disneyplus.com"),
+        ("Hulu", "michael.hulu", "NoAds2025!", "Live TV + streaming", "https://auth.hulu.com"),
+        ("YouTube Premium", "m.rivers.yt", "NoAdsTube!2025", "Ad-free viewing", "https://www.youtube.com"),
+        ("Spotify", "michaelrivers.music", "FamilyPlan!2026", "Music + podcasts", "https://accounts.spotify.com"),
+        ("Twitch", "mikerivers_tv", "Sub4Life!2024", "Streaming follows", "https://www.twitch.tv"),
 
         // Travel & transportation
-        ("Delta Airlines", "m.rivers.delta", "SkyMiles!Elite25", "Frequent flyer"),
-        ("United Airlines", "mrivers.united", "MileagePlus2025", "Travel rewards"),
-        ("Airbnb", "mikerivers.host", "HostPro!2025", "Hosting & stays"),
-        ("Uber", "m.rivers.uber", "UberOne!2025", "Rides & eats"),
-        ("Lyft", "michael.lyft", "PinkPass!2024", "Alternative rides"),
+        ("Delta Airlines", "m.rivers.delta", "SkyMiles!Elite25", "Frequent flyer", "https://www.delta.com"),
+        ("United Airlines", "mrivers.united", "MileagePlus2025", "Travel rewards", "https://www.united.com"),
+        ("Airbnb", "mikerivers.host", "HostPro!2025", "Hosting & stays", "https://www.airbnb.com"),
+        ("Uber", "m.rivers.uber", "UberOne!2025", "Rides & eats", "https://auth.uber.com"),
+        ("Lyft", "michael.lyft", "PinkPass!2024", "Alternative rides", "https://account.lyft.com"),
 
         // Work & productivity
-        ("LinkedIn", "michael-rivers-mba", "CareerPath!2025", "Professional network"),
-        ("Work Email", "michael.rivers@techsolutions.io", "Corp!Secure2026", "Office 365"),
-        ("Slack", "m.rivers", "TeamChat!2025", "Work communication"),
-        ("Notion", "mikerivers.notion", "LifeOS!2025", "All-in-one workspace"),
-        ("Zoom", "m.rivers.zoom", "MeetingPro!2025", "Video conferencing"),
+        ("LinkedIn", "michael-rivers-mba", "CareerPath!2025", "Professional network", "https://www.linkedin.com"),
+        ("Work Email", "michael.rivers@techsolutions.io", "Corp!Secure2026", "Office 365", "https://outlook.office.com"),
+        ("Slack", "m.rivers", "TeamChat!2025", "Work communication", "https://app.slack.com"),
+        ("Notion", "mikerivers.notion", "LifeOS!2025", "All-in-one workspace", "https://www.notion.so"),
+        ("Zoom", "m.rivers.zoom", "MeetingPro!2025", "Video conferencing", "https://zoom.us"),
 
         // Gaming & hobbies
-        ("Steam", "MikeRivers87", "SteamDeck!2025", "Game library"),
-        ("PlayStation Network", "MikeRivers_PS5", "PSPlus!Extra24", "PS5 account"),
-        ("Xbox Live", "MikeRivers_Xbox", "GamePass!Ultimate25", "Xbox gaming"),
-        ("Epic Games", "mikerivers.epic", "Fortnite!2025", "Fortnite & Unreal"),
+        ("Steam", "MikeRivers87", "SteamDeck!2025", "Game library", "https://store.steampowered.com"),
+        ("PlayStation Network", "MikeRivers_PS5", "PSPlus!Extra24", "PS5 account", "https://www.playstation.com"),
+        ("Xbox Live", "MikeRivers_Xbox", "GamePass!Ultimate25", "Xbox gaming", "https://account.xbox.com"),
+        ("Epic Games", "mikerivers.epic", "Fortnite!2025", "Fortnite & Unreal", "https://www.epicgames.com"),
 
         // Other popular services
-        ("Reddit", "u/mikerivers_tech", "Lurker!2025", "Tech & memes"),
-        ("GitHub", "mikerivers-dev", "CodeSafe!2025", "Personal projects"),
-        ("Dropbox", "michael.rivers", "FamilyBackup!24", "File storage"),
-        ("1Password", "m.rivers.1pw", "MetaPassword!2025", "Irony password manager :D"),
-        ("Coinbase", "mikerivers.crypto", "HODL2025!", "Crypto wallet"),
+        ("Reddit", "u/mikerivers_tech", "Lurker!2025", "Tech & memes", "https://www.reddit.com"),
+        ("GitHub", "mikerivers-dev", "CodeSafe!2025", "Personal projects", "https://github.com"),
+        ("Dropbox", "michael.rivers", "FamilyBackup!24", "File storage", "https://www.dropbox.com"),
+        ("1Password", "m.rivers.1pw", "MetaPassword!2025", "Irony password manager :D", "https://my.1password.com"),
+        ("Coinbase", "mikerivers.crypto", "HODL2025!", "Crypto wallet", "https://www.coinbase.com"),
     ];
 
     let mut rng = rand::thread_rng();
-    let max_entries = services.len().min(count);
+    let mut others = fake_other_entries(&mut rng);
+    let total = services.len() + others.len();
+    let max_entries = total.min(count);
 
     // Select random services
-    let mut selected_indices: Vec<usize> = (0..services.len()).collect();
+    let mut selected_indices: Vec<usize> = (0..total).collect();
     for i in 0..max_entries {
-        let j = rng.gen_range(i..services.len());
+        let j = rng.gen_range(i..total);
         selected_indices.swap(i, j);
     }
 
@@ -104,13 +180,41 @@ pub fn generate_fake_entries(count: usize) -> Vec<Entry> {
         .iter()
         .enumerate()
         .map(|(idx, &i)| {
-            let (service, login, password, note) = &services[i];
+            let id = -(idx as i64 + 1); // Negative IDs for fake entries
+            if i >= services.len() {
+                let (service, kind, note, details) = std::mem::take(&mut others[i - services.len()]);
+                return Entry {
+                    id,
+                    service: service.to_string(),
+                    login: String::new(),
+                    password: "".into(),
+                    note: note.into(),
+                    kind,
+                    details,
+                    fields: Vec::new(),
+                    urls: Vec::new(),
+                    folder_id: None,
+                    tag_ids: Vec::new(),
+                    strength: None,
+                };
+            }
+
+            let (service, login, password, note, url) = services[i];
+            let password = SecretString::from(password);
+            let strength = strength::summary(&password, &[service, login]);
             Entry {
-                id: -(idx as i64 + 1), // Negative IDs for fake entries
+                id,
                 service: service.to_string(),
                 login: login.to_string(),
-                password: password.to_string(),
-                note: note.to_string(),
+                password,
+                note: note.into(),
+                kind: EntryKind::Login,
+                details: Details::new(),
+                fields: Vec::new(),
+                urls: vec![EntryUrl { url: url.into(), match_mode: MatchMode::default() }],
+                folder_id: None,
+                tag_ids: Vec::new(),
+                strength,
             }
         })
         .collect()
@@ -119,12 +223,25 @@ pub fn generate_fake_entries(count: usize) -> Vec<Entry> {
 /// Alternative generator with very generic/empty-looking entries (maximum plausible deniability)
 pub fn generate_empty_fake_entries(count: usize) -> Vec<Entry> {
     (0..count)
-        .map(|i| Entry {
-            id: -(i as i64 + 1),
-            service: format!("Service {}", i + 1),
-            login: format!("user{}@example.com", i + 1),
-            password: format!("password{}", i + 1),
-            note: String::from("No real data stored here"),
+        .map(|i| {
+            let service = format!("Service {}", i + 1);
+            let login = format!("user{}@example.com", i + 1);
+            let password = SecretString::from(format!("password{}", i + 1));
+            let strength = strength::summary(&password, &[&service, &login]);
+            Entry {
+                id: -(i as i64 + 1),
+                service,
+                login,
+                password,
+                note: "No real data stored here".into(),
+                kind: EntryKind::Login,
+                details: Details::new(),
+                fields: Vec::new(),
+                urls: Vec::new(),
+                folder_id: None,
+                tag_ids: Vec::new(),
+                strength,
+            }
         })
         .collect()
 }
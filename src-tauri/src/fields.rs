@@ -0,0 +1,231 @@
+// ============================================================================
+// X-PASS Password Manager
+// Copyright (C) 2026 ar3love
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See LICENSE.
+// ============================================================================
+
+// src-tauri/src/fields.rs
+//
+// Custom fields on entries: security questions, PINs, account numbers, API
+// tokens and the like. Every field's name and value are encrypted with the
+// data key, each under its own nonce, in `entry_fields`. The type is stored
+// in clear; it only tells the UI how to show and validate the value.
+use aes_gcm::Aes256Gcm;
+use base32::Alphabet;
+use chrono::NaiveDate;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+use crate::error::XPassError;
+use crate::keys;
+use crate::secrets::SecretString;
+
+const MAX_FIELDS: usize = 50;
+const MAX_NAME_LEN: usize = 100;
+const MAX_VALUE_LEN: usize = 10_000;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldType {
+    Text,
+    /// Shown masked, like the password
+    Hidden,
+    Url,
+    Email,
+    Number,
+    /// ISO date, YYYY-MM-DD
+    Date,
+    /// Base32 TOTP secret or otpauth:// URI
+    Totp,
+}
+
+impl FieldType {
+    fn as_str(&self) -> &'static str {
+        match self {
+            FieldType::Text => "text",
+            FieldType::Hidden => "hidden",
+            FieldType::Url => "url",
+            FieldType::Email => "email",
+            FieldType::Number => "number",
+            FieldType::Date => "date",
+            FieldType::Totp => "totp",
+        }
+    }
+
+    fn parse(value: &str) -> Option<FieldType> {
+        Some(match value {
+            "text" => FieldType::Text,
+            "hidden" => FieldType::Hidden,
+            "url" => FieldType::Url,
+            "email" => FieldType::Email,
+            "number" => FieldType::Number,
+            "date" => FieldType::Date,
+            "totp" => FieldType::Totp,
+            _ => return None,
+        })
+    }
+
+    /// Checks a non-empty value against the type
+    fn accepts(&self, value: &str) -> bool {
+        let value = value.trim();
+        match self {
+            FieldType::Text | FieldType::Hidden => true,
+            FieldType::Url => !value.contains(char::is_whitespace) && (value.contains("://") || value.contains('.')),
+            FieldType::Email => match value.split_once('@') {
+                Some((local, domain)) => !local.is_empty() && domain.contains('.') && !value.contains(char::is_whitespace),
+                None => false,
+            },
+            FieldType::Number => value.parse::<f64>().map(|n| n.is_finite()).unwrap_or(false),
+            FieldType::Date => NaiveDate::parse_from_str(value, "%Y-%m-%d").is_ok(),
+            FieldType::Totp => {
+                let secret = if value.starts_with("otpauth://totp/") {
+                    value.split(['?', '&'])
+                        .find_map(|param| param.strip_prefix("secret="))
+                        .unwrap_or_default()
+                } else {
+                    value
+                };
+                let secret: String = secret.chars().filter(|c| !c.is_whitespace()).collect::<String>().to_uppercase();
+                let decoded = base32::decode(Alphabet::RFC4648 { padding: false }, secret.trim_end_matches('='));
+                matches!(decoded, Some(bytes) if !bytes.is_empty())
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CustomField {
+    pub name: SecretString,
+    pub field_type: FieldType,
+    pub value: SecretString,
+}
+
+/// Rejects field lists the UI should never send: too many fields, empty or
+/// overlong names, and values that do not match their type
+pub fn validate(fields: &[CustomField]) -> Result<(), XPassError> {
+    if fields.len() > MAX_FIELDS {
+        return Err(XPassError::invalid_input("fields", format!("at most {} custom fields per entry", MAX_FIELDS)));
+    }
+    for field in fields {
+        let name = field.name.expose().trim();
+        if name.is_empty() || name.chars().count() > MAX_NAME_LEN {
+            return Err(XPassError::invalid_input("fields", format!("field names must be 1 to {} characters", MAX_NAME_LEN)));
+        }
+        if field.value.expose().len() > MAX_VALUE_LEN {
+            return Err(XPassError::invalid_input("fields", format!("field values must be at most {} bytes", MAX_VALUE_LEN)));
+        }
+        if !field.value.is_empty() && !field.field_type.accepts(field.value.expose()) {
+            return Err(XPassError::invalid_input("fields", format!("value is not a valid {}", field.field_type.as_str())));
+        }
+    }
+    Ok(())
+}
+
+/// Replaces all custom fields of an entry. Call inside the transaction
+/// that writes the entry.
+pub fn save_fields(conn: &Connection, entry_id: i64, fields: &[CustomField], cipher: &Aes256Gcm) -> Result<(), XPassError> {
+    conn.execute("DELETE FROM entry_fields WHERE entry_id = ?1", params![entry_id])?;
+    insert_fields(
+        conn,
+        "INSERT INTO entry_fields (entry_id, position, field_type, enc_name, name_nonce, enc_value, value_nonce)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        entry_id,
+        fields,
+        cipher
+    )
+}
+
+/// Stores the custom fields of a revision imported from a backup
+pub fn save_revision_fields(conn: &Connection, revision_id: i64, fields: &[CustomField], cipher: &Aes256Gcm) -> Result<(), XPassError> {
+    insert_fields(
+        conn,
+        "INSERT INTO entry_history_fields (revision_id, position, field_type, enc_name, name_nonce, enc_value, value_nonce)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        revision_id,
+        fields,
+        cipher
+    )
+}
+
+fn insert_fields(conn: &Connection, sql: &str, owner_id: i64, fields: &[CustomField], cipher: &Aes256Gcm) -> Result<(), XPassError> {
+    let mut stmt = conn.prepare_cached(sql)?;
+    for (position, field) in fields.iter().enumerate() {
+        let (enc_name, name_nonce) = keys::encrypt_secret(cipher, &field.name)?;
+        let (enc_value, value_nonce) = keys::encrypt_secret(cipher, &field.value)?;
+        stmt.execute(params![
+            owner_id,
+            position as i64,
+            field.field_type.as_str(),
+            enc_name,
+            name_nonce,
+            enc_value,
+            value_nonce
+        ])?;
+    }
+    Ok(())
+}
+
+/// Copies the fields of an entry, still encrypted, to a revision of it
+pub fn copy_to_revision(conn: &Connection, entry_id: i64, revision_id: i64) -> Result<(), XPassError> {
+    conn.execute(
+        "INSERT INTO entry_history_fields (revision_id, position, field_type, enc_name, name_nonce, enc_value, value_nonce)
+         SELECT ?2, position, field_type, enc_name, name_nonce, enc_value, value_nonce
+         FROM entry_fields WHERE entry_id = ?1",
+        params![entry_id, revision_id],
+    )?;
+    Ok(())
+}
+
+/// Decrypted custom fields of an entry, in the order they were saved
+pub fn load_fields(conn: &Connection, entry_id: i64, cipher: &Aes256Gcm) -> Result<Vec<CustomField>, XPassError> {
+    read_fields(
+        conn,
+        "SELECT field_type, enc_name, name_nonce, enc_value, value_nonce
+         FROM entry_fields WHERE entry_id = ?1 ORDER BY position",
+        entry_id,
+        cipher
+    )
+}
+
+/// Decrypted custom fields of a revision
+pub fn load_revision_fields(conn: &Connection, revision_id: i64, cipher: &Aes256Gcm) -> Result<Vec<CustomField>, XPassError> {
+    read_fields(
+        conn,
+        "SELECT field_type, enc_name, name_nonce, enc_value, value_nonce
+         FROM entry_history_fields WHERE revision_id = ?1 ORDER BY position",
+        revision_id,
+        cipher
+    )
+}
+
+fn read_fields(conn: &Connection, sql: &str, owner_id: i64, cipher: &Aes256Gcm) -> Result<Vec<CustomField>, XPassError> {
+    let mut stmt = conn.prepare_cached(sql)?;
+    let rows = stmt.query_map(params![owner_id], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, Vec<u8>>(1)?,
+            row.get::<_, Vec<u8>>(2)?,
+            row.get::<_, Vec<u8>>(3)?,
+            row.get::<_, Vec<u8>>(4)?,
+        ))
+    })?;
+
+    let mut fields = Vec::new();
+    for row in rows {
+        let (field_type, enc_name, name_nonce, enc_value, value_nonce) = row?;
+        fields.push(CustomField {
+            name: keys::decrypt_secret(cipher, &enc_name, &name_nonce)?,
+            // Types from a newer version are shown masked rather than in clear
+            field_type: FieldType::parse(&field_type).unwrap_or(FieldType::Hidden),
+            value: keys::decrypt_secret(cipher, &enc_value, &value_nonce)?,
+        });
+    }
+    Ok(fields)
+}
@@ -15,6 +15,8 @@
 
 use tauri::command;
 
+use crate::error::XPassError;
+
 // Embed SVG flags directly into the binary
 const FLAG_GB: &str = include_str!("../assets/flags/gb.svg");
 const FLAG_RU: &str = include_str!("../assets/flags/ru.svg");
@@ -31,7 +33,7 @@ const FLAG_IN: &str = include_str!("../assets/flags/in.svg");
 const FLAG_ID: &str = include_str!("../assets/flags/id.svg");
 
 #[command]
-pub fn get_flag(country_code: String) -> Result<String, String> {
+pub fn get_flag(country_code: String) -> Result<String, XPassError> {
     let flag = match country_code.to_uppercase().as_str() {
         "GB" => FLAG_GB,    // United Kingdom
         "RU" => FLAG_RU,    // Russia
@@ -46,7 +48,7 @@ pub fn get_flag(country_code: String) -> Result<String, String> {
         "SA" => FLAG_SA,    // Saudi Arabia
         "IN" => FLAG_IN,    // India
         "ID" => FLAG_ID,    // Indonesia
-        _ => return Err(format!("Flag not found for code: {}", country_code)),
+        _ => return Err(XPassError::NotFound { what: "Flag" }),
     };
     
     Ok(flag.to_string())
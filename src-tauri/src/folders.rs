@@ -0,0 +1,344 @@
+// ============================================================================
+// X-PASS Password Manager
+// Copyright (C) 2026 ar3love
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See LICENSE.
+// ============================================================================
+
+// src-tauri/src/folders.rs
+//
+// Nested folders for entries. Folder names are encrypted with the data key,
+// so uniqueness among siblings is checked on the decrypted names rather
+// than by the database. Deleting a folder deletes its subfolders; the
+// entries inside are kept and become unfiled.
+//
+// A pseudo session sees no folders; its changes are accepted but not stored
+// (see `keys::is_vault_key`), and a folder it creates gets a negative id.
+use std::collections::HashMap;
+
+use aes_gcm::Aes256Gcm;
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use tauri::State;
+
+use crate::database::Database;
+use crate::error::XPassError;
+use crate::keys;
+use crate::secrets::SecretString;
+use crate::session::SessionStore;
+
+pub const MAX_NAME_LEN: usize = 100;
+/// Deepest nesting allowed, counting the top level
+const MAX_DEPTH: usize = 16;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct Folder {
+    pub id: i64,
+    pub parent_id: Option<i64>,
+    pub name: SecretString,
+}
+
+/// Trims a folder or tag name and checks its length
+pub fn clean_name(name: &SecretString) -> Result<SecretString, XPassError> {
+    let trimmed = name.expose().trim();
+    if trimmed.is_empty() || trimmed.chars().count() > MAX_NAME_LEN {
+        return Err(XPassError::invalid_input("name", format!("must be 1 to {} characters", MAX_NAME_LEN)));
+    }
+    Ok(SecretString::from(trimmed))
+}
+
+/// All folders of the user, decrypted
+pub fn load_folders(conn: &Connection, user_id: i64, cipher: &Aes256Gcm) -> Result<Vec<Folder>, XPassError> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT id, parent_id, enc_name, name_nonce FROM folders WHERE user_id = ?1 ORDER BY id"
+    )?;
+    let rows = stmt.query_map(params![user_id], |row| {
+        Ok((row.get::<_, i64>(0)?, row.get::<_, Option<i64>>(1)?, row.get::<_, Vec<u8>>(2)?, row.get::<_, Vec<u8>>(3)?))
+    })?;
+
+    let mut folders = Vec::new();
+    for row in rows {
+        let (id, parent_id, enc_name, name_nonce) = row?;
+        folders.push(Folder { id, parent_id, name: keys::decrypt_secret(cipher, &enc_name, &name_nonce)? });
+    }
+    Ok(folders)
+}
+
+/// Names of the folder and its ancestors, from the top level down
+pub fn path_of(folders: &[Folder], folder_id: i64) -> Vec<SecretString> {
+    let by_id: HashMap<i64, &Folder> = folders.iter().map(|f| (f.id, f)).collect();
+    let mut path = Vec::new();
+    let mut current = by_id.get(&folder_id);
+    while let Some(folder) = current {
+        path.push(folder.name.clone());
+        if path.len() > MAX_DEPTH {
+            break;
+        }
+        current = folder.parent_id.and_then(|id| by_id.get(&id));
+    }
+    path.reverse();
+    path
+}
+
+fn depth_of(folders: &[Folder], folder_id: Option<i64>) -> usize {
+    folder_id.map(|id| path_of(folders, id).len()).unwrap_or(0)
+}
+
+/// True if `folder_id` is `ancestor` or lies somewhere below it
+fn is_within(folders: &[Folder], folder_id: i64, ancestor: i64) -> bool {
+    let mut current = Some(folder_id);
+    for _ in 0..=MAX_DEPTH {
+        match current {
+            Some(id) if id == ancestor => return true,
+            Some(id) => current = folders.iter().find(|f| f.id == id).and_then(|f| f.parent_id),
+            None => return false,
+        }
+    }
+    false
+}
+
+fn ensure_unique(folders: &[Folder], parent_id: Option<i64>, name: &SecretString, except: Option<i64>) -> Result<(), XPassError> {
+    let taken = folders.iter().any(|f| {
+        f.parent_id == parent_id
+            && Some(f.id) != except
+            && f.name.expose().to_lowercase() == name.expose().to_lowercase()
+    });
+    if taken {
+        return Err(XPassError::invalid_input("name", "a folder with this name already exists here"));
+    }
+    Ok(())
+}
+
+fn insert_folder(conn: &Connection, user_id: i64, parent_id: Option<i64>, name: &SecretString, cipher: &Aes256Gcm) -> Result<i64, XPassError> {
+    let (enc_name, name_nonce) = keys::encrypt_secret(cipher, name)?;
+    conn.execute(
+        "INSERT INTO folders (user_id, parent_id, enc_name, name_nonce, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![user_id, parent_id, enc_name, name_nonce, Utc::now().to_rfc3339()],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Returns the folder at `path`, creating whatever part of it is missing.
+/// Used when importing backups, which carry folders as name paths.
+pub fn ensure_path(conn: &Connection, user_id: i64, path: &[SecretString], cipher: &Aes256Gcm) -> Result<Option<i64>, XPassError> {
+    let mut folders = load_folders(conn, user_id, cipher)?;
+    let mut parent_id = None;
+    for name in path.iter().take(MAX_DEPTH) {
+        let name = clean_name(name)?;
+        let existing = folders.iter()
+            .find(|f| f.parent_id == parent_id && f.name.expose().to_lowercase() == name.expose().to_lowercase())
+            .map(|f| f.id);
+        parent_id = Some(match existing {
+            Some(id) => id,
+            None => {
+                let id = insert_folder(conn, user_id, parent_id, &name, cipher)?;
+                folders.push(Folder { id, parent_id, name });
+                id
+            }
+        });
+    }
+    Ok(parent_id)
+}
+
+/// Checks that the folder exists and belongs to the user
+pub fn ensure_owned(conn: &Connection, user_id: i64, folder_id: i64) -> Result<(), XPassError> {
+    conn.query_row(
+        "SELECT 1 FROM folders WHERE id = ?1 AND user_id = ?2",
+        params![folder_id, user_id],
+        |_| Ok(())
+    ).optional()?.ok_or(XPassError::NotFound { what: "Folder" })
+}
+
+/// Lists the user's folders; the tree is rebuilt from `parent_id`
+#[tauri::command]
+pub fn list_folders(
+    db: State<'_, Database>,
+    sessions: State<'_, SessionStore>,
+    user_id: i64,
+    session_id: String
+) -> Result<Vec<Folder>, XPassError> {
+    let cipher = sessions.cipher(&session_id, user_id)?;
+    let conn = db.conn()?;
+
+    if !keys::is_vault_key(&conn, user_id, &cipher)? {
+        return Ok(vec![]);
+    }
+    load_folders(&conn, user_id, &cipher)
+}
+
+/// Creates a folder at the top level or inside `parent_id`
+#[tauri::command]
+pub fn create_folder(
+    db: State<'_, Database>,
+    sessions: State<'_, SessionStore>,
+    user_id: i64,
+    session_id: String,
+    name: SecretString,
+    parent_id: Option<i64>
+) -> Result<Folder, XPassError> {
+    let cipher = sessions.cipher(&session_id, user_id)?;
+    let conn = db.conn()?;
+    let name = clean_name(&name)?;
+
+    if !keys::is_vault_key(&conn, user_id, &cipher)? {
+        return Ok(Folder { id: -Utc::now().timestamp_millis(), parent_id, name });
+    }
+    if let Some(parent_id) = parent_id {
+        ensure_owned(&conn, user_id, parent_id)?;
+    }
+
+    let folders = load_folders(&conn, user_id, &cipher)?;
+    if depth_of(&folders, parent_id) >= MAX_DEPTH {
+        return Err(XPassError::invalid_input("parent_id", format!("folders can be nested at most {} levels deep", MAX_DEPTH)));
+    }
+    ensure_unique(&folders, parent_id, &name, None)?;
+
+    let id = insert_folder(&conn, user_id, parent_id, &name, &cipher)?;
+    crate::activity_logger::log_activity(&conn, user_id, "folder_created", "Folder created")?;
+    Ok(Folder { id, parent_id, name })
+}
+
+/// Renames a folder
+#[tauri::command]
+pub fn rename_folder(
+    db: State<'_, Database>,
+    sessions: State<'_, SessionStore>,
+    user_id: i64,
+    session_id: String,
+    folder_id: i64,
+    name: SecretString
+) -> Result<(), XPassError> {
+    let cipher = sessions.cipher(&session_id, user_id)?;
+    let conn = db.conn()?;
+    let name = clean_name(&name)?;
+
+    if !keys::is_vault_key(&conn, user_id, &cipher)? {
+        return Ok(());
+    }
+    let folders = load_folders(&conn, user_id, &cipher)?;
+    let folder = folders.iter().find(|f| f.id == folder_id).ok_or(XPassError::NotFound { what: "Folder" })?;
+    ensure_unique(&folders, folder.parent_id, &name, Some(folder_id))?;
+
+    let (enc_name, name_nonce) = keys::encrypt_secret(&cipher, &name)?;
+    conn.execute(
+        "UPDATE folders SET enc_name = ?1, name_nonce = ?2 WHERE id = ?3 AND user_id = ?4",
+        params![enc_name, name_nonce, folder_id, user_id],
+    )?;
+    Ok(())
+}
+
+/// Moves a folder under another one, or to the top level with no parent
+#[tauri::command]
+pub fn move_folder(
+    db: State<'_, Database>,
+    sessions: State<'_, SessionStore>,
+    user_id: i64,
+    session_id: String,
+    folder_id: i64,
+    parent_id: Option<i64>
+) -> Result<(), XPassError> {
+    let cipher = sessions.cipher(&session_id, user_id)?;
+    let conn = db.conn()?;
+
+    if !keys::is_vault_key(&conn, user_id, &cipher)? {
+        return Ok(());
+    }
+    let folders = load_folders(&conn, user_id, &cipher)?;
+    let folder = folders.iter().find(|f| f.id == folder_id).ok_or(XPassError::NotFound { what: "Folder" })?;
+
+    if let Some(parent_id) = parent_id {
+        if !folders.iter().any(|f| f.id == parent_id) {
+            return Err(XPassError::NotFound { what: "Folder" });
+        }
+        if is_within(&folders, parent_id, folder_id) {
+            return Err(XPassError::invalid_input("parent_id", "a folder cannot be moved into itself"));
+        }
+    }
+
+    // Levels the moved subtree spans, counting the folder itself
+    let own_depth = depth_of(&folders, Some(folder_id));
+    let subtree_depth = folders.iter()
+        .filter(|f| is_within(&folders, f.id, folder_id))
+        .map(|f| depth_of(&folders, Some(f.id)) + 1 - own_depth)
+        .max()
+        .unwrap_or(1);
+    if depth_of(&folders, parent_id) + subtree_depth > MAX_DEPTH {
+        return Err(XPassError::invalid_input("parent_id", format!("folders can be nested at most {} levels deep", MAX_DEPTH)));
+    }
+    ensure_unique(&folders, parent_id, &folder.name, Some(folder_id))?;
+
+    conn.execute(
+        "UPDATE folders SET parent_id = ?1 WHERE id = ?2 AND user_id = ?3",
+        params![parent_id, folder_id, user_id],
+    )?;
+    Ok(())
+}
+
+/// Deletes a folder and its subfolders. Their entries are not deleted, they
+/// just no longer belong to a folder.
+#[tauri::command]
+pub fn delete_folder(
+    db: State<'_, Database>,
+    sessions: State<'_, SessionStore>,
+    user_id: i64,
+    session_id: String,
+    folder_id: i64
+) -> Result<(), XPassError> {
+    let cipher = sessions.cipher(&session_id, user_id)?;
+    let conn = db.conn()?;
+
+    if !keys::is_vault_key(&conn, user_id, &cipher)? {
+        return Ok(());
+    }
+    let count = conn.execute(
+        "DELETE FROM folders WHERE id = ?1 AND user_id = ?2",
+        params![folder_id, user_id],
+    )?;
+    if count == 0 {
+        return Err(XPassError::NotFound { what: "Folder" });
+    }
+
+    crate::activity_logger::log_activity(&conn, user_id, "folder_deleted", "Folder deleted")?;
+    Ok(())
+}
+
+/// Files an entry into a folder, or takes it out of any folder with None
+#[tauri::command]
+pub fn set_entry_folder(
+    db: State<'_, Database>,
+    sessions: State<'_, SessionStore>,
+    user_id: i64,
+    session_id: String,
+    entry_id: i64,
+    folder_id: Option<i64>
+) -> Result<(), XPassError> {
+    let cipher = sessions.cipher(&session_id, user_id)?;
+    // Fake pseudo-mode entries have negative ids and are not in the database
+    if entry_id < 0 {
+        return Ok(());
+    }
+
+    let conn = db.conn()?;
+    if !keys::is_vault_key(&conn, user_id, &cipher)? {
+        return Ok(());
+    }
+    if let Some(folder_id) = folder_id {
+        ensure_owned(&conn, user_id, folder_id)?;
+    }
+
+    let count = conn.execute(
+        "UPDATE entries SET folder_id = ?1 WHERE id = ?2 AND user_id = ?3 AND deleted_at IS NULL",
+        params![folder_id, entry_id, user_id],
+    )?;
+    if count == 0 {
+        return Err(XPassError::NotFound { what: "Entry" });
+    }
+    Ok(())
+}
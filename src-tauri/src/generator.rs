@@ -0,0 +1,496 @@
+// ============================================================================
+// X-PASS Password Manager
+// Copyright (C) 2026 ar3love
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See LICENSE.
+// ============================================================================
+
+// src-tauri/src/generator.rs
+//
+// Random password generation and saved generator profiles. Passwords are
+// drawn from `rand::thread_rng`, a ChaCha CSPRNG seeded by the operating
+// system: first the required minimum of each character class, then the rest
+// from all enabled classes, then shuffled. A character that completes a run
+// like "aaa", "abc", "321" or "qwer" is drawn again from its own class, so
+// the minimums still hold.
+//
+// A profile is a named policy. The policy itself is stored as JSON in clear,
+// it holds no secrets; the name is encrypted like folder and tag names. A
+// pseudo session sees no profiles; its changes are accepted but not stored
+// (see `keys::is_vault_key`), and it generates with the default policy.
+use aes_gcm::Aes256Gcm;
+use chrono::Utc;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use tauri::State;
+use zeroize::Zeroizing;
+
+use crate::database::Database;
+use crate::error::XPassError;
+use crate::folders::clean_name;
+use crate::keys;
+use crate::secrets::SecretString;
+use crate::session::SessionStore;
+
+pub const MIN_LENGTH: usize = 4;
+pub const MAX_LENGTH: usize = 256;
+const MAX_PROFILES: usize = 50;
+/// Redraws before a policy is reported as too restrictive to avoid weak runs
+const MAX_REDRAWS: usize = 10_000;
+
+const LOWERCASE: &str = "abcdefghijklmnopqrstuvwxyz";
+const UPPERCASE: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+const DIGITS: &str = "0123456789";
+/// Symbols that are safe to paste into web forms and shells
+const DEFAULT_SYMBOLS: &str = "!@#$%^&*()-_=+[]{};:,.<>?/~";
+/// Characters that are easy to misread for one another
+const LOOK_ALIKES: &str = "0Oo1lI|";
+const KEYBOARD_ROWS: [&str; 4] = ["1234567890", "qwertyuiop", "asdfghjkl", "zxcvbnm"];
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct PasswordPolicy {
+    pub length: usize,
+    pub lowercase: bool,
+    pub uppercase: bool,
+    pub digits: bool,
+    pub symbols: bool,
+    /// Minimums apply to enabled classes only
+    pub min_lowercase: usize,
+    pub min_uppercase: usize,
+    pub min_digits: usize,
+    pub min_symbols: usize,
+    pub exclude_look_alikes: bool,
+    /// Replaces the default symbol set
+    pub symbol_set: Option<String>,
+    pub reject_weak_patterns: bool,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        PasswordPolicy {
+            length: 20,
+            lowercase: true,
+            uppercase: true,
+            digits: true,
+            symbols: true,
+            min_lowercase: 1,
+            min_uppercase: 1,
+            min_digits: 1,
+            min_symbols: 1,
+            exclude_look_alikes: false,
+            symbol_set: None,
+            reject_weak_patterns: true,
+        }
+    }
+}
+
+impl PasswordPolicy {
+    /// The enabled character classes with their minimums, after exclusions
+    fn classes(&self) -> Result<Vec<(Vec<char>, usize)>, XPassError> {
+        let symbols = match &self.symbol_set {
+            Some(set) => {
+                if set.chars().any(|c| c.is_alphanumeric() || c.is_whitespace() || c.is_control()) {
+                    return Err(XPassError::invalid_input("symbol_set", "only symbols are allowed"));
+                }
+                set.as_str()
+            }
+            None => DEFAULT_SYMBOLS,
+        };
+
+        let mut classes = Vec::new();
+        for (enabled, set, min) in [
+            (self.lowercase, LOWERCASE, self.min_lowercase),
+            (self.uppercase, UPPERCASE, self.min_uppercase),
+            (self.digits, DIGITS, self.min_digits),
+            (self.symbols, symbols, self.min_symbols),
+        ] {
+            if !enabled {
+                continue;
+            }
+            let mut chars: Vec<char> = set.chars()
+                .filter(|c| !self.exclude_look_alikes || !LOOK_ALIKES.contains(*c))
+                .collect();
+            chars.sort_unstable();
+            chars.dedup();
+            if chars.is_empty() {
+                return Err(XPassError::invalid_input("symbol_set", "no symbols left to choose from"));
+            }
+            classes.push((chars, min));
+        }
+        Ok(classes)
+    }
+
+    /// Checks the policy and returns its character classes
+    fn validate(&self) -> Result<Vec<(Vec<char>, usize)>, XPassError> {
+        if !(MIN_LENGTH..=MAX_LENGTH).contains(&self.length) {
+            return Err(XPassError::invalid_input("length", format!("must be {} to {}", MIN_LENGTH, MAX_LENGTH)));
+        }
+        let classes = self.classes()?;
+        if classes.is_empty() {
+            return Err(XPassError::invalid_input("policy", "enable at least one character class"));
+        }
+        if classes.iter().map(|(_, min)| min).sum::<usize>() > self.length {
+            return Err(XPassError::invalid_input("policy", "the minimums add up to more than the length"));
+        }
+        Ok(classes)
+    }
+}
+
+/// Position of the character that completes the first weak run: three
+/// repeated characters, three consecutive letters or digits in either
+/// direction, or four neighbouring keys on a keyboard row
+fn weak_run_end(password: &[char]) -> Option<usize> {
+    (2..password.len()).find(|&end| {
+        let w = &password[end - 2..=end];
+        if w[0] == w[1] && w[1] == w[2] {
+            return true;
+        }
+        if w.iter().all(char::is_ascii_alphanumeric) {
+            let [a, b, c] = [w[0], w[1], w[2]].map(|ch| ch.to_ascii_lowercase() as i32);
+            if b - a == c - b && (b - a).abs() == 1 {
+                return true;
+            }
+        }
+        end >= 3 && {
+            let run: String = password[end - 3..=end].iter().map(char::to_ascii_lowercase).collect();
+            let reversed: String = run.chars().rev().collect();
+            KEYBOARD_ROWS.iter().any(|row| row.contains(&run) || row.contains(&reversed))
+        }
+    })
+}
+
+/// Generates a password following the policy
+pub fn generate(policy: &PasswordPolicy) -> Result<SecretString, XPassError> {
+    let classes = policy.validate()?;
+    let pool: Vec<char> = classes.iter().flat_map(|(chars, _)| chars.iter().copied()).collect();
+    let mut rng = rand::thread_rng();
+
+    let mut chars = Zeroizing::new(Vec::with_capacity(policy.length));
+    for (class, min) in &classes {
+        chars.extend((0..*min).map(|_| class[rng.gen_range(0..class.len())]));
+    }
+    while chars.len() < policy.length {
+        chars.push(pool[rng.gen_range(0..pool.len())]);
+    }
+    chars.shuffle(&mut rng);
+
+    if policy.reject_weak_patterns {
+        let mut redraws = 0;
+        while let Some(end) = weak_run_end(&chars) {
+            redraws += 1;
+            if redraws > MAX_REDRAWS {
+                return Err(XPassError::invalid_input("policy", "too restrictive to avoid weak patterns"));
+            }
+            // Classes do not overlap, symbols are never alphanumeric
+            let (class, _) = classes.iter()
+                .find(|(class, _)| class.contains(&chars[end]))
+                .ok_or_else(|| XPassError::internal("generated character outside its classes"))?;
+            chars[end] = class[rng.gen_range(0..class.len())];
+        }
+    }
+
+    let mut password = String::with_capacity(policy.length * 4);
+    password.extend(chars.iter());
+    Ok(SecretString::new(password))
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct GeneratorProfile {
+    pub id: i64,
+    pub name: SecretString,
+    pub policy: PasswordPolicy,
+}
+
+fn load_profiles(conn: &Connection, user_id: i64, cipher: &Aes256Gcm) -> Result<Vec<GeneratorProfile>, XPassError> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT id, enc_name, name_nonce, policy FROM generator_profiles WHERE user_id = ?1 ORDER BY id"
+    )?;
+    let rows = stmt.query_map(params![user_id], |row| {
+        Ok((
+            row.get::<_, i64>(0)?,
+            row.get::<_, Vec<u8>>(1)?,
+            row.get::<_, Vec<u8>>(2)?,
+            row.get::<_, String>(3)?,
+        ))
+    })?;
+
+    let mut profiles = Vec::new();
+    for row in rows {
+        let (id, enc_name, name_nonce, policy) = row?;
+        profiles.push(GeneratorProfile {
+            id,
+            name: keys::decrypt_secret(cipher, &enc_name, &name_nonce)?,
+            // Unknown or missing settings fall back to the defaults
+            policy: serde_json::from_str(&policy).map_err(XPassError::internal)?,
+        });
+    }
+    Ok(profiles)
+}
+
+fn ensure_unique(profiles: &[GeneratorProfile], name: &SecretString, except: Option<i64>) -> Result<(), XPassError> {
+    let name = name.expose().to_lowercase();
+    if profiles.iter().any(|p| Some(p.id) != except && p.name.expose().to_lowercase() == name) {
+        return Err(XPassError::invalid_input("name", "a profile with this name already exists"));
+    }
+    Ok(())
+}
+
+/// Generates a password. Without a policy the defaults are used: 20
+/// characters from all four classes, at least one of each.
+#[tauri::command]
+pub fn generate_password(policy: Option<PasswordPolicy>) -> Result<SecretString, XPassError> {
+    generate(&policy.unwrap_or_default())
+}
+
+/// Generates a password with a saved profile
+#[tauri::command]
+pub fn generate_password_with_profile(
+    db: State<'_, Database>,
+    sessions: State<'_, SessionStore>,
+    user_id: i64,
+    session_id: String,
+    profile_id: i64
+) -> Result<SecretString, XPassError> {
+    let cipher = sessions.cipher(&session_id, user_id)?;
+    let conn = db.conn()?;
+
+    if !keys::is_vault_key(&conn, user_id, &cipher)? {
+        return generate(&PasswordPolicy::default());
+    }
+    let policy: String = conn.query_row(
+        "SELECT policy FROM generator_profiles WHERE id = ?1 AND user_id = ?2",
+        params![profile_id, user_id],
+        |row| row.get(0)
+    ).optional()?.ok_or(XPassError::NotFound { what: "Generator profile" })?;
+
+    let policy: PasswordPolicy = serde_json::from_str(&policy).map_err(XPassError::internal)?;
+    generate(&policy)
+}
+
+/// Lists the user's generator profiles
+#[tauri::command]
+pub fn list_generator_profiles(
+    db: State<'_, Database>,
+    sessions: State<'_, SessionStore>,
+    user_id: i64,
+    session_id: String
+) -> Result<Vec<GeneratorProfile>, XPassError> {
+    let cipher = sessions.cipher(&session_id, user_id)?;
+    let conn = db.conn()?;
+
+    if !keys::is_vault_key(&conn, user_id, &cipher)? {
+        return Ok(vec![]);
+    }
+    load_profiles(&conn, user_id, &cipher)
+}
+
+/// Saves a new generator profile
+#[tauri::command]
+pub fn create_generator_profile(
+    db: State<'_, Database>,
+    sessions: State<'_, SessionStore>,
+    user_id: i64,
+    session_id: String,
+    name: SecretString,
+    policy: PasswordPolicy
+) -> Result<GeneratorProfile, XPassError> {
+    let cipher = sessions.cipher(&session_id, user_id)?;
+    let name = clean_name(&name)?;
+    policy.validate()?;
+    let conn = db.conn()?;
+
+    if !keys::is_vault_key(&conn, user_id, &cipher)? {
+        return Ok(GeneratorProfile { id: -Utc::now().timestamp_millis(), name, policy });
+    }
+    let profiles = load_profiles(&conn, user_id, &cipher)?;
+    if profiles.len() >= MAX_PROFILES {
+        return Err(XPassError::invalid_input("name", format!("at most {} profiles", MAX_PROFILES)));
+    }
+    ensure_unique(&profiles, &name, None)?;
+
+    let (enc_name, name_nonce) = keys::encrypt_secret(&cipher, &name)?;
+    let now = Utc::now().to_rfc3339();
+    conn.execute(
+        "INSERT INTO generator_profiles (user_id, enc_name, name_nonce, policy, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?5)",
+        params![user_id, enc_name, name_nonce, serde_json::to_string(&policy).map_err(XPassError::internal)?, now],
+    )?;
+    Ok(GeneratorProfile { id: conn.last_insert_rowid(), name, policy })
+}
+
+/// Renames a generator profile and replaces its policy
+#[tauri::command]
+pub fn update_generator_profile(
+    db: State<'_, Database>,
+    sessions: State<'_, SessionStore>,
+    user_id: i64,
+    session_id: String,
+    profile_id: i64,
+    name: SecretString,
+    policy: PasswordPolicy
+) -> Result<GeneratorProfile, XPassError> {
+    let cipher = sessions.cipher(&session_id, user_id)?;
+    let name = clean_name(&name)?;
+    policy.validate()?;
+    let conn = db.conn()?;
+
+    if !keys::is_vault_key(&conn, user_id, &cipher)? {
+        return Ok(GeneratorProfile { id: profile_id, name, policy });
+    }
+    let profiles = load_profiles(&conn, user_id, &cipher)?;
+    if !profiles.iter().any(|p| p.id == profile_id) {
+        return Err(XPassError::NotFound { what: "Generator profile" });
+    }
+    ensure_unique(&profiles, &name, Some(profile_id))?;
+
+    let (enc_name, name_nonce) = keys::encrypt_secret(&cipher, &name)?;
+    conn.execute(
+        "UPDATE generator_profiles SET enc_name = ?1, name_nonce = ?2, policy = ?3, updated_at = ?4
+         WHERE id = ?5 AND user_id = ?6",
+        params![
+            enc_name,
+            name_nonce,
+            serde_json::to_string(&policy).map_err(XPassError::internal)?,
+            Utc::now().to_rfc3339(),
+            profile_id,
+            user_id
+        ],
+    )?;
+    Ok(GeneratorProfile { id: profile_id, name, policy })
+}
+
+/// Deletes a generator profile
+#[tauri::command]
+pub fn delete_generator_profile(
+    db: State<'_, Database>,
+    sessions: State<'_, SessionStore>,
+    user_id: i64,
+    session_id: String,
+    profile_id: i64
+) -> Result<(), XPassError> {
+    let cipher = sessions.cipher(&session_id, user_id)?;
+    let conn = db.conn()?;
+
+    if !keys::is_vault_key(&conn, user_id, &cipher)? {
+        return Ok(());
+    }
+    let count = conn.execute(
+        "DELETE FROM generator_profiles WHERE id = ?1 AND user_id = ?2",
+        params![profile_id, user_id],
+    )?;
+    if count == 0 {
+        return Err(XPassError::NotFound { what: "Generator profile" });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chars(password: &str) -> Vec<char> {
+        password.chars().collect()
+    }
+
+    #[test]
+    fn weak_runs_are_found_where_they_end() {
+        assert_eq!(weak_run_end(&chars("xaaa")), Some(3));
+        assert_eq!(weak_run_end(&chars("Abc9")), Some(2));
+        assert_eq!(weak_run_end(&chars("x321")), Some(3));
+        assert_eq!(weak_run_end(&chars("#qwer")), Some(4));
+        assert_eq!(weak_run_end(&chars("poiu")), Some(3));
+        assert_eq!(weak_run_end(&chars("aa!a")), None);
+        assert_eq!(weak_run_end(&chars("ace7")), None);
+        assert_eq!(weak_run_end(&chars("qwe!")), None);
+        assert_eq!(weak_run_end(&chars("")), None);
+    }
+
+    #[test]
+    fn minimums_hold_after_redraws() {
+        let policy = PasswordPolicy {
+            length: 8,
+            min_lowercase: 2,
+            min_uppercase: 2,
+            min_digits: 2,
+            min_symbols: 2,
+            ..Default::default()
+        };
+        for _ in 0..200 {
+            let password = generate(&policy).unwrap();
+            let chars = chars(password.expose());
+            assert_eq!(chars.len(), 8);
+            assert_eq!(chars.iter().filter(|c| c.is_ascii_lowercase()).count(), 2);
+            assert_eq!(chars.iter().filter(|c| c.is_ascii_uppercase()).count(), 2);
+            assert_eq!(chars.iter().filter(|c| c.is_ascii_digit()).count(), 2);
+            assert_eq!(weak_run_end(&chars), None);
+        }
+    }
+
+    #[test]
+    fn digits_only_avoid_weak_runs() {
+        let policy = PasswordPolicy {
+            length: 40,
+            lowercase: false,
+            uppercase: false,
+            symbols: false,
+            ..Default::default()
+        };
+        for _ in 0..50 {
+            let password = generate(&policy).unwrap();
+            assert!(password.expose().chars().all(|c| c.is_ascii_digit()));
+            assert_eq!(weak_run_end(&chars(password.expose())), None);
+        }
+    }
+
+    #[test]
+    fn look_alikes_are_excluded() {
+        let policy = PasswordPolicy { length: MAX_LENGTH, exclude_look_alikes: true, ..Default::default() };
+        for _ in 0..20 {
+            let password = generate(&policy).unwrap();
+            assert!(!password.expose().chars().any(|c| LOOK_ALIKES.contains(c)));
+        }
+    }
+
+    #[test]
+    fn invalid_policies_are_rejected() {
+        let invalid = [
+            PasswordPolicy { length: MIN_LENGTH - 1, ..Default::default() },
+            PasswordPolicy { length: MAX_LENGTH + 1, ..Default::default() },
+            PasswordPolicy { lowercase: false, uppercase: false, digits: false, symbols: false, ..Default::default() },
+            PasswordPolicy { length: 4, min_lowercase: 2, min_digits: 3, ..Default::default() },
+            PasswordPolicy { symbol_set: Some("ab".to_string()), ..Default::default() },
+            PasswordPolicy { symbol_set: Some("! ".to_string()), ..Default::default() },
+            PasswordPolicy { symbol_set: Some("|".to_string()), exclude_look_alikes: true, ..Default::default() },
+        ];
+        for policy in &invalid {
+            assert!(policy.validate().is_err(), "{:?}", policy);
+        }
+        // Minimums of disabled classes do not count
+        let policy = PasswordPolicy { length: 4, symbols: false, min_symbols: 10, ..Default::default() };
+        assert_eq!(policy.validate().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn custom_symbol_set_is_used() {
+        let policy = PasswordPolicy {
+            length: 30,
+            lowercase: false,
+            uppercase: false,
+            digits: false,
+            symbol_set: Some("#%".to_string()),
+            reject_weak_patterns: false,
+            ..Default::default()
+        };
+        let password = generate(&policy).unwrap();
+        assert!(password.expose().chars().all(|c| c == '#' || c == '%'));
+    }
+}
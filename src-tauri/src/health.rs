@@ -0,0 +1,333 @@
+// ============================================================================
+// X-PASS Password Manager
+// Copyright (C) 2026 ar3love
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See LICENSE.
+// ============================================================================
+
+// src-tauri/src/health.rs
+//
+// Vault health report for the security dashboard. The vault is decrypted
+// once in the backend and only findings leave it: which entries share a
+// password is told by a group number, never by the password itself.
+//
+// Passwords count as similar when they differ in at most two edits, or when
+// only digits and symbols tell them apart ("Summer2024!", "summer2025").
+// Reuse of the master password is only checked when the caller passes the
+// master password in, which is verified first. A pseudo session gets the
+// report for the entries it is shown.
+use std::collections::HashMap;
+
+use chrono::{DateTime, Months, Utc};
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use tauri::State;
+use zeroize::Zeroizing;
+
+use crate::database::Database;
+use crate::error::XPassError;
+use crate::keys;
+use crate::kinds::EntryKind;
+use crate::password_manager::{self, Entry};
+use crate::secrets::SecretString;
+use crate::session::SessionStore;
+use crate::strength;
+
+pub const DEFAULT_STALE_MONTHS: u32 = 12;
+const MAX_STALE_MONTHS: u32 = 120;
+/// Edits that still make two passwords similar
+const MAX_SIMILAR_DISTANCE: usize = 2;
+/// Shorter passwords are too close to everything to compare
+const MIN_SIMILAR_CHARS: usize = 8;
+const MIN_SKELETON_CHARS: usize = 4;
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FindingKind {
+    /// The entry's password is the master password
+    MasterReused,
+    /// Other entries use the same password
+    Reused,
+    Weak,
+    /// Other entries use a nearly identical password
+    Similar,
+    /// A login without a password
+    Empty,
+    /// The password has not changed for the configured number of months
+    Stale,
+}
+
+#[derive(Serialize, Debug)]
+pub struct Finding {
+    pub entry_id: i64,
+    pub service: String,
+    pub kind: FindingKind,
+    pub severity: Severity,
+    /// Entries with the same kind and group share their password
+    /// (`reused`) or have similar ones (`similar`)
+    pub group: Option<usize>,
+    /// Strength score, for `weak`
+    pub score: Option<u8>,
+    /// When the password last changed, for `stale`
+    pub changed_at: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct HealthReport {
+    pub entries_checked: usize,
+    /// Whether entries were compared with the master password
+    pub master_checked: bool,
+    /// Most severe first
+    pub findings: Vec<Finding>,
+}
+
+impl Finding {
+    fn new(entry: &Entry, kind: FindingKind, severity: Severity) -> Finding {
+        Finding {
+            entry_id: entry.id,
+            service: entry.service.clone(),
+            kind,
+            severity,
+            group: None,
+            score: None,
+            changed_at: None,
+        }
+    }
+}
+
+/// Letters of a password without case, digits and symbols
+fn skeleton(password: &str) -> Zeroizing<String> {
+    Zeroizing::new(password.chars().filter(|c| c.is_alphabetic()).flat_map(char::to_lowercase).collect())
+}
+
+/// Whether the edit distance between two passwords is at most `limit`.
+/// Only the diagonal band of the distance matrix that can stay within the
+/// limit is computed, in the two rows of `buffer`.
+fn within_distance(a: &[char], b: &[char], limit: usize, buffer: &mut [Vec<usize>; 2]) -> bool {
+    if a.len().abs_diff(b.len()) > limit {
+        return false;
+    }
+    let beyond = limit + 1;
+    let [previous, current] = buffer;
+    previous.clear();
+    previous.extend((0..=b.len()).map(|j| j.min(beyond)));
+    for (i, ca) in a.iter().enumerate() {
+        let row = i + 1;
+        let from = row.saturating_sub(limit).max(1);
+        let to = (row + limit).min(b.len());
+        current.clear();
+        current.resize(b.len() + 1, beyond);
+        current[0] = row.min(beyond);
+        for j in from..=to {
+            let substitution = previous[j - 1] + usize::from(*ca != b[j - 1]);
+            current[j] = substitution.min(previous[j] + 1).min(current[j - 1] + 1).min(beyond);
+        }
+        // Later rows never get below the smallest value of this one
+        if current[from - 1..=to].iter().all(|&d| d > limit) {
+            return false;
+        }
+        std::mem::swap(previous, current);
+    }
+    previous[b.len()] <= limit
+}
+
+fn find_root(parents: &mut [usize], mut i: usize) -> usize {
+    while parents[i] != i {
+        parents[i] = parents[parents[i]];
+        i = parents[i];
+    }
+    i
+}
+
+/// Groups of different passwords that are similar to each other, as lists
+/// of indexes into `passwords`
+fn similar_groups(passwords: &[&SecretString]) -> Vec<Vec<usize>> {
+    let chars: Vec<Zeroizing<Vec<char>>> = passwords.iter()
+        .map(|password| Zeroizing::new(password.expose().chars().collect()))
+        .collect();
+    let mut parents: Vec<usize> = (0..passwords.len()).collect();
+
+    let skeletons: Vec<Zeroizing<String>> = passwords.iter().map(|password| skeleton(password.expose())).collect();
+    let mut by_skeleton: HashMap<&str, usize> = HashMap::new();
+    for (i, skeleton) in skeletons.iter().enumerate() {
+        if skeleton.chars().count() < MIN_SKELETON_CHARS {
+            continue;
+        }
+        if let Some(&first) = by_skeleton.get(skeleton.as_str()) {
+            let (a, b) = (find_root(&mut parents, first), find_root(&mut parents, i));
+            parents[b] = a;
+        } else {
+            by_skeleton.insert(skeleton, i);
+        }
+    }
+
+    let mut buffer = [Vec::new(), Vec::new()];
+    for (i, a) in chars.iter().enumerate() {
+        if a.len() < MIN_SIMILAR_CHARS {
+            continue;
+        }
+        for (j, b) in chars.iter().enumerate().skip(i + 1) {
+            if b.len() < MIN_SIMILAR_CHARS {
+                continue;
+            }
+            let (root_a, root_b) = (find_root(&mut parents, i), find_root(&mut parents, j));
+            if root_a != root_b && within_distance(a, b, MAX_SIMILAR_DISTANCE, &mut buffer) {
+                parents[root_b] = root_a;
+            }
+        }
+    }
+
+    let mut groups: HashMap<usize, Vec<usize>> = HashMap::new();
+    for i in 0..passwords.len() {
+        let root = find_root(&mut parents, i);
+        groups.entry(root).or_default().push(i);
+    }
+    let mut groups: Vec<Vec<usize>> = groups.into_values().filter(|group| group.len() > 1).collect();
+    groups.sort();
+    groups
+}
+
+fn password_change_dates(conn: &Connection, user_id: i64) -> Result<HashMap<i64, String>, XPassError> {
+    let mut stmt = conn.prepare(
+        "SELECT id, password_changed_at FROM entries
+         WHERE user_id = ?1 AND deleted_at IS NULL AND password_changed_at IS NOT NULL"
+    )?;
+    let dates = stmt.query_map(params![user_id], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<_, _>>()?;
+    Ok(dates)
+}
+
+/// Checks the entries. `master_password` is the verified master password,
+/// if reuse of it should be reported.
+fn build_report(
+    entries: &[Entry],
+    master_password: Option<&SecretString>,
+    changed_at: &HashMap<i64, String>,
+    stale_before: DateTime<Utc>
+) -> HealthReport {
+    let mut findings = Vec::new();
+
+    // Entries by distinct password, in order of first use
+    let mut distinct: Vec<&SecretString> = Vec::new();
+    let mut users: Vec<Vec<&Entry>> = Vec::new();
+    let mut positions: HashMap<&str, usize> = HashMap::new();
+    for entry in entries {
+        if entry.password.is_empty() {
+            if entry.kind == EntryKind::Login {
+                findings.push(Finding::new(entry, FindingKind::Empty, Severity::Medium));
+            }
+            continue;
+        }
+        match positions.get(entry.password.expose()) {
+            Some(&i) => users[i].push(entry),
+            None => {
+                positions.insert(entry.password.expose(), distinct.len());
+                distinct.push(&entry.password);
+                users.push(vec![entry]);
+            }
+        }
+    }
+
+    let mut reused_groups = 0;
+    for (password, entries) in distinct.iter().zip(&users) {
+        if master_password.is_some_and(|master| master == *password) {
+            for entry in entries {
+                findings.push(Finding::new(entry, FindingKind::MasterReused, Severity::Critical));
+            }
+        }
+        if entries.len() > 1 {
+            reused_groups += 1;
+            for entry in entries {
+                findings.push(Finding { group: Some(reused_groups), ..Finding::new(entry, FindingKind::Reused, Severity::High) });
+            }
+        }
+        for entry in entries {
+            let score = entry.strength.as_ref()
+                .map(|strength| strength.score)
+                .unwrap_or_else(|| strength::estimate(password, &[&entry.service, &entry.login]).score);
+            if score < strength::MIN_MASTER_SCORE {
+                let severity = if score <= 1 { Severity::High } else { Severity::Medium };
+                findings.push(Finding { score: Some(score), ..Finding::new(entry, FindingKind::Weak, severity) });
+            }
+        }
+    }
+
+    for (group, members) in similar_groups(&distinct).into_iter().enumerate() {
+        for entry in members.into_iter().flat_map(|i| &users[i]) {
+            findings.push(Finding { group: Some(group + 1), ..Finding::new(entry, FindingKind::Similar, Severity::Medium) });
+        }
+    }
+
+    for entry in entries.iter().filter(|entry| !entry.password.is_empty()) {
+        let Some(date) = changed_at.get(&entry.id) else {
+            continue;
+        };
+        let stale = DateTime::parse_from_rfc3339(date).is_ok_and(|date| date < stale_before);
+        if stale {
+            findings.push(Finding { changed_at: Some(date.clone()), ..Finding::new(entry, FindingKind::Stale, Severity::Low) });
+        }
+    }
+
+    findings.sort_by(|a, b| b.severity.cmp(&a.severity).then(a.entry_id.cmp(&b.entry_id)));
+    HealthReport {
+        entries_checked: entries.len(),
+        master_checked: master_password.is_some(),
+        findings,
+    }
+}
+
+/// Security report over the vault: reused, weak, similar, empty and stale
+/// passwords, and entries using the master password when it is given.
+/// Passwords older than `stale_months` (12 by default) are stale.
+#[tauri::command]
+pub fn vault_health_report(
+    db: State<'_, Database>,
+    sessions: State<'_, SessionStore>,
+    user_id: i64,
+    session_id: String,
+    master_password: Option<SecretString>,
+    stale_months: Option<u32>
+) -> Result<HealthReport, XPassError> {
+    let stale_months = stale_months.unwrap_or(DEFAULT_STALE_MONTHS);
+    if !(1..=MAX_STALE_MONTHS).contains(&stale_months) {
+        return Err(XPassError::invalid_input("stale_months", format!("must be 1 to {}", MAX_STALE_MONTHS)));
+    }
+    let stale_before = Utc::now()
+        .checked_sub_months(Months::new(stale_months))
+        .ok_or_else(|| XPassError::invalid_input("stale_months", "out of range"))?;
+
+    let cipher = sessions.cipher(&session_id, user_id)?;
+    let conn = db.conn()?;
+
+    if !keys::is_vault_key(&conn, user_id, &cipher)? {
+        // The password is checked like the master password would be.
+        // Fake entries have no history to be stale or to reuse anything real
+        if let Some(master_password) = &master_password {
+            password_manager::check_pseudo_password(&conn, user_id, master_password)?;
+        }
+        let entries = password_manager::pseudo_entries(&conn, user_id);
+        return Ok(build_report(&entries, master_password.as_ref(), &HashMap::new(), stale_before));
+    }
+
+    if let Some(master_password) = &master_password {
+        password_manager::check_master_password(&conn, user_id, master_password)?;
+    }
+    let entries = password_manager::load_entries(&conn, user_id, &cipher)?;
+    let changed_at = password_change_dates(&conn, user_id)?;
+    Ok(build_report(&entries, master_password.as_ref(), &changed_at, stale_before))
+}
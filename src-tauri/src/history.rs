@@ -0,0 +1,403 @@
+// ============================================================================
+// X-PASS Password Manager
+// Copyright (C) 2026 ar3love
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See LICENSE.
+// ============================================================================
+
+// src-tauri/src/history.rs
+//
+// Revision history of vault entries. Before an entry is overwritten its
+// current row is copied into `entry_history` as is, still encrypted with the
+// data key, and its custom fields and URLs into `entry_history_fields` and
+// `entry_history_urls`, so keeping history needs no decryption. Revisions
+// from before fields and URLs were recorded leave those of the entry alone
+// when restored.
+//
+// Each user keeps at most `max_depth` revisions per entry, oldest dropped
+// first; 0 turns history off. Revisions go away with their entry.
+use aes_gcm::Aes256Gcm;
+use chrono::Utc;
+use log::info;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::database::Database;
+use crate::error::XPassError;
+use crate::fields::{self, CustomField};
+use crate::keys;
+use crate::kinds::{self, Details, EntryKind};
+use crate::secrets::SecretString;
+use crate::session::SessionStore;
+use crate::urls::{self, EntryUrl};
+
+pub const DEFAULT_MAX_DEPTH: u32 = 10;
+/// Highest depth that can be configured
+const MAX_DEPTH_LIMIT: u32 = 100;
+
+/// Decrypted content of an entry as it was at `created_at`
+#[derive(Serialize, Deserialize, Clone)]
+pub struct RevisionData {
+    pub service: String,
+    pub login: String,
+    pub password: SecretString,
+    pub note: SecretString,
+    #[serde(default)]
+    pub kind: EntryKind,
+    #[serde(default)]
+    pub details: Details,
+    /// Absent for revisions saved before fields and URLs were recorded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fields: Option<Vec<CustomField>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub urls: Option<Vec<EntryUrl>>,
+    /// When this version was replaced
+    pub created_at: String,
+}
+
+#[derive(Serialize)]
+pub struct EntryRevision {
+    pub id: i64,
+    pub entry_id: i64,
+    #[serde(flatten)]
+    pub data: RevisionData,
+}
+
+/// Custom fields and URLs recorded with a revision
+type RevisionExtras = (Vec<CustomField>, Vec<EntryUrl>);
+
+struct RawRevision {
+    id: i64,
+    entry_id: i64,
+    service: String,
+    login: String,
+    enc_password: Vec<u8>,
+    password_nonce: Vec<u8>,
+    enc_note: Vec<u8>,
+    note_nonce: Vec<u8>,
+    kind: String,
+    enc_details: Option<Vec<u8>>,
+    details_nonce: Option<Vec<u8>>,
+    created_at: String,
+    fields_recorded: bool,
+}
+
+const REVISION_COLUMNS: &str =
+    "id, entry_id, service, login, enc_password, password_nonce, enc_note, note_nonce, kind, enc_details, details_nonce, created_at,
+     fields_recorded";
+
+impl RawRevision {
+    fn from_row(row: &rusqlite::Row) -> rusqlite::Result<RawRevision> {
+        Ok(RawRevision {
+            id: row.get(0)?,
+            entry_id: row.get(1)?,
+            service: row.get(2)?,
+            login: row.get(3)?,
+            enc_password: row.get(4)?,
+            password_nonce: row.get(5)?,
+            enc_note: row.get(6)?,
+            note_nonce: row.get(7)?,
+            kind: row.get(8)?,
+            enc_details: row.get(9)?,
+            details_nonce: row.get(10)?,
+            created_at: row.get(11)?,
+            fields_recorded: row.get(12)?,
+        })
+    }
+
+    /// Custom fields and URLs of the revision, if it recorded them
+    fn load_fields_and_urls(&self, conn: &Connection, cipher: &Aes256Gcm) -> Result<Option<RevisionExtras>, XPassError> {
+        if !self.fields_recorded {
+            return Ok(None);
+        }
+        Ok(Some((fields::load_revision_fields(conn, self.id, cipher)?, urls::load_revision_urls(conn, self.id, cipher)?)))
+    }
+
+    fn decrypt(self, conn: &Connection, cipher: &Aes256Gcm) -> Result<EntryRevision, XPassError> {
+        let (fields, urls) = self.load_fields_and_urls(conn, cipher)?.unzip();
+        Ok(EntryRevision {
+            id: self.id,
+            entry_id: self.entry_id,
+            data: RevisionData {
+                password: keys::decrypt_secret(cipher, &self.enc_password, &self.password_nonce)?,
+                note: keys::decrypt_secret(cipher, &self.enc_note, &self.note_nonce)?,
+                kind: EntryKind::parse(&self.kind),
+                details: kinds::open(cipher, self.enc_details.as_deref(), self.details_nonce.as_deref())?,
+                fields,
+                urls,
+                service: self.service,
+                login: self.login,
+                created_at: self.created_at,
+            },
+        })
+    }
+}
+
+/// Revisions kept per entry for this user
+pub fn max_depth(conn: &Connection, user_id: i64) -> Result<u32, XPassError> {
+    let depth = conn.query_row(
+        "SELECT max_depth FROM history_settings WHERE user_id = ?1",
+        params![user_id],
+        |row| row.get(0)
+    ).optional()?;
+    Ok(depth.unwrap_or(DEFAULT_MAX_DEPTH))
+}
+
+/// Drops the oldest revisions of every entry of the user beyond `depth`
+fn prune(conn: &Connection, user_id: i64, depth: u32) -> Result<usize, XPassError> {
+    let removed = conn.execute(
+        "DELETE FROM entry_history WHERE user_id = ?1 AND id NOT IN (
+            SELECT id FROM (
+                SELECT id, ROW_NUMBER() OVER (PARTITION BY entry_id ORDER BY id DESC) AS pos
+                FROM entry_history WHERE user_id = ?1
+            ) WHERE pos <= ?2
+         )",
+        params![user_id, depth],
+    )?;
+    Ok(removed)
+}
+
+/// Saves the current version of an entry as a revision. Call right before
+/// overwriting the entry, in the same transaction.
+pub fn record_revision(conn: &Connection, user_id: i64, entry_id: i64) -> Result<(), XPassError> {
+    let depth = max_depth(conn, user_id)?;
+    if depth == 0 {
+        return Ok(());
+    }
+
+    let recorded = conn.execute(
+        "INSERT INTO entry_history (entry_id, user_id, service, login, enc_password, password_nonce, enc_note, note_nonce,
+                                    kind, enc_details, details_nonce, created_at, fields_recorded)
+         SELECT id, user_id, service, login, enc_password, password_nonce, enc_note, note_nonce,
+                kind, enc_details, details_nonce, ?3, 1
+         FROM entries WHERE id = ?1 AND user_id = ?2",
+        params![entry_id, user_id, Utc::now().to_rfc3339()],
+    )?;
+    if recorded == 0 {
+        return Ok(());
+    }
+    let revision_id = conn.last_insert_rowid();
+    fields::copy_to_revision(conn, entry_id, revision_id)?;
+    urls::copy_to_revision(conn, entry_id, revision_id)?;
+
+    conn.execute(
+        "DELETE FROM entry_history WHERE entry_id = ?1 AND id NOT IN (
+            SELECT id FROM entry_history WHERE entry_id = ?1 ORDER BY id DESC LIMIT ?2
+         )",
+        params![entry_id, depth],
+    )?;
+    Ok(())
+}
+
+/// Decrypted revisions of an entry, newest first
+pub fn list_revisions(conn: &Connection, user_id: i64, entry_id: i64, cipher: &Aes256Gcm) -> Result<Vec<EntryRevision>, XPassError> {
+    let mut stmt = conn.prepare_cached(&format!(
+        "SELECT {} FROM entry_history WHERE entry_id = ?1 AND user_id = ?2 ORDER BY id DESC",
+        REVISION_COLUMNS
+    ))?;
+    let raw: Vec<RawRevision> = stmt.query_map(params![entry_id, user_id], RawRevision::from_row)?
+        .collect::<Result<_, _>>()?;
+
+    raw.into_iter().map(|r| r.decrypt(conn, cipher)).collect()
+}
+
+/// Re-encrypts revisions from a backup (newest first, as `list_revisions`
+/// returns them) and attaches them to an imported entry, keeping at most
+/// the configured depth
+pub fn import_revisions(
+    conn: &Connection,
+    user_id: i64,
+    entry_id: i64,
+    revisions: &[RevisionData],
+    cipher: &Aes256Gcm
+) -> Result<usize, XPassError> {
+    let depth = max_depth(conn, user_id)? as usize;
+    let kept = &revisions[..revisions.len().min(depth)];
+
+    // Oldest first so that ids keep the original order
+    for revision in kept.iter().rev() {
+        let (enc_password, password_nonce) = keys::encrypt_secret(cipher, &revision.password)?;
+        let (enc_note, note_nonce) = keys::encrypt_secret(cipher, &revision.note)?;
+        let (enc_details, details_nonce) = kinds::seal(cipher, &revision.details)?;
+        let fields_recorded = revision.fields.is_some() || revision.urls.is_some();
+        conn.execute(
+            "INSERT INTO entry_history (entry_id, user_id, service, login, enc_password, password_nonce, enc_note, note_nonce,
+                                        kind, enc_details, details_nonce, created_at, fields_recorded)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+            params![
+                entry_id,
+                user_id,
+                &revision.service,
+                &revision.login,
+                enc_password,
+                password_nonce,
+                enc_note,
+                note_nonce,
+                revision.kind.as_str(),
+                enc_details,
+                details_nonce,
+                &revision.created_at,
+                fields_recorded
+            ],
+        )?;
+        let revision_id = conn.last_insert_rowid();
+        fields::save_revision_fields(conn, revision_id, revision.fields.as_deref().unwrap_or_default(), cipher)?;
+        urls::save_revision_urls(conn, revision_id, revision.urls.as_deref().unwrap_or_default(), cipher)?;
+    }
+    Ok(kept.len())
+}
+
+/// Lists the saved revisions of an entry, newest first. A pseudo session
+/// cannot decrypt them and gets an empty history, like an empty vault.
+#[tauri::command]
+pub fn get_entry_history(
+    db: State<'_, Database>,
+    sessions: State<'_, SessionStore>,
+    user_id: i64,
+    session_id: String,
+    entry_id: i64
+) -> Result<Vec<EntryRevision>, XPassError> {
+    let cipher = sessions.cipher(&session_id, user_id)?;
+    let conn = db.conn()?;
+
+    match list_revisions(&conn, user_id, entry_id, &cipher) {
+        Err(XPassError::DecryptionFailed) => Ok(vec![]),
+        result => result,
+    }
+}
+
+/// Puts a revision back into its entry. The version being replaced becomes
+/// a revision itself, so a restore can be undone the same way.
+#[tauri::command]
+pub fn restore_entry_revision(
+    db: State<'_, Database>,
+    sessions: State<'_, SessionStore>,
+    user_id: i64,
+    session_id: String,
+    revision_id: i64
+) -> Result<(), XPassError> {
+    let cipher = sessions.cipher(&session_id, user_id)?;
+    let mut conn = db.conn()?;
+
+    let revision = conn.query_row(
+        &format!("SELECT {} FROM entry_history WHERE id = ?1 AND user_id = ?2", REVISION_COLUMNS),
+        params![revision_id, user_id],
+        RawRevision::from_row
+    ).optional()?.ok_or(XPassError::NotFound { what: "Revision" })?;
+
+    // Only the real data key may restore; a pseudo session must not be able
+    // to tell that the revision exists
+    let restored_password = keys::decrypt_secret(&cipher, &revision.enc_password, &revision.password_nonce)
+        .map_err(|_| XPassError::NotFound { what: "Revision" })?;
+    // Read before recording the current version, which may prune this revision
+    let restored_fields_and_urls = revision.load_fields_and_urls(&conn, &cipher)?;
+
+    let tx = conn.transaction()?;
+    let current: Option<(Vec<u8>, Vec<u8>)> = tx.query_row(
+        "SELECT enc_password, password_nonce FROM entries WHERE id = ?1 AND user_id = ?2 AND deleted_at IS NULL",
+        params![revision.entry_id, user_id],
+        |row| Ok((row.get(0)?, row.get(1)?))
+    ).optional()?;
+    let password_changed = current.is_none_or(|(enc_password, nonce)| {
+        keys::decrypt_secret(&cipher, &enc_password, &nonce).map_or(true, |password| password != restored_password)
+    });
+    record_revision(&tx, user_id, revision.entry_id)?;
+
+    let count = tx.execute(
+        "UPDATE entries
+         SET service = ?1, login = ?2, enc_password = ?3, password_nonce = ?4, enc_note = ?5, note_nonce = ?6,
+             kind = ?7, enc_details = ?8, details_nonce = ?9
+         WHERE id = ?10 AND user_id = ?11 AND deleted_at IS NULL",
+        params![
+            &revision.service,
+            &revision.login,
+            &revision.enc_password,
+            &revision.password_nonce,
+            &revision.enc_note,
+            &revision.note_nonce,
+            &revision.kind,
+            &revision.enc_details,
+            &revision.details_nonce,
+            revision.entry_id,
+            user_id
+        ],
+    )?;
+    if count == 0 {
+        return Err(XPassError::NotFound { what: "Entry" });
+    }
+    if password_changed {
+        tx.execute(
+            "UPDATE entries SET password_changed_at = ?1 WHERE id = ?2",
+            params![Utc::now().to_rfc3339(), revision.entry_id],
+        )?;
+    }
+    if let Some((restored_fields, restored_urls)) = &restored_fields_and_urls {
+        fields::save_fields(&tx, revision.entry_id, restored_fields, &cipher)?;
+        urls::save_urls(&tx, revision.entry_id, restored_urls, &cipher)?;
+    }
+
+    crate::activity_logger::log_activity(
+        &tx,
+        user_id,
+        "entry_restored",
+        &format!("Restored entry for service: {} to its version from {}", revision.service, revision.created_at)
+    )?;
+
+    tx.commit()?;
+    Ok(())
+}
+
+/// Returns how many revisions are kept per entry
+#[tauri::command]
+pub fn get_history_depth(db: State<'_, Database>, user_id: i64) -> Result<u32, XPassError> {
+    let conn = db.conn()?;
+    max_depth(&conn, user_id)
+}
+
+/// Sets how many revisions are kept per entry. Lowering it drops the oldest
+/// revisions right away, 0 clears the history and stops recording it.
+#[tauri::command]
+pub fn set_history_depth(
+    db: State<'_, Database>,
+    sessions: State<'_, SessionStore>,
+    user_id: i64,
+    session_id: String,
+    max_depth: u32
+) -> Result<u32, XPassError> {
+    let cipher = sessions.cipher(&session_id, user_id)?;
+    if max_depth > MAX_DEPTH_LIMIT {
+        return Err(XPassError::invalid_input("max_depth", format!("must be at most {}", MAX_DEPTH_LIMIT)));
+    }
+
+    let mut conn = db.conn()?;
+    // A pseudo session must neither prune the real history nor notice that
+    // the setting was not applied
+    if !keys::is_vault_key(&conn, user_id, &cipher)? {
+        return Ok(max_depth);
+    }
+    let tx = conn.transaction()?;
+    tx.execute(
+        "INSERT INTO history_settings (user_id, max_depth) VALUES (?1, ?2)
+         ON CONFLICT(user_id) DO UPDATE SET max_depth = ?2",
+        params![user_id, max_depth],
+    )?;
+    let removed = prune(&tx, user_id, max_depth)?;
+
+    crate::activity_logger::log_activity(
+        &tx,
+        user_id,
+        "history_depth_changed",
+        &format!("Entry history limited to {} revisions, {} removed", max_depth, removed)
+    )?;
+    tx.commit()?;
+
+    info!("History depth set to {} for user_id {}", max_depth, user_id);
+    Ok(max_depth)
+}
@@ -0,0 +1,228 @@
+// ============================================================================
+// X-PASS Password Manager
+// Copyright (C) 2026 ar3love
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See LICENSE.
+// ============================================================================
+
+// src-tauri/src/kdf.rs
+//
+// Argon2 cost parameters. The vault keeps a policy (calibrated for the
+// machine) and every user records the parameters their KEK was derived
+// with. Password hashes carry their own parameters in the PHC string. When
+// either is below the policy, the next successful login rehashes the
+// password and re-wraps the data key.
+use std::time::{Duration, Instant};
+
+use argon2::{Algorithm, Argon2, Params, PasswordHash, Version};
+use chrono::Utc;
+use log::info;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::database::Database;
+use crate::error::XPassError;
+use crate::keys;
+use crate::password_manager;
+use crate::secrets::SecretString;
+use crate::session::SessionStore;
+
+/// Unlock time the calibration aims for when none is given
+const DEFAULT_TARGET_MS: u64 = 500;
+const MIN_TARGET_MS: u64 = 100;
+const MAX_TARGET_MS: u64 = 5000;
+/// Memory the calibration starts from before trading it for time
+const CALIBRATION_M_COST: u32 = 64 * 1024;
+const MAX_T_COST: u32 = 10;
+const MAX_P_COST: u32 = 4;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KdfParams {
+    /// Memory in KiB
+    pub m_cost: u32,
+    /// Iterations
+    pub t_cost: u32,
+    /// Lanes
+    pub p_cost: u32,
+}
+
+impl KdfParams {
+    /// The `Argon2::default()` parameters every vault used before this was
+    /// configurable. Also the lowest policy that can be set.
+    pub const LEGACY: KdfParams = KdfParams {
+        m_cost: Params::DEFAULT_M_COST,
+        t_cost: Params::DEFAULT_T_COST,
+        p_cost: Params::DEFAULT_P_COST,
+    };
+
+    pub fn argon2(&self) -> Result<Argon2<'static>, XPassError> {
+        let params = Params::new(self.m_cost, self.t_cost, self.p_cost, None)?;
+        Ok(Argon2::new(Algorithm::Argon2id, Version::V0x13, params))
+    }
+
+    /// Parameters a PHC hash string was created with
+    pub fn from_hash(hash: &PasswordHash) -> Option<KdfParams> {
+        let params = Params::try_from(hash).ok()?;
+        Some(KdfParams {
+            m_cost: params.m_cost(),
+            t_cost: params.t_cost(),
+            p_cost: params.p_cost(),
+        })
+    }
+
+    /// True if any cost is lower than in `policy`
+    pub fn is_below(&self, policy: &KdfParams) -> bool {
+        self.m_cost < policy.m_cost || self.t_cost < policy.t_cost || self.p_cost < policy.p_cost
+    }
+
+    /// Raises every cost to at least the legacy defaults
+    fn at_least_legacy(self) -> KdfParams {
+        KdfParams {
+            m_cost: self.m_cost.max(Self::LEGACY.m_cost),
+            t_cost: self.t_cost.max(Self::LEGACY.t_cost),
+            p_cost: self.p_cost.max(Self::LEGACY.p_cost),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct KdfCalibration {
+    pub params: KdfParams,
+    /// Measured time of one derivation with `params`
+    pub elapsed_ms: u64,
+    pub target_ms: u64,
+    pub applied: bool,
+}
+
+/// Current vault policy, the legacy defaults if none was calibrated
+pub fn load_policy(conn: &Connection) -> Result<KdfParams, XPassError> {
+    let policy = conn.query_row(
+        "SELECT m_cost, t_cost, p_cost FROM kdf_policy WHERE id = 1",
+        [],
+        |row| Ok(KdfParams { m_cost: row.get(0)?, t_cost: row.get(1)?, p_cost: row.get(2)? })
+    ).optional()?;
+    Ok(policy.unwrap_or(KdfParams::LEGACY))
+}
+
+fn save_policy(conn: &Connection, policy: &KdfParams) -> Result<(), XPassError> {
+    conn.execute(
+        "INSERT INTO kdf_policy (id, m_cost, t_cost, p_cost, updated_at) VALUES (1, ?1, ?2, ?3, ?4)
+         ON CONFLICT(id) DO UPDATE SET m_cost = ?1, t_cost = ?2, p_cost = ?3, updated_at = ?4",
+        params![policy.m_cost, policy.t_cost, policy.p_cost, Utc::now().to_rfc3339()],
+    )?;
+    Ok(())
+}
+
+/// Parameters the user's KEK was derived with
+pub fn user_params(conn: &Connection, user_id: i64) -> Result<KdfParams, XPassError> {
+    let (m_cost, t_cost, p_cost): (Option<u32>, Option<u32>, Option<u32>) = conn.query_row(
+        "SELECT kdf_m_cost, kdf_t_cost, kdf_p_cost FROM users WHERE id = ?1",
+        params![user_id],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+    ).map_err(|_| XPassError::UserNotFound)?;
+
+    match (m_cost, t_cost, p_cost) {
+        (Some(m_cost), Some(t_cost), Some(p_cost)) => Ok(KdfParams { m_cost, t_cost, p_cost }),
+        _ => Ok(KdfParams::LEGACY),
+    }
+}
+
+fn time_derivation(params: &KdfParams) -> Result<Duration, XPassError> {
+    let argon2 = params.argon2()?;
+    let mut out = [0u8; 32];
+    let start = Instant::now();
+    argon2.hash_password_into(b"x-pass calibration", b"calibration-salt", &mut out)?;
+    Ok(start.elapsed())
+}
+
+/// Finds parameters whose derivation takes about `target` on this machine.
+/// Memory is preferred over iterations; memory is only reduced (down to the
+/// legacy default) if a single pass is already too slow.
+pub fn calibrate(target: Duration) -> Result<(KdfParams, Duration), XPassError> {
+    let lanes = std::thread::available_parallelism()
+        .map(|n| n.get() as u32)
+        .unwrap_or(1)
+        .clamp(1, MAX_P_COST);
+
+    let mut params = KdfParams { m_cost: CALIBRATION_M_COST, t_cost: 1, p_cost: lanes };
+    let mut single_pass = time_derivation(&params)?;
+    while single_pass > target && params.m_cost / 2 >= KdfParams::LEGACY.m_cost {
+        params.m_cost /= 2;
+        single_pass = time_derivation(&params)?;
+    }
+
+    let per_pass = single_pass.as_secs_f64().max(0.001);
+    params.t_cost = ((target.as_secs_f64() / per_pass).round() as u32).clamp(1, MAX_T_COST);
+    let params = params.at_least_legacy();
+
+    let elapsed = time_derivation(&params)?;
+    Ok((params, elapsed))
+}
+
+/// Returns the vault KDF policy
+#[tauri::command]
+pub fn get_kdf_policy(db: State<'_, Database>) -> Result<KdfParams, XPassError> {
+    let conn = db.conn()?;
+    load_policy(&conn)
+}
+
+/// Benchmarks Argon2 for the given unlock time and optionally makes the
+/// result the vault policy. Users are upgraded on their next login.
+/// Benchmarking is open to anyone; applying takes a session and the master
+/// password. A pseudo session has its pseudo password checked instead and
+/// the result is not stored.
+#[tauri::command]
+pub async fn calibrate_kdf(
+    db: State<'_, Database>,
+    sessions: State<'_, SessionStore>,
+    target_ms: Option<u64>,
+    apply: bool,
+    user_id: Option<i64>,
+    session_id: Option<String>,
+    master_password: Option<SecretString>
+) -> Result<KdfCalibration, XPassError> {
+    let target_ms = target_ms.unwrap_or(DEFAULT_TARGET_MS).clamp(MIN_TARGET_MS, MAX_TARGET_MS);
+    let store = if apply {
+        let (Some(user_id), Some(session_id), Some(master_password)) = (user_id, session_id, master_password) else {
+            return Err(XPassError::invalid_input("apply", "needs user_id, session_id and master_password"));
+        };
+        let cipher = sessions.cipher(&session_id, user_id)?;
+        let conn = db.conn()?;
+        if keys::is_vault_key(&conn, user_id, &cipher)? {
+            password_manager::check_master_password(&conn, user_id, &master_password)?;
+            true
+        } else {
+            password_manager::check_pseudo_password(&conn, user_id, &master_password)?;
+            false
+        }
+    } else {
+        false
+    };
+
+    let (params, elapsed) = tauri::async_runtime::spawn_blocking(move || calibrate(Duration::from_millis(target_ms)))
+        .await
+        .map_err(XPassError::internal)??;
+
+    if store {
+        let conn = db.conn()?;
+        save_policy(&conn, &params)?;
+        info!(
+            "KDF policy set to m={} KiB, t={}, p={} ({} ms)",
+            params.m_cost, params.t_cost, params.p_cost, elapsed.as_millis()
+        );
+    }
+
+    Ok(KdfCalibration {
+        params,
+        elapsed_ms: elapsed.as_millis() as u64,
+        target_ms,
+        applied: apply,
+    })
+}
@@ -0,0 +1,259 @@
+// ============================================================================
+// X-PASS Password Manager
+// Copyright (C) 2026 ar3love
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See LICENSE.
+// ============================================================================
+
+// src-tauri/src/keys.rs
+//
+// Vault key hierarchy. Entries are encrypted with a random per-user data key
+// (DEK). The DEK is only ever stored wrapped: by a key-encryption key (KEK)
+// derived from the master password, and by any other unlock method (OTP
+// recovery, backups). Changing the password or adding an unlock method
+// re-wraps these 32 bytes instead of re-encrypting the vault.
+//
+// The KEK salt (`users.kek_salt`) is independent of the password hash, so the
+// stored verification hash says nothing about the KEK. Its Argon2 parameters
+// are recorded next to it (see kdf.rs).
+//
+// `users.key_check` is a known value encrypted with the DEK. It lets commands
+// tell a session holding the real DEK from a pseudo session (which holds a
+// random key) before they write anything encrypted.
+use aes_gcm::{
+    aead::{Aead, AeadCore},
+    Aes256Gcm,
+    Nonce,
+};
+use argon2::password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, SaltString};
+use log::info;
+use rusqlite::{params, Connection};
+
+use crate::error::XPassError;
+use crate::kdf::{self, KdfParams};
+use crate::secrets::{SecretBytes, SecretKey, SecretString};
+
+const KEY_CHECK_PLAINTEXT: &[u8] = b"x-pass vault key check";
+
+/// Generates a fresh random data key
+pub fn generate_dek() -> SecretKey {
+    SecretKey::random()
+}
+
+/// Derives a 32-byte key from a secret and a b64 salt string.
+/// Used for the password KEK, the OTP recovery key and, for vaults created
+/// before the key hierarchy, the entry key itself.
+pub fn derive_key(secret: &SecretString, salt_str: &str, kdf_params: &KdfParams) -> Result<SecretKey, XPassError> {
+    let mut key = SecretKey::zeroed();
+    kdf_params.argon2()?.hash_password_into(secret.expose().as_bytes(), salt_str.as_bytes(), key.expose_mut())?;
+    Ok(key)
+}
+
+/// Encrypts `dek` under `kek`, returns the wrapped key and its nonce
+pub fn wrap_key(kek: &SecretKey, dek: &SecretKey) -> Result<(Vec<u8>, Vec<u8>), XPassError> {
+    let nonce = Aes256Gcm::generate_nonce(&mut rand::thread_rng());
+    let wrapped = kek.cipher().encrypt(&nonce, dek.expose().as_ref())
+        .map_err(|_| XPassError::EncryptionFailed)?;
+    Ok((wrapped, nonce.to_vec()))
+}
+
+/// Decrypts a key wrapped by `wrap_key`; fails if `kek` is not the right key
+pub fn unwrap_key(kek: &SecretKey, wrapped: &[u8], nonce: &[u8]) -> Result<SecretKey, XPassError> {
+    if nonce.len() != 12 {
+        return Err(XPassError::CorruptedKeyMaterial { what: "key nonce" });
+    }
+
+    let plain = SecretBytes::from(
+        kek.cipher().decrypt(Nonce::from_slice(nonce), wrapped)
+            .map_err(|_| XPassError::DecryptionFailed)?
+    );
+
+    SecretKey::from_slice(plain.expose())
+        .ok_or(XPassError::CorruptedKeyMaterial { what: "key length" })
+}
+
+/// Encrypts a string field under its own random nonce, returns the
+/// ciphertext and the nonce
+pub fn encrypt_secret(cipher: &Aes256Gcm, value: &SecretString) -> Result<(Vec<u8>, Vec<u8>), XPassError> {
+    let nonce = Aes256Gcm::generate_nonce(&mut rand::thread_rng());
+    let ciphertext = cipher.encrypt(&nonce, value.expose().as_bytes())
+        .map_err(|_| XPassError::EncryptionFailed)?;
+    Ok((ciphertext, nonce.to_vec()))
+}
+
+/// Decrypts a field written by `encrypt_secret`
+pub fn decrypt_secret(cipher: &Aes256Gcm, ciphertext: &[u8], nonce: &[u8]) -> Result<SecretString, XPassError> {
+    if nonce.len() != 12 {
+        return Err(XPassError::DecryptionFailed);
+    }
+    let plain = cipher.decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| XPassError::DecryptionFailed)?;
+    SecretString::from_utf8(SecretBytes::from(plain)).ok_or(XPassError::DecryptionFailed)
+}
+
+/// Wraps `dek` under a KEK derived from `password` with a fresh salt and the
+/// current KDF policy, and stores it
+pub fn store_password_wrapped_dek(
+    conn: &Connection,
+    user_id: i64,
+    dek: &SecretKey,
+    password: &SecretString
+) -> Result<(), XPassError> {
+    let policy = kdf::load_policy(conn)?;
+    let kek_salt = SaltString::generate(&mut OsRng);
+    let kek = derive_key(password, kek_salt.as_str(), &policy)?;
+    let (wrapped_dek, dek_nonce) = wrap_key(&kek, dek)?;
+
+    conn.execute(
+        "UPDATE users SET wrapped_dek = ?1, dek_nonce = ?2, kek_salt = ?3,
+                          kdf_m_cost = ?4, kdf_t_cost = ?5, kdf_p_cost = ?6
+         WHERE id = ?7",
+        params![
+            wrapped_dek,
+            dek_nonce,
+            kek_salt.as_str(),
+            policy.m_cost,
+            policy.t_cost,
+            policy.p_cost,
+            user_id
+        ],
+    )?;
+    Ok(())
+}
+
+/// Hashes a password with the current KDF policy, returns the salt and PHC string
+pub fn hash_password(conn: &Connection, password: &SecretString) -> Result<(SaltString, String), XPassError> {
+    let policy = kdf::load_policy(conn)?;
+    let salt = SaltString::generate(&mut OsRng);
+    let hash = policy.argon2()?.hash_password(password.expose().as_bytes(), &salt)?.to_string();
+    Ok((salt, hash))
+}
+
+/// Rehashes the password and re-wraps the data key if either was created
+/// with weaker parameters than the current policy. Returns true if it did.
+pub fn upgrade_password_kdf(
+    conn: &Connection,
+    user_id: i64,
+    dek: &SecretKey,
+    password: &SecretString
+) -> Result<bool, XPassError> {
+    let policy = kdf::load_policy(conn)?;
+    let hash: String = conn.query_row(
+        "SELECT hash FROM users WHERE id = ?1",
+        params![user_id],
+        |row| row.get(0)
+    ).map_err(|_| XPassError::UserNotFound)?;
+
+    let parsed_hash = PasswordHash::new(&hash)
+        .map_err(|_| XPassError::CorruptedKeyMaterial { what: "password hash" })?;
+    let hash_params = KdfParams::from_hash(&parsed_hash).unwrap_or(KdfParams::LEGACY);
+    let kek_params = kdf::user_params(conn, user_id)?;
+    if !hash_params.is_below(&policy) && !kek_params.is_below(&policy) {
+        return Ok(false);
+    }
+
+    let (salt, hash) = hash_password(conn, password)?;
+    conn.execute(
+        "UPDATE users SET salt = ?1, hash = ?2 WHERE id = ?3",
+        params![salt.as_str(), hash, user_id],
+    )?;
+    store_password_wrapped_dek(conn, user_id, dek, password)?;
+
+    info!(
+        "Upgraded KDF for user_id {} to m={} KiB, t={}, p={}",
+        user_id, policy.m_cost, policy.t_cost, policy.p_cost
+    );
+    Ok(true)
+}
+
+/// Key material kept in a user's row
+pub struct StoredKeys {
+    /// Salt of the legacy password-derived key
+    pub salt: String,
+    /// The data key wrapped by the password KEK, absent for legacy users
+    pub wrapped_dek: Option<Vec<u8>>,
+    pub dek_nonce: Option<Vec<u8>>,
+    pub kek_salt: Option<String>,
+}
+
+pub fn stored_keys(conn: &Connection, user_id: i64) -> Result<StoredKeys, XPassError> {
+    conn.query_row(
+        "SELECT salt, wrapped_dek, dek_nonce, kek_salt FROM users WHERE id = ?1",
+        params![user_id],
+        |row| Ok(StoredKeys {
+            salt: row.get(0)?,
+            wrapped_dek: row.get(1)?,
+            dek_nonce: row.get(2)?,
+            kek_salt: row.get(3)?,
+        })
+    ).map_err(|_| XPassError::UserNotFound)
+}
+
+/// Returns the user's data key for an already verified master password.
+///
+/// Users created before the key hierarchy have no wrapped key yet: their
+/// entries are encrypted with the key derived from the password and
+/// `users.salt`, so that key becomes their DEK and is wrapped on the spot.
+pub fn unlock_dek(conn: &Connection, user_id: i64, password: &SecretString) -> Result<SecretKey, XPassError> {
+    let kek_params = kdf::user_params(conn, user_id)?;
+    let StoredKeys { salt, wrapped_dek, dek_nonce, kek_salt } = stored_keys(conn, user_id)?;
+
+    match (wrapped_dek, dek_nonce, kek_salt) {
+        (Some(wrapped_dek), Some(dek_nonce), Some(kek_salt)) => {
+            let kek = derive_key(password, &kek_salt, &kek_params)?;
+            unwrap_key(&kek, &wrapped_dek, &dek_nonce)
+        }
+        _ => {
+            let dek = derive_key(password, &salt, &KdfParams::LEGACY)?;
+            store_password_wrapped_dek(conn, user_id, &dek, password)?;
+            info!("Migrated user_id {} to a wrapped vault key", user_id);
+            Ok(dek)
+        }
+    }
+}
+
+/// Stores the key check value for `dek` if the user has none yet
+pub fn ensure_key_check(conn: &Connection, user_id: i64, dek: &SecretKey) -> Result<(), XPassError> {
+    let existing: Option<Vec<u8>> = conn.query_row(
+        "SELECT key_check FROM users WHERE id = ?1",
+        params![user_id],
+        |row| row.get(0)
+    ).map_err(|_| XPassError::UserNotFound)?;
+    if existing.is_some() {
+        return Ok(());
+    }
+
+    let nonce = Aes256Gcm::generate_nonce(&mut rand::thread_rng());
+    let key_check = dek.cipher().encrypt(&nonce, KEY_CHECK_PLAINTEXT)
+        .map_err(|_| XPassError::EncryptionFailed)?;
+    conn.execute(
+        "UPDATE users SET key_check = ?1, key_check_nonce = ?2 WHERE id = ?3",
+        params![key_check, nonce.to_vec(), user_id],
+    )?;
+    Ok(())
+}
+
+/// True if `cipher` is keyed with the user's data key, false for a pseudo
+/// session. Every real unlock stores the key check first, so a user without
+/// one can only be in a pseudo session.
+pub fn is_vault_key(conn: &Connection, user_id: i64, cipher: &Aes256Gcm) -> Result<bool, XPassError> {
+    let (key_check, key_check_nonce): (Option<Vec<u8>>, Option<Vec<u8>>) = conn.query_row(
+        "SELECT key_check, key_check_nonce FROM users WHERE id = ?1",
+        params![user_id],
+        |row| Ok((row.get(0)?, row.get(1)?))
+    ).map_err(|_| XPassError::UserNotFound)?;
+
+    Ok(match (key_check, key_check_nonce) {
+        (Some(ciphertext), Some(nonce)) if nonce.len() == 12 => cipher
+            .decrypt(Nonce::from_slice(&nonce), ciphertext.as_ref())
+            .map(|plain| plain == KEY_CHECK_PLAINTEXT)
+            .unwrap_or(false),
+        _ => false,
+    })
+}
@@ -0,0 +1,365 @@
+// ============================================================================
+// X-PASS Password Manager
+// Copyright (C) 2026 ar3love
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See LICENSE.
+// ============================================================================
+
+// src-tauri/src/kinds.rs
+//
+// Entry kinds. Besides website logins an entry can hold a credit card, a bank
+// account, an identity, a Wi-Fi network, a software license, an SSH key or a
+// secure note. `service` is the entry's title for every kind; login and
+// password belong to logins and are left empty by the other kinds, whose
+// values live in `details`, keyed by the names of the kind's schema below.
+//
+// The kind is stored in clear, the details as one encrypted JSON object with
+// a single nonce, so history can copy them along with the rest of the row.
+use std::collections::BTreeMap;
+
+use aes_gcm::Aes256Gcm;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::error::XPassError;
+use crate::keys;
+use crate::secrets::SecretString;
+
+const MAX_VALUE_LEN: usize = 10_000;
+
+pub type Details = BTreeMap<String, SecretString>;
+/// Encrypted details and their nonce, as stored in `entries`
+type SealedDetails = (Option<Vec<u8>>, Option<Vec<u8>>);
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryKind {
+    #[default]
+    Login,
+    CreditCard,
+    BankAccount,
+    Identity,
+    WifiNetwork,
+    SoftwareLicense,
+    SshKey,
+    SecureNote,
+}
+
+/// What a detail value must look like
+#[derive(Serialize, Debug, Clone, Copy)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum ValueFormat {
+    Text,
+    /// 13 to 19 digits passing the Luhn check, spaces and dashes allowed
+    CardNumber,
+    /// MM/YY or MM/YYYY
+    Expiry,
+    Digits { min: usize, max: usize },
+    /// ISO date, YYYY-MM-DD
+    Date,
+    Email,
+    Phone,
+    Iban,
+    Bic,
+    /// "true" or "false"
+    Flag,
+    Choice { options: &'static [&'static str] },
+    PrivateKey,
+    PublicKey,
+}
+
+#[derive(Serialize, Debug, Clone, Copy)]
+pub struct DetailField {
+    pub key: &'static str,
+    pub format: ValueFormat,
+    pub required: bool,
+    /// Shown masked, like the password
+    pub hidden: bool,
+}
+
+const fn field(key: &'static str, format: ValueFormat, required: bool, hidden: bool) -> DetailField {
+    DetailField { key, format, required, hidden }
+}
+
+const WIFI_SECURITY: &[&str] = &["none", "wep", "wpa", "wpa2", "wpa3", "wpa2_enterprise", "wpa3_enterprise"];
+const SSH_KEY_TYPES: &[&str] = &["rsa", "ed25519", "ecdsa", "dsa"];
+
+const CREDIT_CARD: &[DetailField] = &[
+    field("cardholder_name", ValueFormat::Text, false, false),
+    field("card_number", ValueFormat::CardNumber, true, true),
+    field("expiry", ValueFormat::Expiry, true, false),
+    field("cvv", ValueFormat::Digits { min: 3, max: 4 }, false, true),
+    field("pin", ValueFormat::Digits { min: 4, max: 12 }, false, true),
+    field("brand", ValueFormat::Text, false, false),
+];
+
+const BANK_ACCOUNT: &[DetailField] = &[
+    field("bank_name", ValueFormat::Text, true, false),
+    field("account_holder", ValueFormat::Text, false, false),
+    field("account_number", ValueFormat::Text, false, true),
+    field("iban", ValueFormat::Iban, false, true),
+    field("bic", ValueFormat::Bic, false, false),
+    field("routing_number", ValueFormat::Text, false, false),
+    field("pin", ValueFormat::Digits { min: 4, max: 12 }, false, true),
+];
+
+const IDENTITY: &[DetailField] = &[
+    field("first_name", ValueFormat::Text, false, false),
+    field("middle_name", ValueFormat::Text, false, false),
+    field("last_name", ValueFormat::Text, false, false),
+    field("date_of_birth", ValueFormat::Date, false, false),
+    field("email", ValueFormat::Email, false, false),
+    field("phone", ValueFormat::Phone, false, false),
+    field("address", ValueFormat::Text, false, false),
+    field("passport_number", ValueFormat::Text, false, true),
+    field("national_id", ValueFormat::Text, false, true),
+    field("driver_license", ValueFormat::Text, false, true),
+];
+
+const WIFI_NETWORK: &[DetailField] = &[
+    field("ssid", ValueFormat::Text, true, false),
+    field("security", ValueFormat::Choice { options: WIFI_SECURITY }, true, false),
+    field("passphrase", ValueFormat::Text, false, true),
+    field("hidden_network", ValueFormat::Flag, false, false),
+];
+
+const SOFTWARE_LICENSE: &[DetailField] = &[
+    field("product", ValueFormat::Text, true, false),
+    field("license_key", ValueFormat::Text, true, true),
+    field("version", ValueFormat::Text, false, false),
+    field("licensed_to", ValueFormat::Text, false, false),
+    field("email", ValueFormat::Email, false, false),
+    field("purchase_date", ValueFormat::Date, false, false),
+    field("expires", ValueFormat::Date, false, false),
+];
+
+const SSH_KEY: &[DetailField] = &[
+    field("private_key", ValueFormat::PrivateKey, true, true),
+    field("public_key", ValueFormat::PublicKey, false, false),
+    field("passphrase", ValueFormat::Text, false, true),
+    field("key_type", ValueFormat::Choice { options: SSH_KEY_TYPES }, false, false),
+    field("host", ValueFormat::Text, false, false),
+];
+
+impl EntryKind {
+    pub const ALL: [EntryKind; 8] = [
+        EntryKind::Login,
+        EntryKind::CreditCard,
+        EntryKind::BankAccount,
+        EntryKind::Identity,
+        EntryKind::WifiNetwork,
+        EntryKind::SoftwareLicense,
+        EntryKind::SshKey,
+        EntryKind::SecureNote,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EntryKind::Login => "login",
+            EntryKind::CreditCard => "credit_card",
+            EntryKind::BankAccount => "bank_account",
+            EntryKind::Identity => "identity",
+            EntryKind::WifiNetwork => "wifi_network",
+            EntryKind::SoftwareLicense => "software_license",
+            EntryKind::SshKey => "ssh_key",
+            EntryKind::SecureNote => "secure_note",
+        }
+    }
+
+    /// Kinds written by a newer version are shown as secure notes, which
+    /// keeps their details readable without pretending to know the schema
+    pub fn parse(value: &str) -> EntryKind {
+        EntryKind::ALL.into_iter()
+            .find(|kind| kind.as_str() == value)
+            .unwrap_or(EntryKind::SecureNote)
+    }
+
+    /// The details this kind stores; logins and secure notes have none
+    pub fn schema(&self) -> &'static [DetailField] {
+        match self {
+            EntryKind::Login | EntryKind::SecureNote => &[],
+            EntryKind::CreditCard => CREDIT_CARD,
+            EntryKind::BankAccount => BANK_ACCOUNT,
+            EntryKind::Identity => IDENTITY,
+            EntryKind::WifiNetwork => WIFI_NETWORK,
+            EntryKind::SoftwareLicense => SOFTWARE_LICENSE,
+            EntryKind::SshKey => SSH_KEY,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct KindSchema {
+    pub kind: EntryKind,
+    pub fields: &'static [DetailField],
+}
+
+/// Luhn check digit for a string of digits
+pub fn luhn_check_digit(digits: &str) -> u32 {
+    let sum: u32 = digits.chars().rev()
+        .filter_map(|c| c.to_digit(10))
+        .enumerate()
+        .map(|(i, d)| if i % 2 == 0 { let d = d * 2; if d > 9 { d - 9 } else { d } } else { d })
+        .sum();
+    (10 - sum % 10) % 10
+}
+
+fn is_valid_card_number(value: &str) -> bool {
+    let digits: String = value.chars().filter(|c| *c != ' ' && *c != '-').collect();
+    if !(13..=19).contains(&digits.len()) || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return false;
+    }
+    let (body, check) = digits.split_at(digits.len() - 1);
+    check.parse::<u32>().ok() == Some(luhn_check_digit(body))
+}
+
+fn is_valid_iban(value: &str) -> bool {
+    let iban: String = value.chars().filter(|c| !c.is_whitespace()).collect::<String>().to_uppercase();
+    if !(15..=34).contains(&iban.len()) || !iban.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return false;
+    }
+    if !iban[..2].chars().all(|c| c.is_ascii_alphabetic()) || !iban[2..4].chars().all(|c| c.is_ascii_digit()) {
+        return false;
+    }
+
+    // Country code and check digits move to the end, letters count as 10..35
+    let rearranged = iban[4..].chars().chain(iban[..4].chars());
+    let remainder = rearranged.fold(0u32, |acc, c| {
+        let value = c.to_digit(36).unwrap_or(0);
+        if value < 10 { (acc * 10 + value) % 97 } else { (acc * 100 + value) % 97 }
+    });
+    remainder == 1
+}
+
+impl ValueFormat {
+    /// Checks a non-empty value against the format
+    fn accepts(&self, value: &str) -> bool {
+        let value = value.trim();
+        match self {
+            ValueFormat::Text => true,
+            ValueFormat::CardNumber => is_valid_card_number(value),
+            ValueFormat::Expiry => match value.split_once('/') {
+                Some((month, year)) => {
+                    matches!(month.parse::<u32>(), Ok(1..=12))
+                        && month.len() == 2
+                        && (year.len() == 2 || year.len() == 4)
+                        && year.chars().all(|c| c.is_ascii_digit())
+                }
+                None => false,
+            },
+            ValueFormat::Digits { min, max } => {
+                (*min..=*max).contains(&value.len()) && value.chars().all(|c| c.is_ascii_digit())
+            }
+            ValueFormat::Date => NaiveDate::parse_from_str(value, "%Y-%m-%d").is_ok(),
+            ValueFormat::Email => match value.split_once('@') {
+                Some((local, domain)) => !local.is_empty() && domain.contains('.') && !value.contains(char::is_whitespace),
+                None => false,
+            },
+            ValueFormat::Phone => {
+                value.chars().filter(|c| c.is_ascii_digit()).count() >= 3
+                    && value.chars().all(|c| c.is_ascii_digit() || " +-().".contains(c))
+            }
+            ValueFormat::Iban => is_valid_iban(value),
+            ValueFormat::Bic => {
+                (value.len() == 8 || value.len() == 11)
+                    && value.chars().all(|c| c.is_ascii_alphanumeric())
+                    && value[..6].chars().all(|c| c.is_ascii_alphabetic())
+            }
+            ValueFormat::Flag => value == "true" || value == "false",
+            ValueFormat::Choice { options } => options.contains(&value),
+            ValueFormat::PrivateKey => value.starts_with("-----BEGIN ") && value.contains("PRIVATE KEY-----"),
+            ValueFormat::PublicKey => {
+                let mut parts = value.split_whitespace();
+                let algorithm = parts.next().unwrap_or_default();
+                (algorithm.starts_with("ssh-") || algorithm.starts_with("ecdsa-") || algorithm.starts_with("sk-"))
+                    && parts.next().is_some()
+            }
+        }
+    }
+}
+
+fn filled<'a>(details: &'a Details, key: &str) -> Option<&'a str> {
+    details.get(key).map(|v| v.expose().trim()).filter(|v| !v.is_empty())
+}
+
+/// Checks details against the kind's schema: no unknown keys, required
+/// values present, every value in its format, plus the few rules that
+/// involve more than one value
+pub fn validate(kind: EntryKind, details: &Details) -> Result<(), XPassError> {
+    let schema = kind.schema();
+
+    for (key, value) in details {
+        let field = schema.iter().find(|f| f.key == key)
+            .ok_or_else(|| XPassError::invalid_input("details", format!("{} has no {} field", kind.as_str(), key)))?;
+        if value.expose().len() > MAX_VALUE_LEN {
+            return Err(XPassError::invalid_input("details", format!("values must be at most {} bytes", MAX_VALUE_LEN)));
+        }
+        if !value.expose().trim().is_empty() && !field.format.accepts(value.expose()) {
+            return Err(XPassError::invalid_input("details", format!("{} is not valid", key)));
+        }
+    }
+    if let Some(missing) = schema.iter().find(|f| f.required && filled(details, f.key).is_none()) {
+        return Err(XPassError::invalid_input("details", format!("{} is required", missing.key)));
+    }
+
+    match kind {
+        EntryKind::BankAccount if filled(details, "account_number").is_none() && filled(details, "iban").is_none() => {
+            Err(XPassError::invalid_input("details", "an account number or IBAN is required"))
+        }
+        EntryKind::Identity if filled(details, "first_name").is_none() && filled(details, "last_name").is_none() => {
+            Err(XPassError::invalid_input("details", "a first or last name is required"))
+        }
+        EntryKind::WifiNetwork => {
+            let security = filled(details, "security").unwrap_or("none");
+            let passphrase = details.get("passphrase").map(|v| v.expose()).unwrap_or_default();
+            // WPA personal takes 8 to 63 characters or a raw 64 digit hex key
+            let personal = matches!(security, "wpa" | "wpa2" | "wpa3");
+            let valid_key = (8..=63).contains(&passphrase.chars().count())
+                || (passphrase.len() == 64 && passphrase.chars().all(|c| c.is_ascii_hexdigit()));
+            if personal && !valid_key {
+                Err(XPassError::invalid_input("details", "WPA passphrases must be 8 to 63 characters"))
+            } else if filled(details, "ssid").is_some_and(|ssid| ssid.len() > 32) {
+                Err(XPassError::invalid_input("details", "SSIDs are at most 32 bytes"))
+            } else {
+                Ok(())
+            }
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Encrypts the details into ciphertext and nonce, both None when there
+/// are no details so that plain logins keep empty columns
+pub fn seal(cipher: &Aes256Gcm, details: &Details) -> Result<SealedDetails, XPassError> {
+    if details.is_empty() {
+        return Ok((None, None));
+    }
+    let json = SecretString::new(serde_json::to_string(details).map_err(XPassError::internal)?);
+    let (enc_details, details_nonce) = keys::encrypt_secret(cipher, &json)?;
+    Ok((Some(enc_details), Some(details_nonce)))
+}
+
+/// Decrypts details written by `seal`
+pub fn open(cipher: &Aes256Gcm, enc_details: Option<&[u8]>, details_nonce: Option<&[u8]>) -> Result<Details, XPassError> {
+    match (enc_details, details_nonce) {
+        (Some(enc_details), Some(details_nonce)) => {
+            let json = keys::decrypt_secret(cipher, enc_details, details_nonce)?;
+            serde_json::from_str(json.expose()).map_err(|_| XPassError::DecryptionFailed)
+        }
+        _ => Ok(Details::new()),
+    }
+}
+
+/// Lists every kind with its fields so the UI can build the forms
+#[tauri::command]
+pub fn get_entry_kinds() -> Vec<KindSchema> {
+    EntryKind::ALL.into_iter()
+        .map(|kind| KindSchema { kind, fields: kind.schema() })
+        .collect()
+}
@@ -8,6 +8,30 @@
 // Core Modules
 // ============================================================================
 mod password_manager;      // Password vault CRUD operations
+mod kinds;                 // Entry kinds and their details
+mod fields;                // Encrypted custom entry fields
+mod urls;                  // Entry website URLs and matching
+mod generator;             // Password generator and its profiles
+mod passphrase;            // Diceware passphrase generator
+mod strength;              // Password strength estimation
+mod health;                // Vault health report
+mod breach;                // Offline breached-password checks
+mod breach_api;            // Online k-anonymity breach check
+mod attachments;           // Encrypted file attachments
+mod folders;               // Nested entry folders
+mod tags;                  // Entry tags
+mod history;               // Entry revision history
+mod search;                // In-memory vault search index
+mod trash;                 // Soft-deleted entries
+mod error;                 // Typed command errors
+mod database;              // Shared vault database connection
+mod session;               // Backend-held unlock sessions
+mod keys;                  // Vault data key wrapping
+mod secrets;               // Zeroizing secret containers
+mod kdf;                   // Argon2 cost parameters
+mod throttle;              // Failed-attempt backoff and lockout
+mod vault;                 // Vault file locations
+mod migrations;            // Versioned schema migrations
 mod backup;                // Encrypted backup/restore
 mod otp;                   // Two-Factor Authentication (TOTP)
 mod pseudo_mode;           // Duress password functionality
@@ -36,6 +60,11 @@ async fn toggle_network(enable: bool) -> bool {
     enable
 }
 
+/// Whether the user has switched networking on
+pub(crate) fn network_enabled() -> bool {
+    NETWORK_ENABLED.lock().map(|enabled| *enabled).unwrap_or(false)
+}
+
 // ============================================================================
 // Application Entry Point
 // ============================================================================
@@ -55,6 +84,17 @@ pub fn run() {
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_shell::init())
         
+        // ========================================
+        // Managed State
+        // ========================================
+        .manage(database::Database::default())
+        .manage(session::SessionStore::default())
+        .setup(|app| {
+            // Resolve the vault location before any command touches the database
+            vault::init(app.handle())?;
+            Ok(())
+        })
+        
         // ========================================
         // Command Handlers
         // ========================================
@@ -78,6 +118,7 @@ pub fn run() {
             password_manager::get_entries,
             password_manager::delete_entry,
             password_manager::delete_user,
+            password_manager::change_master_password,
             password_manager::verify_user_password,
             password_manager::login_with_otp,
             password_manager::login,
@@ -85,6 +126,97 @@ pub fn run() {
             password_manager::setup_otp_recovery,  // ← Добавьте
             password_manager::has_otp_recovery,  // ← Добавьте
             
+            // --- Search ---
+            search::search_entries,
+            
+            // --- Website URLs ---
+            urls::find_entries_for_url,
+            
+            // --- Password Generator ---
+            generator::generate_password,
+            generator::generate_password_with_profile,
+            generator::list_generator_profiles,
+            generator::create_generator_profile,
+            generator::update_generator_profile,
+            generator::delete_generator_profile,
+            passphrase::generate_passphrase,
+            
+            // --- Password Strength ---
+            strength::estimate_password_strength,
+            
+            // --- Vault Health ---
+            health::vault_health_report,
+            breach::import_breach_dataset,
+            breach::get_breach_filter_info,
+            breach::delete_breach_filter,
+            breach::check_breached_passwords,
+            breach_api::get_breach_api_settings,
+            breach_api::set_breach_api_settings,
+            breach_api::check_breached_passwords_online,
+            
+            // --- Entry Kinds ---
+            kinds::get_entry_kinds,
+            
+            // --- Folders & Tags ---
+            folders::list_folders,
+            folders::create_folder,
+            folders::rename_folder,
+            folders::move_folder,
+            folders::delete_folder,
+            folders::set_entry_folder,
+            tags::list_tags,
+            tags::create_tag,
+            tags::rename_tag,
+            tags::delete_tag,
+            tags::set_entry_tags,
+            
+            // --- Attachments ---
+            attachments::list_attachments,
+            attachments::add_attachment,
+            attachments::save_attachment,
+            attachments::delete_attachment,
+            attachments::get_attachment_usage,
+            
+            // --- Entry History ---
+            history::get_entry_history,
+            history::restore_entry_revision,
+            history::get_history_depth,
+            history::set_history_depth,
+            
+            // --- Trash ---
+            trash::list_trash,
+            trash::restore_from_trash,
+            trash::purge_trash_entry,
+            trash::empty_trash,
+            trash::get_trash_retention,
+            trash::set_trash_retention,
+            
+            // --- Sessions ---
+            session::lock_vault,
+            session::logout,
+            
+            // --- Vault Files ---
+            vault::list_vaults,
+            vault::get_active_vault,
+            vault::create_vault,
+            vault::open_vault,
+            vault::switch_vault,
+            
+            // --- Database Encryption ---
+            database::get_database_status,
+            database::unlock_database,
+            database::encrypt_database,
+            database::decrypt_database,
+            
+            // --- Key Derivation ---
+            kdf::get_kdf_policy,
+            kdf::calibrate_kdf,
+            
+            // --- Brute-Force Protection ---
+            throttle::get_lockout_status,
+            throttle::get_throttle_policy,
+            throttle::set_throttle_policy,
+
             // --- Two-Factor Authentication ---
             otp::generate_otp_secret,
             otp::verify_otp,
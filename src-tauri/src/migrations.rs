@@ -0,0 +1,736 @@
+// ============================================================================
+// X-PASS Password Manager
+// Copyright (C) 2026 ar3love
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See LICENSE.
+// ============================================================================
+
+// src-tauri/src/migrations.rs
+//
+// Versioned schema migrations. Every schema change is appended to
+// `MIGRATIONS` with the next version number and is never edited afterwards.
+// Each migration runs in its own transaction and is recorded in the
+// `schema_version` table.
+use std::fmt;
+
+use chrono::Utc;
+use log::info;
+use rusqlite::{params, Connection, Transaction};
+
+pub struct Migration {
+    pub version: u32,
+    pub name: &'static str,
+    up: fn(&Transaction) -> rusqlite::Result<()>,
+}
+
+/// All migrations, in the order they are applied
+const MIGRATIONS: &[Migration] = &[
+    Migration { version: 1, name: "baseline schema", up: baseline_schema },
+    Migration { version: 2, name: "otp recovery key columns", up: otp_recovery_columns },
+    Migration { version: 3, name: "wrapped vault data key", up: wrapped_dek_columns },
+    Migration { version: 4, name: "per-user kdf parameters", up: kdf_parameters },
+    Migration { version: 5, name: "login attempt throttling", up: login_throttling },
+    Migration { version: 6, name: "entry revision history", up: entry_history },
+    Migration { version: 7, name: "entry trash", up: entry_trash },
+    Migration { version: 8, name: "custom entry fields", up: entry_fields },
+    Migration { version: 9, name: "folders and tags", up: folders_and_tags },
+    Migration { version: 10, name: "entry kinds", up: entry_kinds },
+    Migration { version: 11, name: "entry attachments", up: entry_attachments },
+    Migration { version: 12, name: "entry urls", up: entry_urls },
+    Migration { version: 13, name: "password generator profiles", up: generator_profiles },
+    Migration { version: 14, name: "entry password change dates", up: password_changed_at },
+    Migration { version: 15, name: "breach api settings", up: breach_api_settings },
+    Migration { version: 16, name: "revision fields and urls", up: revision_fields_and_urls },
+];
+
+/// Schema version this build writes
+pub const LATEST_VERSION: u32 = MIGRATIONS[MIGRATIONS.len() - 1].version;
+
+#[derive(Debug)]
+pub enum SchemaError {
+    Sqlite(rusqlite::Error),
+    /// The database was written by a newer x-pass with migrations we do not know
+    NewerSchema { found: u32, supported: u32 },
+    /// A migration failed and its transaction was rolled back
+    Failed { version: u32, name: &'static str, source: rusqlite::Error },
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaError::Sqlite(e) => write!(f, "{}", e),
+            SchemaError::NewerSchema { found, supported } => write!(
+                f,
+                "This vault was created by a newer version of X-PASS (schema v{}, this version supports up to v{}). Please update X-PASS to open it.",
+                found, supported
+            ),
+            SchemaError::Failed { version, name, source } => {
+                write!(f, "Database migration v{} ({}) failed: {}", version, name, source)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+impl From<rusqlite::Error> for SchemaError {
+    fn from(e: rusqlite::Error) -> Self {
+        SchemaError::Sqlite(e)
+    }
+}
+
+/// Returns the schema version recorded in the database, 0 for a fresh or pre-migration database
+pub fn current_version(conn: &Connection) -> rusqlite::Result<u32> {
+    conn.query_row(
+        "SELECT COALESCE(MAX(version), 0) FROM schema_version",
+        [],
+        |row| row.get(0)
+    )
+}
+
+/// Brings the database schema up to `LATEST_VERSION`, returns the number of applied migrations
+pub fn run(conn: &mut Connection) -> Result<usize, SchemaError> {
+    apply(conn, MIGRATIONS)
+}
+
+fn apply(conn: &mut Connection, migrations: &[Migration]) -> Result<usize, SchemaError> {
+    let latest = migrations.last().map_or(0, |m| m.version);
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS schema_version (
+            version INTEGER PRIMARY KEY,
+            name TEXT NOT NULL,
+            applied_at TEXT NOT NULL
+        )",
+        [],
+    )?;
+
+    let current = current_version(conn)?;
+    if current > latest {
+        return Err(SchemaError::NewerSchema { found: current, supported: latest });
+    }
+
+    let mut applied = 0;
+    for migration in migrations.iter().filter(|m| m.version > current) {
+        let failed = |source| SchemaError::Failed {
+            version: migration.version,
+            name: migration.name,
+            source,
+        };
+
+        // Dropping the transaction on error rolls the migration back
+        let tx = conn.transaction().map_err(failed)?;
+        (migration.up)(&tx).map_err(failed)?;
+        tx.execute(
+            "INSERT INTO schema_version (version, name, applied_at) VALUES (?1, ?2, ?3)",
+            params![migration.version, migration.name, Utc::now().to_rfc3339()],
+        ).map_err(failed)?;
+        tx.commit().map_err(failed)?;
+
+        info!("Applied database migration v{}: {}", migration.version, migration.name);
+        applied += 1;
+    }
+
+    Ok(applied)
+}
+
+fn has_column(tx: &Transaction, table: &str, column: &str) -> rusqlite::Result<bool> {
+    let mut stmt = tx.prepare(&format!("PRAGMA table_info({})", table))?;
+    let columns = stmt.query_map([], |row| row.get::<_, String>(1))?;
+    for name in columns {
+        if name? == column {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+// ============================================================================
+// Migrations
+// ============================================================================
+
+/// v1: the schema as it was before versioning. Vaults created by earlier
+/// versions already have these tables, hence `IF NOT EXISTS`.
+fn baseline_schema(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE IF NOT EXISTS users (
+            id INTEGER PRIMARY KEY,
+            username TEXT UNIQUE NOT NULL,
+            salt TEXT NOT NULL,
+            hash TEXT NOT NULL,
+            otp_secret TEXT
+        );
+
+        CREATE TABLE IF NOT EXISTS entries (
+            id INTEGER PRIMARY KEY,
+            user_id INTEGER NOT NULL,
+            service TEXT NOT NULL,
+            login TEXT NOT NULL,
+            enc_password BLOB NOT NULL,
+            password_nonce BLOB NOT NULL,
+            enc_note BLOB NOT NULL,
+            note_nonce BLOB NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS pseudo_passwords (
+            id INTEGER PRIMARY KEY,
+            user_id INTEGER NOT NULL,
+            salt TEXT NOT NULL,
+            hash TEXT NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS failed_login_photos (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            user_id INTEGER NOT NULL,
+            encrypted_photo BLOB NOT NULL,
+            photo_nonce BLOB NOT NULL,
+            timestamp TEXT NOT NULL,
+            username_attempt TEXT NOT NULL,
+            FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+        );
+
+        CREATE TABLE IF NOT EXISTS activity_logs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            user_id INTEGER NOT NULL,
+            action_type TEXT NOT NULL,
+            details TEXT NOT NULL,
+            timestamp TEXT NOT NULL,
+            FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_activity_user_time
+            ON activity_logs(user_id, timestamp DESC);
+
+        CREATE INDEX IF NOT EXISTS idx_activity_type
+            ON activity_logs(action_type);
+
+        CREATE TABLE IF NOT EXISTS pseudo_mode_settings (
+            user_id INTEGER PRIMARY KEY,
+            enabled BOOLEAN DEFAULT 0,
+            hide_activity_logs BOOLEAN DEFAULT 0,
+            hide_failed_login_photos BOOLEAN DEFAULT 0,
+            hide_security_settings BOOLEAN DEFAULT 0,
+            show_fake_entries BOOLEAN DEFAULT 0,
+            hide_pseudo_mode_card BOOLEAN DEFAULT 0,
+            FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+        );
+
+        CREATE TABLE IF NOT EXISTS pending_deletions (
+            user_id INTEGER PRIMARY KEY,
+            scheduled_at TEXT NOT NULL,
+            FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+        );
+
+        CREATE TABLE IF NOT EXISTS security_settings (
+            user_id INTEGER PRIMARY KEY,
+            photo_on_failed_login BOOLEAN DEFAULT 0,
+            FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+        );"
+    )
+}
+
+/// v2: encrypted vault key for OTP recovery. Databases touched by the old
+/// ad-hoc `ALTER TABLE` may already have some of these columns.
+fn otp_recovery_columns(tx: &Transaction) -> rusqlite::Result<()> {
+    for (column, decl) in [
+        ("otp_recovery_key", "BLOB"),
+        ("otp_recovery_nonce", "BLOB"),
+        ("otp_recovery_salt", "TEXT"),
+    ] {
+        if !has_column(tx, "users", column)? {
+            tx.execute(&format!("ALTER TABLE users ADD COLUMN {} {}", column, decl), [])?;
+        }
+    }
+    Ok(())
+}
+
+/// v3: the per-user data key wrapped by the master-password KEK. Left NULL
+/// for existing users until their next password unlock.
+fn wrapped_dek_columns(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "ALTER TABLE users ADD COLUMN wrapped_dek BLOB;
+         ALTER TABLE users ADD COLUMN dek_nonce BLOB;
+         ALTER TABLE users ADD COLUMN kek_salt TEXT;"
+    )
+}
+
+/// v4: Argon2 parameters of each user's KEK (NULL means the legacy
+/// defaults) and the vault-wide policy they are upgraded to
+fn kdf_parameters(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "ALTER TABLE users ADD COLUMN kdf_m_cost INTEGER;
+         ALTER TABLE users ADD COLUMN kdf_t_cost INTEGER;
+         ALTER TABLE users ADD COLUMN kdf_p_cost INTEGER;
+
+         CREATE TABLE kdf_policy (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            m_cost INTEGER NOT NULL,
+            t_cost INTEGER NOT NULL,
+            p_cost INTEGER NOT NULL,
+            updated_at TEXT NOT NULL
+         );"
+    )
+}
+
+/// v5: persistent failed-attempt counters and the vault throttling policy
+fn login_throttling(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE login_attempts (
+            user_id INTEGER PRIMARY KEY,
+            failed_count INTEGER NOT NULL DEFAULT 0,
+            last_failed_at TEXT,
+            next_attempt_at TEXT,
+            locked_until TEXT,
+            FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+         );
+
+         CREATE TABLE throttle_policy (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            free_attempts INTEGER NOT NULL,
+            base_delay_secs INTEGER NOT NULL,
+            max_delay_secs INTEGER NOT NULL,
+            lockout_threshold INTEGER NOT NULL,
+            lockout_secs INTEGER NOT NULL,
+            updated_at TEXT NOT NULL
+         );"
+    )
+}
+
+/// v6: previous versions of entries, still encrypted, and how many of them
+/// each user keeps
+fn entry_history(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE entry_history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            entry_id INTEGER NOT NULL,
+            user_id INTEGER NOT NULL,
+            service TEXT NOT NULL,
+            login TEXT NOT NULL,
+            enc_password BLOB NOT NULL,
+            password_nonce BLOB NOT NULL,
+            enc_note BLOB NOT NULL,
+            note_nonce BLOB NOT NULL,
+            created_at TEXT NOT NULL,
+            FOREIGN KEY (entry_id) REFERENCES entries(id) ON DELETE CASCADE,
+            FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+         );
+
+         CREATE INDEX idx_entry_history_entry
+            ON entry_history(entry_id, id DESC);
+
+         CREATE TABLE history_settings (
+            user_id INTEGER PRIMARY KEY,
+            max_depth INTEGER NOT NULL,
+            FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+         );"
+    )
+}
+
+/// v7: soft deletion. Entries with `deleted_at` set are in the trash.
+fn entry_trash(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "ALTER TABLE entries ADD COLUMN deleted_at TEXT;
+
+         CREATE INDEX idx_entries_user_deleted
+            ON entries(user_id, deleted_at);
+
+         CREATE TABLE trash_settings (
+            user_id INTEGER PRIMARY KEY,
+            retention_days INTEGER NOT NULL,
+            FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+         );"
+    )
+}
+
+/// v8: named custom fields per entry, name and value encrypted separately
+fn entry_fields(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE entry_fields (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            entry_id INTEGER NOT NULL,
+            position INTEGER NOT NULL,
+            field_type TEXT NOT NULL,
+            enc_name BLOB NOT NULL,
+            name_nonce BLOB NOT NULL,
+            enc_value BLOB NOT NULL,
+            value_nonce BLOB NOT NULL,
+            FOREIGN KEY (entry_id) REFERENCES entries(id) ON DELETE CASCADE
+         );
+
+         CREATE INDEX idx_entry_fields_entry
+            ON entry_fields(entry_id, position);"
+    )
+}
+
+/// v9: nested folders and tags with encrypted names, and the key check value
+/// that tells real sessions from pseudo ones before anything is encrypted
+fn folders_and_tags(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "ALTER TABLE users ADD COLUMN key_check BLOB;
+         ALTER TABLE users ADD COLUMN key_check_nonce BLOB;
+
+         CREATE TABLE folders (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            user_id INTEGER NOT NULL,
+            parent_id INTEGER,
+            enc_name BLOB NOT NULL,
+            name_nonce BLOB NOT NULL,
+            created_at TEXT NOT NULL,
+            FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+            FOREIGN KEY (parent_id) REFERENCES folders(id) ON DELETE CASCADE
+         );
+
+         CREATE INDEX idx_folders_user ON folders(user_id, parent_id);
+
+         ALTER TABLE entries ADD COLUMN folder_id INTEGER REFERENCES folders(id) ON DELETE SET NULL;
+
+         CREATE INDEX idx_entries_folder ON entries(folder_id);
+
+         CREATE TABLE tags (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            user_id INTEGER NOT NULL,
+            enc_name BLOB NOT NULL,
+            name_nonce BLOB NOT NULL,
+            created_at TEXT NOT NULL,
+            FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+         );
+
+         CREATE TABLE entry_tags (
+            entry_id INTEGER NOT NULL,
+            tag_id INTEGER NOT NULL,
+            PRIMARY KEY (entry_id, tag_id),
+            FOREIGN KEY (entry_id) REFERENCES entries(id) ON DELETE CASCADE,
+            FOREIGN KEY (tag_id) REFERENCES tags(id) ON DELETE CASCADE
+         );
+
+         CREATE INDEX idx_entry_tags_tag ON entry_tags(tag_id);"
+    )
+}
+
+/// v10: entry kinds with their encrypted details, kept in history as well
+fn entry_kinds(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "ALTER TABLE entries ADD COLUMN kind TEXT NOT NULL DEFAULT 'login';
+         ALTER TABLE entries ADD COLUMN enc_details BLOB;
+         ALTER TABLE entries ADD COLUMN details_nonce BLOB;
+
+         ALTER TABLE entry_history ADD COLUMN kind TEXT NOT NULL DEFAULT 'login';
+         ALTER TABLE entry_history ADD COLUMN enc_details BLOB;
+         ALTER TABLE entry_history ADD COLUMN details_nonce BLOB;"
+    )
+}
+
+/// v11: encrypted file attachments, stored in chunks
+fn entry_attachments(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE attachments (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            entry_id INTEGER NOT NULL,
+            user_id INTEGER NOT NULL,
+            enc_name BLOB NOT NULL,
+            name_nonce BLOB NOT NULL,
+            size INTEGER NOT NULL,
+            chunk_count INTEGER NOT NULL,
+            created_at TEXT NOT NULL,
+            FOREIGN KEY (entry_id) REFERENCES entries(id) ON DELETE CASCADE,
+            FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+         );
+
+         CREATE INDEX idx_attachments_entry ON attachments(entry_id);
+         CREATE INDEX idx_attachments_user ON attachments(user_id);
+
+         CREATE TABLE attachment_chunks (
+            attachment_id INTEGER NOT NULL,
+            position INTEGER NOT NULL,
+            enc_data BLOB NOT NULL,
+            nonce BLOB NOT NULL,
+            PRIMARY KEY (attachment_id, position),
+            FOREIGN KEY (attachment_id) REFERENCES attachments(id) ON DELETE CASCADE
+         );"
+    )
+}
+
+/// v12: website URLs on entries, each with its own match mode
+fn entry_urls(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE entry_urls (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            entry_id INTEGER NOT NULL,
+            position INTEGER NOT NULL,
+            match_mode TEXT NOT NULL,
+            enc_url BLOB NOT NULL,
+            url_nonce BLOB NOT NULL,
+            FOREIGN KEY (entry_id) REFERENCES entries(id) ON DELETE CASCADE
+         );
+
+         CREATE INDEX idx_entry_urls_entry ON entry_urls(entry_id, position);"
+    )
+}
+
+/// v13: saved password generator policies, names encrypted
+fn generator_profiles(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE generator_profiles (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            user_id INTEGER NOT NULL,
+            enc_name BLOB NOT NULL,
+            name_nonce BLOB NOT NULL,
+            policy TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+         );
+
+         CREATE INDEX idx_generator_profiles_user ON generator_profiles(user_id);"
+    )
+}
+
+/// v14: when each entry's password last changed. Existing entries take the
+/// date of their newest revision, or the upgrade date without history.
+fn password_changed_at(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "ALTER TABLE entries ADD COLUMN password_changed_at TEXT;
+
+         UPDATE entries SET password_changed_at = COALESCE(
+            (SELECT MAX(created_at) FROM entry_history WHERE entry_history.entry_id = entries.id),
+            strftime('%Y-%m-%dT%H:%M:%S+00:00', 'now')
+         );"
+    )
+}
+
+/// v15: endpoint and cache lifetime of the online breach check
+fn breach_api_settings(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE breach_api_settings (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            endpoint TEXT NOT NULL,
+            cache_ttl_secs INTEGER NOT NULL,
+            updated_at TEXT NOT NULL
+         );"
+    )
+}
+
+/// v16: custom fields and URLs of revisions, copied still encrypted.
+/// `fields_recorded` tells revisions without any from older ones that
+/// never recorded them.
+fn revision_fields_and_urls(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "ALTER TABLE entry_history ADD COLUMN fields_recorded INTEGER NOT NULL DEFAULT 0;
+
+         CREATE TABLE entry_history_fields (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            revision_id INTEGER NOT NULL,
+            position INTEGER NOT NULL,
+            field_type TEXT NOT NULL,
+            enc_name BLOB NOT NULL,
+            name_nonce BLOB NOT NULL,
+            enc_value BLOB NOT NULL,
+            value_nonce BLOB NOT NULL,
+            FOREIGN KEY (revision_id) REFERENCES entry_history(id) ON DELETE CASCADE
+         );
+
+         CREATE INDEX idx_entry_history_fields_revision
+            ON entry_history_fields(revision_id, position);
+
+         CREATE TABLE entry_history_urls (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            revision_id INTEGER NOT NULL,
+            position INTEGER NOT NULL,
+            match_mode TEXT NOT NULL,
+            enc_url BLOB NOT NULL,
+            url_nonce BLOB NOT NULL,
+            FOREIGN KEY (revision_id) REFERENCES entry_history(id) ON DELETE CASCADE
+         );
+
+         CREATE INDEX idx_entry_history_urls_revision
+            ON entry_history_urls(revision_id, position);"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table_exists(conn: &Connection, table: &str) -> bool {
+        conn.query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = ?1",
+            params![table],
+            |row| row.get::<_, i64>(0)
+        ).unwrap() == 1
+    }
+
+    fn column_exists(conn: &Connection, table: &str, column: &str) -> bool {
+        let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table)).unwrap();
+        let columns: Vec<String> = stmt.query_map([], |row| row.get(1)).unwrap().map(Result::unwrap).collect();
+        columns.iter().any(|name| name == column)
+    }
+
+    #[test]
+    fn fresh_database_reaches_latest_version() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        assert_eq!(run(&mut conn).unwrap(), MIGRATIONS.len());
+        assert_eq!(current_version(&conn).unwrap(), LATEST_VERSION);
+        assert!(table_exists(&conn, "entry_history_fields"));
+
+        // Running again is a no-op
+        assert_eq!(run(&mut conn).unwrap(), 0);
+        assert_eq!(current_version(&conn).unwrap(), LATEST_VERSION);
+    }
+
+    #[test]
+    fn versions_are_increasing() {
+        for pair in MIGRATIONS.windows(2) {
+            assert!(pair[0].version < pair[1].version, "v{} follows v{}", pair[1].version, pair[0].version);
+        }
+    }
+
+    /// Vaults from before versioning: tables created ad hoc on every start,
+    /// `users` and `failed_login_photos` twice, and the OTP recovery columns
+    /// added by an `ALTER TABLE` whose errors were ignored
+    #[test]
+    fn pre_versioning_database_upgrades() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS users (
+                id INTEGER PRIMARY KEY,
+                username TEXT UNIQUE NOT NULL,
+                salt TEXT NOT NULL,
+                hash TEXT NOT NULL,
+                otp_secret TEXT
+            );
+            CREATE TABLE IF NOT EXISTS entries (
+                id INTEGER PRIMARY KEY,
+                user_id INTEGER NOT NULL,
+                service TEXT NOT NULL,
+                login TEXT NOT NULL,
+                enc_password BLOB NOT NULL,
+                password_nonce BLOB NOT NULL,
+                enc_note BLOB NOT NULL,
+                note_nonce BLOB NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS pseudo_passwords (
+                id INTEGER PRIMARY KEY,
+                user_id INTEGER NOT NULL,
+                salt TEXT NOT NULL,
+                hash TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS failed_login_photos (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                user_id INTEGER NOT NULL,
+                encrypted_photo BLOB NOT NULL,
+                photo_nonce BLOB NOT NULL,
+                timestamp TEXT NOT NULL,
+                username_attempt TEXT NOT NULL,
+                FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+            );
+            CREATE TABLE IF NOT EXISTS users (
+                id INTEGER PRIMARY KEY,
+                username TEXT UNIQUE NOT NULL,
+                salt TEXT NOT NULL,
+                hash TEXT NOT NULL,
+                otp_secret TEXT,
+                otp_recovery_key BLOB,
+                otp_recovery_nonce BLOB,
+                otp_recovery_salt TEXT
+            );
+            ALTER TABLE users ADD COLUMN otp_recovery_key BLOB;
+            ALTER TABLE users ADD COLUMN otp_recovery_nonce BLOB;
+            CREATE TABLE IF NOT EXISTS failed_login_photos (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                user_id INTEGER NOT NULL,
+                encrypted_photo BLOB NOT NULL,
+                photo_nonce BLOB NOT NULL,
+                timestamp TEXT NOT NULL,
+                username_attempt TEXT NOT NULL,
+                FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+            );
+            CREATE TABLE IF NOT EXISTS activity_logs (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                user_id INTEGER NOT NULL,
+                action_type TEXT NOT NULL,
+                details TEXT NOT NULL,
+                timestamp TEXT NOT NULL,
+                FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+            );
+
+            INSERT INTO users (id, username, salt, hash) VALUES (1, 'alice', 'salt', 'hash');
+            INSERT INTO entries (user_id, service, login, enc_password, password_nonce, enc_note, note_nonce)
+                VALUES (1, 'mail', 'alice', x'00', x'00', x'00', x'00');"
+        ).unwrap();
+
+        assert_eq!(run(&mut conn).unwrap(), MIGRATIONS.len());
+        assert_eq!(current_version(&conn).unwrap(), LATEST_VERSION);
+        for column in ["otp_recovery_key", "otp_recovery_nonce", "otp_recovery_salt", "wrapped_dek", "key_check"] {
+            assert!(column_exists(&conn, "users", column), "users.{} missing", column);
+        }
+        assert!(table_exists(&conn, "pseudo_mode_settings"));
+
+        let (username, entries): (String, i64) = conn.query_row(
+            "SELECT username, (SELECT COUNT(*) FROM entries WHERE user_id = users.id) FROM users",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?))
+        ).unwrap();
+        assert_eq!((username.as_str(), entries), ("alice", 1));
+        let (kind, changed_at): (String, Option<String>) = conn.query_row(
+            "SELECT kind, password_changed_at FROM entries",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?))
+        ).unwrap();
+        assert_eq!(kind, "login");
+        assert!(changed_at.is_some());
+    }
+
+    #[test]
+    fn newer_schema_is_refused() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        run(&mut conn).unwrap();
+        conn.execute(
+            "INSERT INTO schema_version (version, name, applied_at) VALUES (?1, 'from the future', '')",
+            params![LATEST_VERSION + 1],
+        ).unwrap();
+
+        match run(&mut conn) {
+            Err(SchemaError::NewerSchema { found, supported }) => {
+                assert_eq!(found, LATEST_VERSION + 1);
+                assert_eq!(supported, LATEST_VERSION);
+            }
+            other => panic!("expected NewerSchema, got {:?}", other),
+        }
+    }
+
+    fn create_first(tx: &Transaction) -> rusqlite::Result<()> {
+        tx.execute_batch("CREATE TABLE first (id INTEGER PRIMARY KEY);")
+    }
+
+    fn create_second_then_fail(tx: &Transaction) -> rusqlite::Result<()> {
+        tx.execute_batch(
+            "CREATE TABLE second (id INTEGER PRIMARY KEY);
+             INSERT INTO first (id) VALUES (1);
+             ALTER TABLE missing ADD COLUMN nothing TEXT;"
+        )
+    }
+
+    #[test]
+    fn failed_migration_is_rolled_back() {
+        let migrations = [
+            Migration { version: 1, name: "first", up: create_first },
+            Migration { version: 2, name: "second", up: create_second_then_fail },
+        ];
+        let mut conn = Connection::open_in_memory().unwrap();
+
+        match apply(&mut conn, &migrations) {
+            Err(SchemaError::Failed { version, name, .. }) => assert_eq!((version, name), (2, "second")),
+            other => panic!("expected Failed, got {:?}", other),
+        }
+        assert_eq!(current_version(&conn).unwrap(), 1);
+        assert!(table_exists(&conn, "first"));
+        assert!(!table_exists(&conn, "second"));
+        let rows: i64 = conn.query_row("SELECT COUNT(*) FROM first", [], |row| row.get(0)).unwrap();
+        assert_eq!(rows, 0);
+    }
+}
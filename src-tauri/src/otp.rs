@@ -16,64 +16,73 @@ use totp_rs::{Algorithm, TOTP};
 use rusqlite::{params, OptionalExtension};
 use base32::Alphabet;
 use rand::Rng;
+use zeroize::Zeroize;
 use std::time::SystemTime;
 use log::{error, info};
+use tauri::State;
+use crate::database::Database;
+use crate::error::XPassError;
+use crate::secrets::{SecretBytes, SecretString};
+use crate::throttle;
 
 // OTP secret and QR-code generation
 #[tauri::command]
-pub fn generate_otp_secret(username: String) -> Result<(String, String), String> {
+pub fn generate_otp_secret(db: State<'_, Database>, username: String) -> Result<(SecretString, SecretString), XPassError> {
     info!("Generating OTP secret for username: {}", username);
-    let conn = match super::password_manager::get_conn() {
+    let conn = match db.conn() {
         Ok(conn) => conn,
         Err(e) => {
             error!("Failed to connect to database: {}", e);
-            return Err(format!("Failed to connect to database: {}", e));
+            return Err(e);
         }
     };
     
-    let existing_secret: Option<String> = match conn.query_row(
+    let existing_secret: Option<SecretString> = match conn.query_row(
         "SELECT otp_secret FROM users WHERE username = ?1",
         params![username],
-        |row| row.get::<_, Option<String>>(0),
+        |row| row.get::<_, Option<SecretString>>(0),
     ).optional() {
         Ok(secret) => secret,
         Err(e) => {
             error!("Failed to query otp_secret for username {}: {}", username, e);
-            return Err(format!("Failed to query otp_secret: {}", e));
+            return Err(e.into());
         }
     }.flatten();
     
-    let secret_bytes: Vec<u8>;
-    let secret_base32: String;
+    let secret_bytes: SecretBytes;
+    let secret_base32: SecretString;
     
     if let Some(secret) = existing_secret {
         info!("Reusing existing OTP secret for username: {}", username);
         secret_base32 = secret;
-        secret_bytes = match base32::decode(Alphabet::RFC4648 { padding: false }, &secret_base32) {
-            Some(bytes) => bytes,
+        secret_bytes = match base32::decode(Alphabet::RFC4648 { padding: false }, secret_base32.expose()) {
+            Some(bytes) => bytes.into(),
             None => {
                 error!("Invalid Base32 secret for username: {}", username);
-                return Err("Invalid Base32 secret".to_string());
+                return Err(XPassError::CorruptedKeyMaterial { what: "OTP secret" });
             }
         };
     } else {
-        secret_bytes = rand::thread_rng().gen::<[u8; 20]>().to_vec();
-        secret_base32 = base32::encode(Alphabet::RFC4648 { padding: false }, &secret_bytes);
-        info!("Generated new secret (Base32) for username {}: {}", username, secret_base32);
+        let mut raw = [0u8; 20];
+        rand::thread_rng().fill(&mut raw);
+        secret_bytes = raw.to_vec().into();
+        raw.zeroize();
+        secret_base32 = base32::encode(Alphabet::RFC4648 { padding: false }, secret_bytes.expose()).into();
+        info!("Generated new OTP secret for username: {}", username);
         match conn.execute(
             "UPDATE users SET otp_secret = ?1 WHERE username = ?2",
-            params![secret_base32, username],
+            params![secret_base32.expose(), username],
         ) {
             Ok(rows_affected) => {
                 if rows_affected == 0 {
                     error!("No user found with username: {}", username);
-                    return Err("User not found".to_string());
+                    return Err(XPassError::UserNotFound);
                 }
                 info!("Updated otp_secret for username: {}", username);
             }
             Err(e) => {
                 error!("Failed to update otp_secret in database: {}", e);
-                return Err(format!("Failed to update database: {}", e));
+                return Err(e.into());
             }
         };
     }
@@ -83,32 +92,33 @@ pub fn generate_otp_secret(username: String) -> Result<(String, String), String>
         6,
         1,
         30,
-        secret_bytes,
+        secret_bytes.expose().to_vec(),
     ) {
         Ok(totp) => totp,
         Err(e) => {
             error!("Failed to create TOTP: {}", e);
-            return Err(format!("Failed to create TOTP: {}", e));
+            return Err(XPassError::internal(e));
         }
     };
     
-    let qr_code = format!(
+    // The URL embeds the secret, so it is handled as one too
+    let qr_code = SecretString::new(format!(
         "otpauth://totp/PasswordManager:{}?secret={}&issuer=PasswordManager&algorithm=SHA1&digits=6&period=30",
-        username, secret_base32
-    );
+        username, secret_base32.expose()
+    ));
     
     Ok((secret_base32, qr_code))
 }
 
 // Reset OTP secret (also clears recovery key)
 #[tauri::command]
-pub fn reset_otp_secret(username: String) -> Result<(String, String), String> {
+pub fn reset_otp_secret(db: State<'_, Database>, username: String) -> Result<(SecretString, SecretString), XPassError> {
     info!("Resetting OTP secret for username: {}", username);
-    let conn = match super::password_manager::get_conn() {
+    let conn = match db.conn() {
         Ok(conn) => conn,
         Err(e) => {
             error!("Failed to connect to database: {}", e);
-            return Err(format!("Failed to connect to database: {}", e));
+            return Err(e);
         }
     };
     
@@ -120,49 +130,55 @@ pub fn reset_otp_secret(username: String) -> Result<(String, String), String> {
         Ok(rows_affected) => {
             if rows_affected == 0 {
                 error!("No user found with username: {}", username);
-                return Err("User not found".to_string());
+                return Err(XPassError::UserNotFound);
             }
             info!("Cleared otp_secret and recovery key for username: {}", username);
         }
         Err(e) => {
             error!("Failed to clear otp_secret in database: {}", e);
-            return Err(format!("Failed to clear otp_secret: {}", e));
+            return Err(e.into());
         }
     };
     
+    // Release the connection before generating the new secret
+    drop(conn);
+    
     // Generate new OTP secret
-    generate_otp_secret(username)
+    generate_otp_secret(db, username)
 }
 
 // OTP code verification
 #[tauri::command]
-pub fn verify_otp(username: String, otp_code: String) -> Result<bool, String> {
+pub fn verify_otp(db: State<'_, Database>, username: String, otp_code: SecretString) -> Result<bool, XPassError> {
     info!("Verifying OTP for username: {}", username);
-    let conn = match super::password_manager::get_conn() {
+    let conn = match db.conn() {
         Ok(conn) => conn,
         Err(e) => {
             error!("Failed to connect to database: {}", e);
-            return Err(format!("Failed to connect to database: {}", e));
+            return Err(e);
         }
     };
     
-    let otp_secret: String = match conn.query_row(
-        "SELECT otp_secret FROM users WHERE username = ?1",
+    let (user_id, otp_secret): (i64, SecretString) = match conn.query_row(
+        "SELECT id, otp_secret FROM users WHERE username = ?1",
         params![username],
-        |row| row.get(0),
+        |row| Ok((row.get(0)?, row.get(1)?)),
     ) {
-        Ok(secret) => secret,
+        Ok(row) => row,
         Err(e) => {
             error!("User or OTP secret not found for username: {}: {}", username, e);
-            return Err("User or OTP secret not found".to_string());
+            return Err(XPassError::OtpNotConfigured);
         }
     };
     
-    let secret_bytes = match base32::decode(Alphabet::RFC4648 { padding: false }, &otp_secret) {
+    // Codes are only six digits, so guessing them is throttled like passwords
+    throttle::ensure_allowed(&conn, user_id)?;
+    
+    let secret_bytes = match base32::decode(Alphabet::RFC4648 { padding: false }, otp_secret.expose()) {
         Some(bytes) => bytes,
         None => {
             error!("Invalid Base32 secret for username: {}", username);
-            return Err("Invalid Base32 secret".to_string());
+            return Err(XPassError::CorruptedKeyMaterial { what: "OTP secret" });
         }
     };
     
@@ -176,7 +192,7 @@ pub fn verify_otp(username: String, otp_code: String) -> Result<bool, String> {
         Ok(totp) => totp,
         Err(e) => {
             error!("Failed to create TOTP: {}", e);
-            return Err(format!("Failed to create TOTP: {}", e));
+            return Err(XPassError::internal(e));
         }
     };
     
@@ -184,36 +200,42 @@ pub fn verify_otp(username: String, otp_code: String) -> Result<bool, String> {
         Ok(time) => time.as_secs(),
         Err(e) => {
             error!("Failed to get current time: {}", e);
-            return Err(format!("Failed to get current time: {}", e));
+            return Err(XPassError::internal(e));
         }
     };
     
-    let is_valid = totp.check(&otp_code, current_time);
+    let is_valid = totp.check(otp_code.expose(), current_time);
     info!("OTP verification result for username {}: {}", username, is_valid);
+    
+    if is_valid {
+        throttle::record_success(&conn, user_id)?;
+    } else {
+        throttle::record_failure(&conn, user_id, "OTP")?;
+    }
     Ok(is_valid)
 }
 
 // Check if OTP secret exists
 #[tauri::command]
-pub fn has_otp_secret(username: String) -> Result<bool, String> {
+pub fn has_otp_secret(db: State<'_, Database>, username: String) -> Result<bool, XPassError> {
     info!("Checking OTP secret existence for username: {}", username);
-    let conn = match super::password_manager::get_conn() {
+    let conn = match db.conn() {
         Ok(conn) => conn,
         Err(e) => {
             error!("Failed to connect to database: {}", e);
-            return Err(format!("Failed to connect to database: {}", e));
+            return Err(e);
         }
     };
     
-    let otp_secret: Option<String> = match conn.query_row(
+    let otp_secret: Option<SecretString> = match conn.query_row(
         "SELECT otp_secret FROM users WHERE username = ?1",
         params![username],
-        |row| row.get::<_, Option<String>>(0),
+        |row| row.get::<_, Option<SecretString>>(0),
     ).optional() {
         Ok(secret) => secret,
         Err(e) => {
             error!("Failed to query otp_secret: {}", e);
-            return Err(format!("Failed to query otp_secret: {}", e));
+            return Err(e.into());
         }
     }.flatten();
     
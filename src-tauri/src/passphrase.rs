@@ -0,0 +1,250 @@
+// ============================================================================
+// X-PASS Password Manager
+// Copyright (C) 2026 ar3love
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See LICENSE.
+// ============================================================================
+
+// src-tauri/src/passphrase.rs
+//
+// Diceware-style passphrases for master and pseudo passwords. Words are
+// drawn uniformly, with repetition, from a wordlist bundled under
+// assets/wordlists and picked by UI language. The bundled lists are the
+// BIP-39 lists (2048 words, 11 bits per word). German, Russian, Hindi,
+// Indonesian and Arabic have no list yet and use English, listed in
+// `FALLBACKS`; the result says which list was used. A list file holds one
+// word per line; lines starting with `#` are comments and dice numbers in
+// front of a word are skipped, so the EFF large list (7776 words, 12.9 bits
+// per word) or a localized diceware list can be dropped in as is.
+//
+// The reported entropy assumes the attacker knows the settings and the
+// list: words × log2(list size), plus one bit per word for random
+// capitalization and, for inserted digits, the digit and the word it went to.
+use rand::seq::index;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::error::XPassError;
+use crate::secrets::SecretString;
+
+pub const MIN_WORDS: usize = 3;
+pub const MAX_WORDS: usize = 20;
+const MAX_SEPARATOR_LEN: usize = 8;
+const DEFAULT_LANGUAGE: &str = "en";
+
+/// Bundled lists by UI language code, English first
+pub const WORDLISTS: [(&str, &str); 8] = [
+    ("en", include_str!("../assets/wordlists/en.txt")),
+    ("es", include_str!("../assets/wordlists/es.txt")),
+    ("fr", include_str!("../assets/wordlists/fr.txt")),
+    ("it", include_str!("../assets/wordlists/it.txt")),
+    ("ja", include_str!("../assets/wordlists/ja.txt")),
+    ("ko", include_str!("../assets/wordlists/ko.txt")),
+    ("pt", include_str!("../assets/wordlists/pt.txt")),
+    ("zh", include_str!("../assets/wordlists/zh.txt")),
+];
+
+/// UI languages without a list and the list they use instead. English words
+/// are plain ASCII and can be typed on any keyboard layout.
+const FALLBACKS: [(&str, &str); 5] = [
+    ("ar", "en"),
+    ("de", "en"),
+    ("hi", "en"),
+    ("id", "en"),
+    ("ru", "en"),
+];
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Capitalization {
+    #[default]
+    Lowercase,
+    /// First letter of every word
+    Words,
+    Uppercase,
+    /// Each word either lowercase or with a capital first letter
+    Random,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct PassphrasePolicy {
+    pub words: usize,
+    pub separator: String,
+    pub capitalization: Capitalization,
+    /// Digits appended to as many different words
+    pub digits: usize,
+    /// UI language code, see `WORDLISTS` and `FALLBACKS`
+    pub language: String,
+}
+
+impl Default for PassphrasePolicy {
+    fn default() -> Self {
+        PassphrasePolicy {
+            words: 6,
+            separator: "-".to_string(),
+            capitalization: Capitalization::Lowercase,
+            digits: 0,
+            language: DEFAULT_LANGUAGE.to_string(),
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct Passphrase {
+    pub passphrase: SecretString,
+    pub entropy_bits: f64,
+    /// Language of the list that was used
+    pub language: &'static str,
+    pub list_size: usize,
+}
+
+/// The words of a bundled list
+pub fn parse_wordlist(list: &str) -> Vec<&str> {
+    list.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.split_whitespace().last())
+        .collect()
+}
+
+/// The list for a UI language, or the one its entry in `FALLBACKS` names
+fn wordlist(language: &str) -> Result<(&'static str, Vec<&'static str>), XPassError> {
+    let language = FALLBACKS.iter()
+        .find(|(code, _)| *code == language)
+        .map_or(language, |(_, fallback)| fallback);
+    let (code, list) = WORDLISTS.iter()
+        .find(|(code, _)| *code == language)
+        .ok_or_else(|| XPassError::invalid_input("language", "not a UI language"))?;
+    Ok((code, parse_wordlist(list)))
+}
+
+fn capitalize_first(word: &str, out: &mut String) {
+    let mut chars = word.chars();
+    if let Some(first) = chars.next() {
+        out.extend(first.to_uppercase());
+        out.push_str(chars.as_str());
+    }
+}
+
+/// log2 of n choose k
+fn log2_binomial(n: usize, k: usize) -> f64 {
+    (0..k).map(|i| ((n - i) as f64 / (i + 1) as f64).log2()).sum()
+}
+
+/// Generates a passphrase following the policy
+pub fn generate(policy: &PassphrasePolicy) -> Result<Passphrase, XPassError> {
+    if !(MIN_WORDS..=MAX_WORDS).contains(&policy.words) {
+        return Err(XPassError::invalid_input("words", format!("must be {} to {}", MIN_WORDS, MAX_WORDS)));
+    }
+    if policy.digits > policy.words {
+        return Err(XPassError::invalid_input("digits", "at most one digit per word"));
+    }
+    if policy.separator.chars().count() > MAX_SEPARATOR_LEN || policy.separator.chars().any(char::is_control) {
+        return Err(XPassError::invalid_input("separator", format!("at most {} printable characters", MAX_SEPARATOR_LEN)));
+    }
+
+    let (language, words) = wordlist(&policy.language)?;
+    if words.len() < 2 {
+        return Err(XPassError::internal(format!("wordlist '{}' is empty", language)));
+    }
+    let mut rng = rand::thread_rng();
+    let chosen: Vec<&str> = (0..policy.words).map(|_| words[rng.gen_range(0..words.len())]).collect();
+    let with_digit = index::sample(&mut rng, policy.words, policy.digits);
+
+    // Sized up front so the passphrase is never copied by a reallocation
+    let capacity = chosen.iter().map(|w| w.len() * 2 + policy.separator.len() + 1).sum();
+    let mut passphrase = String::with_capacity(capacity);
+    for (position, word) in chosen.iter().enumerate() {
+        if position > 0 {
+            passphrase.push_str(&policy.separator);
+        }
+        match policy.capitalization {
+            Capitalization::Lowercase => passphrase.push_str(word),
+            Capitalization::Words => capitalize_first(word, &mut passphrase),
+            Capitalization::Uppercase => passphrase.extend(word.chars().flat_map(char::to_uppercase)),
+            Capitalization::Random => {
+                if rng.gen() {
+                    capitalize_first(word, &mut passphrase);
+                } else {
+                    passphrase.push_str(word);
+                }
+            }
+        }
+        if with_digit.iter().any(|i| i == position) {
+            passphrase.push(char::from(b'0' + rng.gen_range(0..10)));
+        }
+    }
+
+    let mut entropy_bits = policy.words as f64 * (words.len() as f64).log2();
+    // Lists without letter case (Japanese, Korean, Chinese) gain nothing
+    let cased = words.iter().any(|w| w.to_uppercase() != *w);
+    if policy.capitalization == Capitalization::Random && cased {
+        entropy_bits += policy.words as f64;
+    }
+    entropy_bits += policy.digits as f64 * 10f64.log2() + log2_binomial(policy.words, policy.digits);
+
+    Ok(Passphrase {
+        passphrase: SecretString::new(passphrase),
+        entropy_bits,
+        language,
+        list_size: words.len(),
+    })
+}
+
+/// Generates a passphrase. Without a policy: six lowercase English words
+/// joined by dashes, about 66 bits.
+#[tauri::command]
+pub fn generate_passphrase(policy: Option<PassphrasePolicy>) -> Result<Passphrase, XPassError> {
+    generate(&policy.unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The languages of src/i18n.ts
+    const UI_LANGUAGES: [&str; 13] = ["ar", "de", "en", "es", "fr", "hi", "id", "it", "ja", "ko", "pt", "ru", "zh"];
+
+    #[test]
+    fn every_ui_language_has_a_list() {
+        for language in UI_LANGUAGES {
+            let (code, words) = wordlist(language).unwrap();
+            let own_list = WORDLISTS.iter().any(|(list, _)| *list == language);
+            assert_eq!(code == language, own_list, "{}", language);
+            assert!(words.len() >= 2048, "{}", code);
+        }
+        assert!(wordlist("xx").is_err());
+    }
+
+    #[test]
+    fn lists_have_no_duplicates() {
+        for (code, list) in WORDLISTS {
+            let mut words = parse_wordlist(list);
+            let size = words.len();
+            words.sort_unstable();
+            words.dedup();
+            assert_eq!(words.len(), size, "{}", code);
+        }
+    }
+
+    #[test]
+    fn dice_numbers_and_comments_are_skipped() {
+        assert_eq!(parse_wordlist("# EFF\n11111\tabacus\n11112\tabdomen\n\nzebra\n"), ["abacus", "abdomen", "zebra"]);
+    }
+
+    #[test]
+    fn fallback_reports_the_list_used() {
+        let policy = PassphrasePolicy { language: "de".to_string(), ..Default::default() };
+        let passphrase = generate(&policy).unwrap();
+        assert_eq!(passphrase.language, "en");
+        assert_eq!(passphrase.passphrase.expose().split('-').count(), 6);
+        assert_eq!(passphrase.entropy_bits, 66.0);
+    }
+}
@@ -11,166 +11,144 @@
 // MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See LICENSE.
 // ============================================================================
 
-use rusqlite::{Connection, Result as RusqliteResult};
+use rusqlite::Connection;
 use rusqlite::params;
+use rusqlite::OptionalExtension;
+use chrono::Utc;
 use argon2::{
     password_hash::{
         rand_core::OsRng,
-        PasswordHash, PasswordHasher, PasswordVerifier, SaltString
+        PasswordHash, PasswordVerifier, SaltString
     },
     Argon2
 };
 use totp_rs::TOTP;
 use aes_gcm::{
-    aead::{Aead, AeadCore, KeyInit},
+    aead::{Aead, AeadCore},
     Aes256Gcm,
     Nonce,
 };
-use base64::{engine::general_purpose, Engine as _};
 use serde::{Serialize, Deserialize};
 use log::info;
-use crate::pseudo_mode;
+use tauri::State;
+use crate::database::Database;
+use crate::error::XPassError;
 use crate::fake_data_generator::generate_fake_entries_for_user;
-
-#[derive(Serialize, Deserialize)]
+use crate::fields::{self, CustomField};
+use crate::history;
+use crate::kdf::KdfParams;
+use crate::keys;
+use crate::kinds::{self, Details, EntryKind};
+use crate::search;
+use crate::secrets::SecretString;
+use crate::session::SessionStore;
+use crate::strength::{self, StrengthSummary};
+use crate::tags;
+use crate::throttle;
+use crate::trash;
+use crate::urls::{self, EntryUrl};
+
+#[derive(Serialize, Deserialize, Clone)]
 pub struct Entry {
     pub id: i64,
     pub service: String,
     pub login: String,
-    pub password: String,
-    pub note: String,
+    pub password: SecretString,
+    pub note: SecretString,
+    #[serde(default)]
+    pub kind: EntryKind,
+    /// Значения полей, определённых для вида записи (kinds.rs)
+    #[serde(default)]
+    pub details: Details,
+    #[serde(default)]
+    pub fields: Vec<CustomField>,
+    #[serde(default)]
+    pub urls: Vec<EntryUrl>,
+    #[serde(default)]
+    pub folder_id: Option<i64>,
+    #[serde(default)]
+    pub tag_ids: Vec<i64>,
+    /// Оценка стойкости пароля, вычисляется при чтении (strength.rs)
+    #[serde(default, skip_deserializing)]
+    pub strength: Option<StrengthSummary>,
 }
 
-pub fn get_conn() -> RusqliteResult<Connection> {
-    let conn = Connection::open("./passwords.db")?;
-    
-    // Создаем таблицу пользователей
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS users (
-            id INTEGER PRIMARY KEY,
-            username TEXT UNIQUE NOT NULL,
-            salt TEXT NOT NULL,
-            hash TEXT NOT NULL,
-            otp_secret TEXT
-        )",
-        [],
-    )?;
-    
-    // Создаем таблицу записей
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS entries (
-            id INTEGER PRIMARY KEY,
-            user_id INTEGER NOT NULL,
-            service TEXT NOT NULL,
-            login TEXT NOT NULL,
-            enc_password BLOB NOT NULL,
-            password_nonce BLOB NOT NULL,
-            enc_note BLOB NOT NULL,
-            note_nonce BLOB NOT NULL
-        )",
-        [],
-    )?;
-    
-    // Создаем таблицу псевдо-паролей
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS pseudo_passwords (
-            id INTEGER PRIMARY KEY,
-            user_id INTEGER NOT NULL,
-            salt TEXT NOT NULL,
-            hash TEXT NOT NULL
-        )",
-        [],
-    )?;
+/// Проверяет мастер-пароль пользователя на уже открытом соединении.
+/// Неудачные попытки учитываются счётчиком блокировки (throttle.rs),
+/// поэтому вызывать вне транзакции, иначе откат сотрёт счётчик.
+pub fn check_master_password(conn: &Connection, user_id: i64, password: &SecretString) -> Result<(), XPassError> {
+    let hash: String = conn.query_row(
+        "SELECT hash FROM users WHERE id = ?1",
+        params![user_id],
+        |row| row.get(0)
+    ).map_err(|_| XPassError::UserNotFound)?;
     
-    // ✅ Создаем таблицу фото неудачных входов
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS failed_login_photos (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            user_id INTEGER NOT NULL,
-            encrypted_photo BLOB NOT NULL,
-            photo_nonce BLOB NOT NULL,
-            timestamp TEXT NOT NULL,
-            username_attempt TEXT NOT NULL,
-            FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
-        )",
-        [],
-    )?;
+    throttle::guard(conn, user_id, "password", || {
+        let parsed_hash = PasswordHash::new(&hash).map_err(|_| XPassError::CorruptedKeyMaterial { what: "password hash" })?;
+        let argon2 = Argon2::default();
+        
+        argon2.verify_password(password.expose().as_bytes(), &parsed_hash)
+            .map_err(|_| XPassError::InvalidPassword)
+    })
+}
 
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS users (
-            id INTEGER PRIMARY KEY,
-            username TEXT UNIQUE NOT NULL,
-            salt TEXT NOT NULL,
-            hash TEXT NOT NULL,
-            otp_secret TEXT,
-            otp_recovery_key BLOB,
-            otp_recovery_nonce BLOB,
-            otp_recovery_salt TEXT
-        )",
-        [],
-    )?;
+/// То же для псевдо-сессии: пароль сверяется с псевдо-паролями
+/// пользователя, а неудачи считаются тем же счётчиком, что и для
+/// мастер-пароля, чтобы ответы обеих сессий не различались.
+pub fn check_pseudo_password(conn: &Connection, user_id: i64, password: &SecretString) -> Result<(), XPassError> {
+    let hashes: Vec<String> = {
+        let mut stmt = conn.prepare_cached("SELECT hash FROM pseudo_passwords WHERE user_id = ?1")?;
+        let rows = stmt.query_map(params![user_id], |row| row.get(0))?;
+        rows.collect::<Result<_, _>>()?
+    };
 
-    // Миграция: добавляем колонки если их нет
-    conn.execute_batch("
-        ALTER TABLE users ADD COLUMN otp_recovery_key BLOB;
-        ALTER TABLE users ADD COLUMN otp_recovery_nonce BLOB;
-        ALTER TABLE users ADD COLUMN otp_recovery_salt TEXT;
-    ").ok(); // ok() игнорирует ошибку если колонки уже существуют
-    
-    // Инициализируем таблицу логов активности
-    crate::activity_logger::init_activity_table(&conn)?;
-    pseudo_mode::init_pseudo_mode_table(&conn)?;
-    crate::activity_logger::init_security_tables(&conn)?;
-    
-    // ❌ Удаляем эту строку полностью
-    // crate::database_migration::recreate_photos_table(&conn).ok();
-    
-    Ok(conn)
+    throttle::guard(conn, user_id, "password", || {
+        let argon2 = Argon2::default();
+        for hash in &hashes {
+            let parsed_hash = PasswordHash::new(hash).map_err(|_| XPassError::CorruptedKeyMaterial { what: "pseudo password hash" })?;
+            if argon2.verify_password(password.expose().as_bytes(), &parsed_hash).is_ok() {
+                return Ok(());
+            }
+        }
+        Err(XPassError::InvalidPassword)
+    })
 }
 
-// Добавьте эту функцию после get_conn():
 #[tauri::command] 
-pub fn verify_user_password(user_id: i64, password: String) -> Result<(), String> {
-    let conn = get_conn().map_err(|e| e.to_string())?;
-    
-    let (_salt_str, hash): (String, String) = conn.query_row(
-        "SELECT salt, hash FROM users WHERE id = ?1",
-        params![user_id],
-        |row| Ok((row.get(0)?, row.get(1)?))
-    ).map_err(|_| "User not found".to_string())?;
-    
-    let parsed_hash = PasswordHash::new(&hash).map_err(|_| "Invalid hash")?;
-    let argon2 = Argon2::default();
-    
-    argon2.verify_password(password.as_bytes(), &parsed_hash)
-        .map_err(|_| "Invalid password".to_string())?;
-    
-    Ok(())
+pub fn verify_user_password(db: State<'_, Database>, user_id: i64, password: SecretString) -> Result<(), XPassError> {
+    let conn = db.conn()?;
+    check_master_password(&conn, user_id, &password)
 }
 
 #[tauri::command]
-pub fn list_users() -> Result<Vec<String>, String> {
-    let conn = get_conn().map_err(|e| e.to_string())?;
-    let mut stmt = conn.prepare("SELECT username FROM users ORDER BY username").map_err(|e| e.to_string())?;
-    let rows = stmt.query_map([], |row| row.get(0)).map_err(|e| e.to_string())?;
-    let users: Vec<String> = rows.map(|r| r.map_err(|e| e.to_string())).collect::<Result<_, _>>()?;
+pub fn list_users(db: State<'_, Database>) -> Result<Vec<String>, XPassError> {
+    let conn = db.conn()?;
+    let mut stmt = conn.prepare_cached("SELECT username FROM users ORDER BY username")?;
+    let rows = stmt.query_map([], |row| row.get(0))?;
+    let users: Vec<String> = rows.collect::<Result<_, _>>()?;
     Ok(users)
 }
 
 #[tauri::command]
-pub fn create_user(username: String, master_pass: String) -> Result<i64, String> {
-    let conn = get_conn().map_err(|e| e.to_string())?;
-    let salt = SaltString::generate(&mut OsRng);
-    let argon2 = Argon2::default();
-    let hash = argon2.hash_password(master_pass.as_bytes(), &salt).map_err(|e| e.to_string())?.to_string();
+pub fn create_user(db: State<'_, Database>, username: String, master_pass: SecretString) -> Result<i64, XPassError> {
+    strength::require_master_strength(&master_pass, &[&username])?;
+    let mut conn = db.conn()?;
+    let (salt, hash) = keys::hash_password(&conn, &master_pass)?;
     
-    conn.execute(
+    let tx = conn.transaction()?;
+    tx.execute(
         "INSERT INTO users (username, salt, hash) VALUES (?1, ?2, ?3)",
         params![username, salt.as_str(), hash],
-    ).map_err(|e| e.to_string())?;
+    )?;
     
     // Получаем ID созданного пользователя
-    let user_id = conn.last_insert_rowid();
+    let user_id = tx.last_insert_rowid();
+    
+    // Случайный ключ данных, хранится только в обёрнутом виде
+    let dek = keys::generate_dek();
+    keys::store_password_wrapped_dek(&tx, user_id, &dek, &master_pass)?;
+    keys::ensure_key_check(&tx, user_id, &dek)?;
+    tx.commit()?;
     
     // Логируем создание аккаунта
     crate::activity_logger::log_activity(
@@ -178,73 +156,71 @@ pub fn create_user(username: String, master_pass: String) -> Result<i64, String>
         user_id,
         "account_created",
         &format!("Account created for user: {}", username)
-    ).map_err(|e| e.to_string())?;
+    )?;
     
     Ok(user_id)
 }
 
 #[tauri::command]
-pub fn login(username: String, master_pass: String) -> Result<(i64, String, bool), String> {
+pub fn login(db: State<'_, Database>, sessions: State<'_, SessionStore>, username: String, master_pass: SecretString) -> Result<(i64, String, bool), XPassError> {
     info!("Attempting login for username: {}", username);
-    let conn = get_conn().map_err(|e| e.to_string())?;
-    let mut stmt = conn.prepare("SELECT id, salt, hash FROM users WHERE username = ?1").map_err(|e| e.to_string())?;
+    let conn = db.conn()?;
+    let mut stmt = conn.prepare_cached("SELECT id, salt, hash FROM users WHERE username = ?1")?;
     let mut rows = stmt.query_map(params![&username], |row| {
         Ok((row.get(0)?, row.get::<_, String>(1)?, row.get(2)?))
-    }).map_err(|e| e.to_string())?;
+    })?;
+
+    let row = rows.next().ok_or(XPassError::UserNotFound)??;
+    let (id, _salt_str, hash): (i64, String, String) = row;
+    drop(rows);
+    drop(stmt);
+
+    if master_pass.is_empty() {
+        // Пустой пароль - не разрешен
+        return Err(XPassError::EmptyPassword);
+    }
 
-    let row = rows.next().ok_or("User not found")?.map_err(|e| e.to_string())?;
-    let (id, salt_str, hash): (i64, String, String) = row;
+    // Заблокированный аккаунт отклоняем до проверки пароля
+    throttle::ensure_allowed(&conn, id)?;
 
-    let mut output_key_material = [0u8; 32];
     let argon2 = Argon2::default();
     let mut verified = false;
     let mut login_method = "password";
     let mut is_pseudo = false;
 
-    if !master_pass.is_empty() {
-        let salt = SaltString::from_b64(&salt_str).map_err(|_| "Invalid salt")?;
-        let parsed_hash = PasswordHash::new(&hash).map_err(|_| "Invalid hash")?;
-        
-        // Проверяем реальный пароль
-        if argon2.verify_password(master_pass.as_bytes(), &parsed_hash).is_ok() {
-            verified = true;
-            is_pseudo = false;
-        }
+    let parsed_hash = PasswordHash::new(&hash).map_err(|_| XPassError::CorruptedKeyMaterial { what: "password hash" })?;
+    
+    // Проверяем реальный пароль
+    if argon2.verify_password(master_pass.expose().as_bytes(), &parsed_hash).is_ok() {
+        verified = true;
+        is_pseudo = false;
+    }
+    
+    // Проверяем псевдо-пароли всегда (даже если реальный пароль уже подошел)
+    if !verified {
+        let mut pseudo_stmt = conn.prepare_cached("SELECT salt, hash FROM pseudo_passwords WHERE user_id = ?1")?;
+        let mut pseudo_rows = pseudo_stmt.query_map(params![id], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
         
-        // Проверяем псевдо-пароли всегда (даже если реальный пароль уже подошел)
-        if !verified {
-            let mut pseudo_stmt = conn.prepare("SELECT salt, hash FROM pseudo_passwords WHERE user_id = ?1")
-                .map_err(|e| e.to_string())?;
-            let mut pseudo_rows = pseudo_stmt.query_map(params![id], |row| {
-                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
-            }).map_err(|e| e.to_string())?;
+        while let Some(pseudo_row_result) = pseudo_rows.next() {
+            let (pseudo_salt_str, pseudo_hash_str) = pseudo_row_result?;
+            let pseudo_salt = SaltString::from_b64(&pseudo_salt_str).map_err(|_| XPassError::CorruptedKeyMaterial { what: "pseudo password salt" })?;
+            let pseudo_parsed_hash = PasswordHash::new(&pseudo_hash_str).map_err(|_| XPassError::CorruptedKeyMaterial { what: "pseudo password hash" })?;
             
-            while let Some(pseudo_row_result) = pseudo_rows.next() {
-                let (pseudo_salt_str, pseudo_hash_str) = pseudo_row_result.map_err(|e| e.to_string())?;
-                let pseudo_salt = SaltString::from_b64(&pseudo_salt_str).map_err(|_| "Invalid pseudo salt")?;
-                let pseudo_parsed_hash = PasswordHash::new(&pseudo_hash_str).map_err(|_| "Invalid pseudo hash")?;
-                
-                if argon2.verify_password(master_pass.as_bytes(), &pseudo_parsed_hash).is_ok() {
-                    verified = true;
-                    login_method = "pseudo_password";
-                    is_pseudo = true;
-                    info!("Pseudo password login successful for username: {}", username);
-                    break;
-                }
+            if argon2.verify_password(master_pass.expose().as_bytes(), &pseudo_parsed_hash).is_ok() {
+                verified = true;
+                login_method = "pseudo_password";
+                is_pseudo = true;
+                info!("Pseudo password login successful for username: {}", username);
+                break;
             }
         }
-        
-        if verified {
-            argon2
-                .hash_password_into(master_pass.as_bytes(), salt.as_str().as_bytes(), &mut output_key_material)
-                .map_err(|e| e.to_string())?;
-        }
-    } else {
-        // Пустой пароль - не разрешен
-        return Err("Password cannot be empty".to_string());
     }
-
+    
     if !verified {
+        throttle::record_failure(&conn, id, "password")?;
+        
         // Логируем неудачную попытку входа
         crate::activity_logger::log_activity(
             &conn,
@@ -253,10 +229,43 @@ pub fn login(username: String, master_pass: String) -> Result<(i64, String, bool
             &format!("Failed login attempt for user: {}", username)
         ).ok();
         
-        return Err("Invalid password".to_string());
+        return Err(XPassError::InvalidPassword);
+    }
+
+    let output_key_material = if is_pseudo {
+        // Псевдо-сессия получает случайный ключ: настоящие записи
+        // не расшифруются, и get_entries покажет фейковые
+        keys::generate_dek()
+    } else {
+        let dek = keys::unlock_dek(&conn, id, &master_pass)?;
+        keys::ensure_key_check(&conn, id, &dek)?;
+        
+        // Параметры Argon2 ниже текущей политики - пересчитываем хеш и ключ
+        match keys::upgrade_password_kdf(&conn, id, &dek, &master_pass) {
+            Ok(true) => {
+                crate::activity_logger::log_activity(
+                    &conn,
+                    id,
+                    "kdf_upgraded",
+                    "Password hash and vault key upgraded to the current KDF policy"
+                ).ok();
+            }
+            Ok(false) => {}
+            Err(e) => log::warn!("KDF upgrade failed for user_id {}: {}", id, e),
+        }
+        dek
+    };
+
+    // Успешный вход (в том числе по псевдо-паролю) сбрасывает счётчик
+    throttle::record_success(&conn, id)?;
+
+    // Окончательно удаляем записи, срок хранения которых в корзине истёк
+    if let Err(e) = trash::purge_expired(&conn, id) {
+        log::warn!("Trash purge failed for user_id {}: {}", id, e);
     }
 
-    let enc_key = general_purpose::STANDARD.encode(output_key_material);
+    // Ключ остаётся в бэкенде, фронтенд получает только идентификатор сессии
+    let session_id = sessions.open(id, output_key_material);
     
     // Логируем успешный вход
     crate::activity_logger::log_activity(
@@ -264,94 +273,183 @@ pub fn login(username: String, master_pass: String) -> Result<(i64, String, bool
         id,
         if is_pseudo { "pseudo_login_access" } else { "login" },
         &format!("Successful login via {} for user: {}", login_method, username)
-    ).map_err(|e| e.to_string())?;
+    )?;
+    
+    // Индекс поиска строится после всех записей в базу, иначе первый же
+    // поиск счёл бы его устаревшим
+    search::index_session(&conn, &sessions, &session_id, id);
     
     info!("Login successful for username: {} (pseudo: {})", username, is_pseudo);
     
-    Ok((id, enc_key, is_pseudo))
+    Ok((id, session_id, is_pseudo))
 }
 
 
 #[tauri::command]
-pub fn add_entry(user_id: i64, service: String, login: String, password: String, note: String, enc_key: String) -> Result<(), String> {
-    let conn = get_conn().map_err(|e| e.to_string())?;
-
-    let key_bytes = general_purpose::STANDARD.decode(enc_key).map_err(|e| e.to_string())?;
-    if key_bytes.len() != 32 {
-        return Err("Invalid key length".to_string());
+pub fn add_entry(
+    db: State<'_, Database>,
+    sessions: State<'_, SessionStore>,
+    user_id: i64,
+    service: String,
+    login: String,
+    password: SecretString,
+    note: SecretString,
+    kind: Option<EntryKind>,
+    details: Option<Details>,
+    fields: Option<Vec<CustomField>>,
+    urls: Option<Vec<EntryUrl>>,
+    session_id: String
+) -> Result<(), XPassError> {
+    let kind = kind.unwrap_or_default();
+    let details = details.unwrap_or_default();
+    kinds::validate(kind, &details)?;
+    let fields = fields.unwrap_or_default();
+    fields::validate(&fields)?;
+    let urls = urls.unwrap_or_default();
+    urls::validate(&urls)?;
+
+    let cipher = sessions.cipher(&session_id, user_id)?;
+    let mut conn = db.conn()?;
+
+    // Псевдо-сессия держит случайный ключ: записанное им настоящий ключ
+    // не расшифрует, поэтому «успешно» ничего не пишем
+    if !keys::is_vault_key(&conn, user_id, &cipher)? {
+        return Ok(());
     }
-    let key = aes_gcm::Key::<Aes256Gcm>::from_slice(&key_bytes);
-    let cipher = Aes256Gcm::new(key);
 
     let password_nonce = Aes256Gcm::generate_nonce(&mut rand::thread_rng());
-    let enc_password = cipher.encrypt(&password_nonce, password.as_bytes().as_ref()).map_err(|_| "Encryption failed".to_string())?;
+    let enc_password = cipher.encrypt(&password_nonce, password.expose().as_bytes()).map_err(|_| XPassError::EncryptionFailed)?;
 
     let note_nonce = Aes256Gcm::generate_nonce(&mut rand::thread_rng());
-    let enc_note = cipher.encrypt(&note_nonce, note.as_bytes().as_ref()).map_err(|_| "Encryption failed".to_string())?;
+    let enc_note = cipher.encrypt(&note_nonce, note.expose().as_bytes()).map_err(|_| XPassError::EncryptionFailed)?;
 
-    conn.execute(
-        "INSERT INTO entries (user_id, service, login, enc_password, password_nonce, enc_note, note_nonce)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
-        params![user_id, service, login, &*enc_password, &*password_nonce, &*enc_note, &*note_nonce],
-    ).map_err(|e| e.to_string())?;
+    let (enc_details, details_nonce) = kinds::seal(&cipher, &details)?;
+
+    let tx = conn.transaction()?;
+    tx.execute(
+        "INSERT INTO entries (user_id, service, login, enc_password, password_nonce, enc_note, note_nonce, kind, enc_details, details_nonce,
+                              password_changed_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+        params![user_id, service, login, &*enc_password, &*password_nonce, &*enc_note, &*note_nonce, kind.as_str(), enc_details, details_nonce,
+                Utc::now().to_rfc3339()],
+    )?;
+    let entry_id = tx.last_insert_rowid();
+    fields::save_fields(&tx, entry_id, &fields, &cipher)?;
+    urls::save_urls(&tx, entry_id, &urls, &cipher)?;
 
     // Логируем добавление записи
     crate::activity_logger::log_activity(
-        &conn,
+        &tx,
         user_id,
         "add_entry",
         &format!("Added entry for service: {}", service)
-    ).map_err(|e| e.to_string())?;
+    )?;
 
+    tx.commit()?;
     Ok(())
 }
 
 #[tauri::command]
 pub fn update_entry(
+    db: State<'_, Database>,
+    sessions: State<'_, SessionStore>,
     entry_id: i64,
     user_id: i64,
     service: String,
     login: String,
-    password: String,
-    note: String,
-    enc_key: String
-) -> Result<(), String> {
-    let conn = get_conn().map_err(|e| e.to_string())?;
-
-    let key_bytes = general_purpose::STANDARD.decode(enc_key).map_err(|e| e.to_string())?;
-    if key_bytes.len() != 32 {
-        return Err("Invalid key length".to_string());
+    password: SecretString,
+    note: SecretString,
+    kind: Option<EntryKind>,
+    details: Option<Details>,
+    fields: Option<Vec<CustomField>>,
+    urls: Option<Vec<EntryUrl>>,
+    session_id: String
+) -> Result<(), XPassError> {
+    if let Some(fields) = &fields {
+        fields::validate(fields)?;
+    }
+    if let Some(urls) = &urls {
+        urls::validate(urls)?;
+    }
+
+    let cipher = sessions.cipher(&session_id, user_id)?;
+    let mut conn = db.conn()?;
+
+    // Псевдо-сессия правит только фейковые записи, которых в базе нет
+    if !keys::is_vault_key(&conn, user_id, &cipher)? {
+        return Ok(());
     }
-    let key = aes_gcm::Key::<Aes256Gcm>::from_slice(&key_bytes);
-    let cipher = Aes256Gcm::new(key);
 
     let password_nonce = Aes256Gcm::generate_nonce(&mut rand::thread_rng());
-    let enc_password = cipher.encrypt(&password_nonce, password.as_bytes().as_ref())
-        .map_err(|_| "Encryption failed".to_string())?;
+    let enc_password = cipher.encrypt(&password_nonce, password.expose().as_bytes())
+        .map_err(|_| XPassError::EncryptionFailed)?;
 
     let note_nonce = Aes256Gcm::generate_nonce(&mut rand::thread_rng());
-    let enc_note = cipher.encrypt(&note_nonce, note.as_bytes().as_ref())
-        .map_err(|_| "Encryption failed".to_string())?;
+    let enc_note = cipher.encrypt(&note_nonce, note.expose().as_bytes())
+        .map_err(|_| XPassError::EncryptionFailed)?;
+
+    let tx = conn.transaction()?;
+    let (current_kind, current_password, current_nonce): (String, Vec<u8>, Vec<u8>) = tx.query_row(
+        "SELECT kind, enc_password, password_nonce FROM entries WHERE id = ?1 AND user_id = ?2 AND deleted_at IS NULL",
+        params![entry_id, user_id],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+    ).optional()?.ok_or(XPassError::NotFound { what: "Entry" })?;
+    // Дата смены пароля обновляется, только если пароль действительно другой
+    let password_changed = keys::decrypt_secret(&cipher, &current_password, &current_nonce)
+        .map_or(true, |current| current != password);
+
+    // Без вида и без деталей оставляем существующие как есть; детали
+    // без вида проверяются по текущему виду записи
+    let new_details = match (kind, details) {
+        (None, None) => None,
+        (kind, details) => {
+            let kind = kind.unwrap_or_else(|| EntryKind::parse(&current_kind));
+            let details = details.unwrap_or_default();
+            kinds::validate(kind, &details)?;
+            Some((kind, kinds::seal(&cipher, &details)?))
+        }
+    };
 
-    let count = conn.execute(
+    // Сохраняем предыдущую версию записи в историю
+    history::record_revision(&tx, user_id, entry_id)?;
+
+    tx.execute(
         "UPDATE entries 
          SET service = ?1, login = ?2, enc_password = ?3, password_nonce = ?4, enc_note = ?5, note_nonce = ?6
          WHERE id = ?7 AND user_id = ?8",
         params![service, login, &*enc_password, &*password_nonce, &*enc_note, &*note_nonce, entry_id, user_id],
-    ).map_err(|e| e.to_string())?;
+    )?;
 
-    if count == 0 {
-        return Err("Entry not found or not authorized".to_string());
+    if let Some((kind, (enc_details, details_nonce))) = new_details {
+        tx.execute(
+            "UPDATE entries SET kind = ?1, enc_details = ?2, details_nonce = ?3 WHERE id = ?4",
+            params![kind.as_str(), enc_details, details_nonce, entry_id],
+        )?;
+    }
+    if password_changed {
+        tx.execute(
+            "UPDATE entries SET password_changed_at = ?1 WHERE id = ?2",
+            params![Utc::now().to_rfc3339(), entry_id],
+        )?;
+    }
+
+    // Без списка полей или адресов оставляем существующие как есть
+    if let Some(fields) = &fields {
+        fields::save_fields(&tx, entry_id, fields, &cipher)?;
+    }
+    if let Some(urls) = &urls {
+        urls::save_urls(&tx, entry_id, urls, &cipher)?;
     }
 
     // Логируем обновление записи
     crate::activity_logger::log_activity(
-        &conn,
+        &tx,
         user_id,
         "edit_entry",
         &format!("Updated entry for service: {}", service)
-    ).map_err(|e| e.to_string())?;
+    )?;
 
+    tx.commit()?;
     Ok(())
 }
 
@@ -364,26 +462,17 @@ struct RawEntry {
     password_nonce: Vec<u8>,
     enc_note: Vec<u8>,
     note_nonce: Vec<u8>,
+    kind: String,
+    enc_details: Option<Vec<u8>>,
+    details_nonce: Option<Vec<u8>>,
+    folder_id: Option<i64>,
 }
 
-#[tauri::command]
-pub fn get_entries(user_id: i64, enc_key: String) -> Result<Vec<Entry>, String> {
-    let conn = get_conn().map_err(|e| e.to_string())?;
-    let key_bytes = general_purpose::STANDARD.decode(enc_key).map_err(|e| e.to_string())?;
-    
-    if key_bytes.len() != 32 {
-        return Err("Invalid key length".to_string());
-    }
-    
-    let key = aes_gcm::Key::<Aes256Gcm>::from_slice(&key_bytes);
-    let cipher = Aes256Gcm::new(key);
-    
-    let mut stmt = conn.prepare(
-        "SELECT id, service, login, enc_password, password_nonce, enc_note, note_nonce 
-         FROM entries WHERE user_id = ?1"
-    ).map_err(|e| e.to_string())?;
-    
-    let raw_entries: Vec<RawEntry> = stmt.query_map(params![user_id], |row| {
+const ENTRY_COLUMNS: &str =
+    "id, service, login, enc_password, password_nonce, enc_note, note_nonce, kind, enc_details, details_nonce, folder_id";
+
+impl RawEntry {
+    fn from_row(row: &rusqlite::Row) -> rusqlite::Result<RawEntry> {
         Ok(RawEntry {
             id: row.get(0)?,
             service: row.get(1)?,
@@ -392,128 +481,201 @@ pub fn get_entries(user_id: i64, enc_key: String) -> Result<Vec<Entry>, String>
             password_nonce: row.get(4)?,
             enc_note: row.get(5)?,
             note_nonce: row.get(6)?,
+            kind: row.get(7)?,
+            enc_details: row.get(8)?,
+            details_nonce: row.get(9)?,
+            folder_id: row.get(10)?,
         })
-    }).map_err(|e| e.to_string())?
-    .filter_map(|r| r.ok())
-    .collect();
-    
-    if raw_entries.is_empty() {
-        return Ok(vec![]);
     }
-    
-    // Проверяем дешифровку первой записи
-    let test_nonce = Nonce::from_slice(&raw_entries[0].password_nonce);
-    match cipher.decrypt(test_nonce, raw_entries[0].enc_password.as_ref()) {
-        Ok(_) => {
-            // Реальные данные: дешифруем все
-            let mut entries = Vec::new();
-            for raw in raw_entries {
-                let password_nonce = Nonce::from_slice(&raw.password_nonce);
-                let dec_password = cipher.decrypt(password_nonce, raw.enc_password.as_ref())
-                    .map_err(|_| "Decryption failed".to_string())?;
-                let password = String::from_utf8(dec_password)
-                    .map_err(|_| "Invalid UTF-8".to_string())?;
-                
-                let note_nonce = Nonce::from_slice(&raw.note_nonce);
-                let dec_note = cipher.decrypt(note_nonce, raw.enc_note.as_ref())
-                    .map_err(|_| "Decryption failed".to_string())?;
-                let note = String::from_utf8(dec_note)
-                    .map_err(|_| "Invalid UTF-8".to_string())?;
-                
-                entries.push(Entry {
-                    id: raw.id,
-                    service: raw.service,
-                    login: raw.login,
-                    password,
-                    note,
-                });
-            }
-            Ok(entries)
-        }
-        Err(_) => {
-            // Псевдо-логин: проверяем настройки и возвращаем соответствующие данные
-            info!("Pseudo login detected, checking settings for fake data");
-            
-            // Получаем настройки псевдо-режима
-            let show_fake_entries: bool = conn.query_row(
-                "SELECT COALESCE(show_fake_entries, 1) 
-                 FROM pseudo_mode_settings 
-                 WHERE user_id = ?1",
-                params![user_id],
-                |row| row.get(0)
-            ).unwrap_or(true);
-            
-            // Логируем попытку доступа с неверным ключом
-            crate::activity_logger::log_activity(
-                &conn,
-                user_id,
-                "pseudo_login_access",
-                if show_fake_entries {
-                    "Accessed vault with pseudo password - fake data shown"
-                } else {
-                    "Accessed vault with pseudo password - empty vault shown"
-                }
-            ).ok();
-            
+
+    fn decrypt(self, conn: &Connection, cipher: &Aes256Gcm) -> Result<Entry, XPassError> {
+        let password_nonce = Nonce::from_slice(&self.password_nonce);
+        let dec_password = cipher.decrypt(password_nonce, self.enc_password.as_ref())
+            .map_err(|_| XPassError::DecryptionFailed)?;
+        let password = SecretString::from_utf8(dec_password.into())
+            .ok_or(XPassError::DecryptionFailed)?;
+        
+        let note_nonce = Nonce::from_slice(&self.note_nonce);
+        let dec_note = cipher.decrypt(note_nonce, self.enc_note.as_ref())
+            .map_err(|_| XPassError::DecryptionFailed)?;
+        let note = SecretString::from_utf8(dec_note.into())
+            .ok_or(XPassError::DecryptionFailed)?;
+        
+        let details = kinds::open(cipher, self.enc_details.as_deref(), self.details_nonce.as_deref())?;
+        let fields = fields::load_fields(conn, self.id, cipher)?;
+        let urls = urls::load_urls(conn, self.id, cipher)?;
+        let tag_ids = tags::entry_tag_ids(conn, self.id)?;
+        let strength = strength::summary(&password, &[&self.service, &self.login]);
+        
+        Ok(Entry {
+            id: self.id,
+            service: self.service,
+            login: self.login,
+            password,
+            note,
+            kind: EntryKind::parse(&self.kind),
+            details,
+            fields,
+            urls,
+            folder_id: self.folder_id,
+            tag_ids,
+            strength,
+        })
+    }
+}
+
+/// Расшифровывает все записи пользователя вне корзины. Только для
+/// настоящего ключа данных, псевдо-сессии проверять заранее.
+pub fn load_entries(conn: &Connection, user_id: i64, cipher: &Aes256Gcm) -> Result<Vec<Entry>, XPassError> {
+    let mut stmt = conn.prepare_cached(&format!(
+        "SELECT {} FROM entries WHERE user_id = ?1 AND deleted_at IS NULL ORDER BY id",
+        ENTRY_COLUMNS
+    ))?;
+    let raw_entries: Vec<RawEntry> = stmt.query_map(params![user_id], RawEntry::from_row)?
+        .collect::<Result<_, _>>()?;
+    raw_entries.into_iter().map(|raw| raw.decrypt(conn, cipher)).collect()
+}
+
+/// Расшифровывает одну запись пользователя вне корзины
+pub fn load_entry(conn: &Connection, user_id: i64, entry_id: i64, cipher: &Aes256Gcm) -> Result<Entry, XPassError> {
+    conn.query_row(
+        &format!("SELECT {} FROM entries WHERE id = ?1 AND user_id = ?2 AND deleted_at IS NULL", ENTRY_COLUMNS),
+        params![entry_id, user_id],
+        RawEntry::from_row
+    ).optional()?
+        .ok_or(XPassError::NotFound { what: "Entry" })?
+        .decrypt(conn, cipher)
+}
+
+fn shows_fake_entries(conn: &Connection, user_id: i64) -> bool {
+    conn.query_row(
+        "SELECT COALESCE(show_fake_entries, 1) 
+         FROM pseudo_mode_settings 
+         WHERE user_id = ?1",
+        params![user_id],
+        |row| row.get(0)
+    ).unwrap_or(true)
+}
+
+/// Что видит псевдо-сессия: фейковые записи или пустое хранилище,
+/// в зависимости от настроек псевдо-режима
+pub fn pseudo_entries(conn: &Connection, user_id: i64) -> Vec<Entry> {
+    if shows_fake_entries(conn, user_id) {
+        generate_fake_entries_for_user(user_id, true, 31)
+    } else {
+        vec![]
+    }
+}
+
+/// Возвращает записи пользователя, по желанию только из папки (вместе
+/// с вложенными) и/или только с заданным тегом
+#[tauri::command]
+pub fn get_entries(
+    db: State<'_, Database>,
+    sessions: State<'_, SessionStore>,
+    user_id: i64,
+    session_id: String,
+    folder_id: Option<i64>,
+    tag_id: Option<i64>
+) -> Result<Vec<Entry>, XPassError> {
+    let cipher = sessions.cipher(&session_id, user_id)?;
+    let conn = db.conn()?;
+    
+    if !keys::is_vault_key(&conn, user_id, &cipher)? {
+        // Псевдо-логин: проверяем настройки и возвращаем соответствующие данные
+        info!("Pseudo login detected, checking settings for fake data");
+        
+        // Получаем настройки псевдо-режима
+        let show_fake_entries = shows_fake_entries(&conn, user_id);
+        
+        // Логируем попытку доступа с неверным ключом
+        crate::activity_logger::log_activity(
+            &conn,
+            user_id,
+            "pseudo_login_access",
             if show_fake_entries {
-                // Возвращаем реалистичные фейковые данные
-                Ok(generate_fake_entries_for_user(user_id, true, 31))
+                "Accessed vault with pseudo password - fake data shown"
             } else {
-                // Возвращаем пустой список (пустое хранилище)
-                Ok(vec![])
+                "Accessed vault with pseudo password - empty vault shown"
             }
+        ).ok();
+        
+        if show_fake_entries {
+            // Возвращаем реалистичные фейковые данные
+            return Ok(generate_fake_entries_for_user(user_id, true, 31));
         }
+        // Возвращаем пустой список (пустое хранилище)
+        return Ok(vec![]);
     }
+    
+    let mut stmt = conn.prepare_cached(&format!(
+        "SELECT {} 
+         FROM entries
+         WHERE user_id = ?1 AND deleted_at IS NULL
+           AND (?2 IS NULL OR folder_id IN (
+                WITH RECURSIVE subtree(id) AS (
+                    SELECT ?2
+                    UNION ALL
+                    SELECT folders.id FROM folders JOIN subtree ON folders.parent_id = subtree.id
+                )
+                SELECT id FROM subtree
+           ))
+           AND (?3 IS NULL OR id IN (SELECT entry_id FROM entry_tags WHERE tag_id = ?3))
+         ORDER BY service COLLATE NOCASE, login COLLATE NOCASE, id",
+        ENTRY_COLUMNS
+    ))?;
+    
+    let raw_entries: Vec<RawEntry> = stmt.query_map(params![user_id, folder_id, tag_id], RawEntry::from_row)?
+    .filter_map(|r| r.ok())
+    .collect();
+    
+    // Реальные данные: дешифруем все
+    raw_entries.into_iter()
+        .map(|raw| raw.decrypt(&conn, &cipher))
+        .collect()
 }
 
+/// Перемещает запись в корзину (см. trash.rs), окончательно она
+/// удаляется по истечении срока хранения или при очистке корзины
 #[tauri::command]
-pub fn delete_entry(entry_id: i64, user_id: i64) -> Result<(), String> {
-    let conn = get_conn().map_err(|e| e.to_string())?;
-    
-    // Получаем информацию о записи перед удалением для логирования
-    let service: String = conn.query_row(
-        "SELECT service FROM entries WHERE id = ?1 AND user_id = ?2",
-        params![entry_id, user_id],
-        |row| row.get(0)
-    ).map_err(|_| "Entry not found".to_string())?;
-    
-    let count = conn.execute(
-        "DELETE FROM entries WHERE id = ?1 AND user_id = ?2",
-        params![entry_id, user_id],
-    ).map_err(|e| e.to_string())?;
+pub fn delete_entry(
+    db: State<'_, Database>,
+    sessions: State<'_, SessionStore>,
+    entry_id: i64,
+    user_id: i64,
+    session_id: String
+) -> Result<(), XPassError> {
+    let cipher = sessions.cipher(&session_id, user_id)?;
+    
+    // Фейковые записи псевдо-режима имеют отрицательные id и в базе
+    // не существуют: удаление «успешно», чтобы режим не выдал себя
+    if entry_id < 0 {
+        return Ok(());
+    }
     
-    if count == 0 {
-        return Err("Entry not found or not authorized".to_string());
+    let conn = db.conn()?;
+    // Псевдо-сессия не должна отправлять в корзину настоящие записи
+    if !keys::is_vault_key(&conn, user_id, &cipher)? {
+        return Ok(());
     }
+    let service = trash::move_to_trash(&conn, user_id, entry_id)?;
     
     // Логируем удаление записи
     crate::activity_logger::log_activity(
         &conn,
         user_id,
         "delete_entry",
-        &format!("Deleted entry for service: {}", service)
-    ).map_err(|e| e.to_string())?;
+        &format!("Moved entry for service: {} to trash", service)
+    )?;
     
     Ok(())
 }
 
 #[tauri::command]
-pub fn delete_user(user_id: i64, master_pass: String) -> Result<(), String> {
-    let mut conn = get_conn().map_err(|e| e.to_string())?;
+pub fn delete_user(db: State<'_, Database>, sessions: State<'_, SessionStore>, user_id: i64, master_pass: SecretString) -> Result<(), XPassError> {
+    let mut conn = db.conn()?;
     
-    // Получаем соль и хеш
-    let (salt_str, hash): (String, String) = {
-        let mut stmt = conn.prepare("SELECT salt, hash FROM users WHERE id = ?1").map_err(|e| e.to_string())?;
-        let row = stmt.query_row(params![user_id], |row| {
-            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
-        }).map_err(|_| "User not found".to_string())?;
-        row
-    };
-
-    let salt = SaltString::from_b64(&salt_str).map_err(|_| "Invalid salt")?;
-    let parsed_hash = PasswordHash::new(&hash).map_err(|_| "Invalid hash")?;
-    let argon2 = Argon2::default();
-    argon2.verify_password(master_pass.as_bytes(), &parsed_hash).map_err(|_| "Invalid password")?;
+    check_master_password(&conn, user_id, &master_pass)?;
 
     // Логируем удаление аккаунта
     crate::activity_logger::log_activity(
@@ -524,76 +686,158 @@ pub fn delete_user(user_id: i64, master_pass: String) -> Result<(), String> {
     ).ok();
 
     // Начинаем транзакцию для удаления пользователя и его данных
-    let tx = conn.transaction().map_err(|e| e.to_string())?;
-    tx.execute("DELETE FROM entries WHERE user_id = ?1", params![user_id]).map_err(|e| e.to_string())?;
-    tx.execute("DELETE FROM activity_logs WHERE user_id = ?1", params![user_id]).map_err(|e| e.to_string())?;
-    tx.execute("DELETE FROM pseudo_passwords WHERE user_id = ?1", params![user_id]).map_err(|e| e.to_string())?;
-    let count = tx.execute("DELETE FROM users WHERE id = ?1", params![user_id]).map_err(|e| e.to_string())?;
+    let tx = conn.transaction()?;
+    tx.execute("DELETE FROM entries WHERE user_id = ?1", params![user_id])?;
+    tx.execute("DELETE FROM activity_logs WHERE user_id = ?1", params![user_id])?;
+    tx.execute("DELETE FROM pseudo_passwords WHERE user_id = ?1", params![user_id])?;
+    tx.execute("DELETE FROM login_attempts WHERE user_id = ?1", params![user_id])?;
+    let count = tx.execute("DELETE FROM users WHERE id = ?1", params![user_id])?;
     
     if count == 0 {
-        return Err("User not found".to_string());
+        return Err(XPassError::UserNotFound);
     }
     
-    tx.commit().map_err(|e| e.to_string())?;
+    tx.commit()?;
+    sessions.close_user(user_id);
     Ok(())
 }
 
 
+/// Меняет мастер-пароль. Записи зашифрованы ключом данных, поэтому
+/// перешифровывать их не нужно: ключ данных лишь заново оборачивается
+/// ключом от нового пароля. Все сессии пользователя закрываются,
+/// вызывающему возвращается новая сессия.
+#[tauri::command]
+pub fn change_master_password(
+    db: State<'_, Database>,
+    sessions: State<'_, SessionStore>,
+    user_id: i64,
+    current_password: SecretString,
+    new_password: SecretString
+) -> Result<String, XPassError> {
+    if new_password.is_empty() {
+        return Err(XPassError::EmptyPassword);
+    }
+    if new_password == current_password {
+        return Err(XPassError::PasswordUnchanged);
+    }
+
+    let conn = db.conn()?;
+    check_master_password(&conn, user_id, &current_password)?;
+    let username: String = conn.query_row(
+        "SELECT username FROM users WHERE id = ?1",
+        params![user_id],
+        |row| row.get(0)
+    )?;
+    strength::require_master_strength(&new_password, &[&username])?;
+
+    let argon2 = Argon2::default();
+
+    // Новый пароль не должен совпадать ни с одним псевдо-паролем,
+    // иначе вход по нему стал бы неоднозначным
+    {
+        let mut stmt = conn.prepare("SELECT hash FROM pseudo_passwords WHERE user_id = ?1")?;
+        let hashes = stmt.query_map(params![user_id], |row| row.get::<_, String>(0))?;
+        for hash in hashes {
+            let hash = hash?;
+            let parsed_hash = PasswordHash::new(&hash).map_err(|_| XPassError::CorruptedKeyMaterial { what: "pseudo password hash" })?;
+            if argon2.verify_password(new_password.expose().as_bytes(), &parsed_hash).is_ok() {
+                return Err(XPassError::PasswordMatchesPseudo);
+            }
+        }
+    }
+
+    let (new_salt, new_hash) = keys::hash_password(&conn, &new_password)?;
+    drop(conn);
+
+    // Сначала перешифровываем файл, если он зашифрован этим паролем: пока
+    // в users старый пароль, неудача здесь ничего не меняет
+    let rekeyed = db.rekey(current_password.expose(), new_password.expose())?;
+
+    let committed = (|| -> Result<_, XPassError> {
+        let mut conn = db.conn()?;
+        let tx = conn.transaction()?;
+
+        // Для старых аккаунтов ключ данных создаётся здесь же, в транзакции
+        let dek = keys::unlock_dek(&tx, user_id, &current_password)?;
+
+        tx.execute(
+            "UPDATE users SET salt = ?1, hash = ?2 WHERE id = ?3",
+            params![new_salt.as_str(), new_hash, user_id],
+        )?;
+
+        keys::store_password_wrapped_dek(&tx, user_id, &dek, &new_password)?;
+
+        crate::activity_logger::log_activity(
+            &tx,
+            user_id,
+            "master_password_changed",
+            "Master password changed, vault key re-wrapped"
+        )?;
+
+        tx.commit()?;
+        Ok(dek)
+    })();
+
+    let dek = match committed {
+        Ok(dek) => dek,
+        Err(e) => {
+            // Возвращаем файлу старый ключ, чтобы он снова открывался старым паролем
+            if rekeyed {
+                if let Err(undo) = db.rekey(new_password.expose(), current_password.expose()) {
+                    log::error!("Failed to restore the vault key for user_id {}: {}", user_id, undo);
+                }
+            }
+            return Err(e);
+        }
+    };
+    if rekeyed {
+        info!("Vault database re-encrypted after master password change for user_id: {}", user_id);
+    }
+
+    // Новая сессия открывается, только когда и файл, и пароль сменились
+    sessions.close_user(user_id);
+    let session_id = sessions.open(user_id, dek);
+
+    info!("Master password changed for user_id: {}", user_id);
+    Ok(session_id)
+}
+
 /// Сохраняет ключ восстановления для OTP (вызывается при настройке OTP)
 #[tauri::command]
-pub fn setup_otp_recovery(user_id: i64, master_pass: String) -> Result<(), String> {
+pub fn setup_otp_recovery(db: State<'_, Database>, user_id: i64, master_pass: SecretString) -> Result<(), XPassError> {
     info!("Setting up OTP recovery for user_id: {}", user_id);
-    let conn = get_conn().map_err(|e| e.to_string())?;
+    let conn = db.conn()?;
     
-    // 1. Проверяем мастер-пароль и получаем данные
-    let (salt_str, hash, username): (String, String, String) = conn.query_row(
-        "SELECT salt, hash, username FROM users WHERE id = ?1",
+    // 1. Проверяем мастер-пароль
+    check_master_password(&conn, user_id, &master_pass)?;
+    let username: String = conn.query_row(
+        "SELECT username FROM users WHERE id = ?1",
         params![user_id],
-        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?))
-    ).map_err(|_| "User not found".to_string())?;
-    
-    let parsed_hash = PasswordHash::new(&hash).map_err(|_| "Invalid hash")?;
-    let argon2 = Argon2::default();
-    
-    // Проверяем мастер-пароль
-    argon2.verify_password(master_pass.as_bytes(), &parsed_hash)
-        .map_err(|_| "Invalid password".to_string())?;
-    
-    // 2. Генерируем ключ шифрования из мастер-пароля (тот же, что используется для записей)
-    let mut encryption_key = [0u8; 32];
-    let salt = SaltString::from_b64(&salt_str).map_err(|_| "Invalid salt")?;
-    argon2
-        .hash_password_into(master_pass.as_bytes(), salt.as_str().as_bytes(), &mut encryption_key)
-        .map_err(|e| e.to_string())?;
+        |row| row.get(0)
+    ).map_err(|_| XPassError::UserNotFound)?;
     
-    // 3. Генерируем случайный ключ для шифрования recovery key
-    let otp_recovery_salt = SaltString::generate(&mut OsRng);
-    let mut otp_derived_key = [0u8; 32];
+    // 2. Получаем ключ данных (тот же, которым зашифрованы записи)
+    let dek = keys::unlock_dek(&conn, user_id, &master_pass)?;
     
-    // Используем OTP secret как основу для ключа шифрования
-    let otp_secret: String = conn.query_row(
+    // 3. Используем OTP secret как основу для ключа обёртки
+    let otp_secret: SecretString = conn.query_row(
         "SELECT otp_secret FROM users WHERE id = ?1",
         params![user_id],
         |row| row.get(0)
-    ).map_err(|_| "OTP secret not found. Please generate OTP first.".to_string())?;
-    
-    argon2
-        .hash_password_into(otp_secret.as_bytes(), otp_recovery_salt.as_str().as_bytes(), &mut otp_derived_key)
-        .map_err(|e| e.to_string())?;
+    ).map_err(|_| XPassError::OtpNotConfigured)?;
     
-    // 4. Шифруем ключ шифрования с помощью производного ключа от OTP secret
-    let key = aes_gcm::Key::<Aes256Gcm>::from_slice(&otp_derived_key);
-    let cipher = Aes256Gcm::new(key);
-    let nonce = Aes256Gcm::generate_nonce(&mut rand::thread_rng());
+    let otp_recovery_salt = SaltString::generate(&mut OsRng);
+    let otp_derived_key = keys::derive_key(&otp_secret, otp_recovery_salt.as_str(), &KdfParams::LEGACY)?;
     
-    let encrypted_key = cipher.encrypt(&nonce, encryption_key.as_ref())
-        .map_err(|_| "Failed to encrypt recovery key".to_string())?;
+    // 4. Оборачиваем ключ данных производным ключом от OTP secret
+    let (encrypted_key, nonce) = keys::wrap_key(&otp_derived_key, &dek)?;
     
-    // 5. Сохраняем зашифрованный ключ восстановления
+    // 5. Сохраняем обёрнутый ключ восстановления
     conn.execute(
         "UPDATE users SET otp_recovery_key = ?1, otp_recovery_nonce = ?2, otp_recovery_salt = ?3 WHERE id = ?4",
-        params![&encrypted_key, &*nonce, otp_recovery_salt.as_str(), user_id],
-    ).map_err(|e| e.to_string())?;
+        params![&encrypted_key, &nonce, otp_recovery_salt.as_str(), user_id],
+    )?;
     
     // Логируем настройку OTP recovery
     crate::activity_logger::log_activity(
@@ -609,8 +853,8 @@ pub fn setup_otp_recovery(user_id: i64, master_pass: String) -> Result<(), Strin
 
 /// Проверяет, настроен ли OTP recovery для пользователя
 #[tauri::command]
-pub fn has_otp_recovery(username: String) -> Result<bool, String> {
-    let conn = get_conn().map_err(|e| e.to_string())?;
+pub fn has_otp_recovery(db: State<'_, Database>, username: String) -> Result<bool, XPassError> {
+    let conn = db.conn()?;
     
     let has_recovery: bool = conn.query_row(
         "SELECT otp_recovery_key IS NOT NULL FROM users WHERE username = ?1",
@@ -623,60 +867,60 @@ pub fn has_otp_recovery(username: String) -> Result<bool, String> {
 
 /// Вход с помощью OTP (использует recovery key)
 #[tauri::command]
-pub fn login_with_otp(username: String, otp_code: String) -> Result<(i64, String, bool), String> {
+pub fn login_with_otp(db: State<'_, Database>, sessions: State<'_, SessionStore>, username: String, otp_code: SecretString) -> Result<(i64, String, bool), XPassError> {
     info!("OTP login attempt for username: {}", username);
-    let conn = get_conn().map_err(|e| e.to_string())?;
+    let conn = db.conn()?;
     
-    // 1. Проверяем OTP код
-    let otp_secret: String = conn.query_row(
-        "SELECT otp_secret FROM users WHERE username = ?1",
+    let (id, otp_secret): (i64, SecretString) = conn.query_row(
+        "SELECT id, otp_secret FROM users WHERE username = ?1",
         params![&username],
-        |row| row.get(0)
-    ).map_err(|_| "User or OTP secret not found".to_string())?;
-    
-    let secret_bytes = base32::decode(base32::Alphabet::RFC4648 { padding: false }, &otp_secret)
-        .ok_or("Invalid Base32 secret".to_string())?;
-    
-    let totp = TOTP::new(
-        totp_rs::Algorithm::SHA1,
-        6,
-        1,
-        30,
-        secret_bytes,
-    ).map_err(|e| format!("Failed to create TOTP: {}", e))?;
-    
-    let current_time = std::time::SystemTime::now()
-        .duration_since(std::time::SystemTime::UNIX_EPOCH)
-        .map_err(|e| format!("Failed to get current time: {}", e))?
-        .as_secs();
-    
-    if !totp.check(&otp_code, current_time) {
-        return Err("Invalid OTP code".to_string());
-    }
+        |row| Ok((row.get(0)?, row.get(1)?))
+    ).map_err(|_| XPassError::OtpNotConfigured)?;
+    
+    // 1. Проверяем OTP код (с учётом счётчика неудачных попыток)
+    throttle::guard(&conn, id, "OTP", || {
+        let secret_bytes = base32::decode(base32::Alphabet::RFC4648 { padding: false }, otp_secret.expose())
+            .ok_or(XPassError::CorruptedKeyMaterial { what: "OTP secret" })?;
+        
+        let totp = TOTP::new(
+            totp_rs::Algorithm::SHA1,
+            6,
+            1,
+            30,
+            secret_bytes,
+        ).map_err(XPassError::internal)?;
+        
+        let current_time = std::time::SystemTime::now()
+            .duration_since(std::time::SystemTime::UNIX_EPOCH)
+            .map_err(XPassError::internal)?
+            .as_secs();
+        
+        if totp.check(otp_code.expose(), current_time) {
+            Ok(())
+        } else {
+            Err(XPassError::InvalidOtp)
+        }
+    })?;
     
     // 2. Получаем зашифрованный recovery key
-    let (id, encrypted_key, nonce, otp_recovery_salt): (i64, Vec<u8>, Vec<u8>, String) = conn.query_row(
-        "SELECT id, otp_recovery_key, otp_recovery_nonce, otp_recovery_salt FROM users WHERE username = ?1",
-        params![&username],
-        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
-    ).map_err(|_| "OTP recovery not set up. Please contact support or use master password.".to_string())?;
+    let (encrypted_key, nonce, otp_recovery_salt): (Vec<u8>, Vec<u8>, String) = conn.query_row(
+        "SELECT otp_recovery_key, otp_recovery_nonce, otp_recovery_salt FROM users WHERE id = ?1",
+        params![id],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+    ).map_err(|_| XPassError::OtpRecoveryNotSetUp)?;
     
     // 3. Генерируем ключ из OTP secret для расшифровки
-    let argon2 = Argon2::default();
-    let mut otp_derived_key = [0u8; 32];
-    argon2
-        .hash_password_into(otp_secret.as_bytes(), otp_recovery_salt.as_bytes(), &mut otp_derived_key)
-        .map_err(|e| e.to_string())?;
+    let otp_derived_key = keys::derive_key(&otp_secret, &otp_recovery_salt, &KdfParams::LEGACY)?;
     
-    // 4. Расшифровываем ключ шифрования
-    let key = aes_gcm::Key::<Aes256Gcm>::from_slice(&otp_derived_key);
-    let cipher = Aes256Gcm::new(key);
-    let nonce_aead = Nonce::from_slice(&nonce);
+    // 4. Разворачиваем ключ данных
+    let key_material = keys::unwrap_key(&otp_derived_key, &encrypted_key, &nonce)
+        .map_err(|_| XPassError::CorruptedKeyMaterial { what: "OTP recovery key" })?;
+    keys::ensure_key_check(&conn, id, &key_material)?;
+    let session_id = sessions.open(id, key_material);
     
-    let decrypted_key = cipher.decrypt(nonce_aead, encrypted_key.as_ref())
-        .map_err(|_| "Failed to decrypt recovery key. OTP recovery may be corrupted.".to_string())?;
-    
-    let enc_key = general_purpose::STANDARD.encode(decrypted_key);
+    if let Err(e) = trash::purge_expired(&conn, id) {
+        log::warn!("Trash purge failed for user_id {}: {}", id, e);
+    }
     
     // 5. Логируем успешный вход
     crate::activity_logger::log_activity(
@@ -684,10 +928,11 @@ pub fn login_with_otp(username: String, otp_code: String) -> Result<(i64, String
         id,
         "login",
         &format!("Successful login via OTP for user: {}", username)
-    ).map_err(|e| e.to_string())?;
+    )?;
+    search::index_session(&conn, &sessions, &session_id, id);
     
     info!("OTP login successful for username: {}", username);
     
     // is_pseudo = false, так как это настоящий вход через OTP
-    Ok((id, enc_key, false))
+    Ok((id, session_id, false))
 }
\ No newline at end of file
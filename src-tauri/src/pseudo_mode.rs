@@ -12,15 +12,16 @@
 // ============================================================================
 
 // pseudo_mode.rs
-use rusqlite::{Connection, Result as RusqliteResult, params};
+use rusqlite::params;
 use argon2::{
-    password_hash::{
-        rand_core::OsRng,
-        PasswordHash, PasswordHasher, PasswordVerifier, SaltString
-    },
+    password_hash::{PasswordHash, PasswordVerifier, SaltString},
     Argon2
 };
 use serde::{Serialize, Deserialize};
+use tauri::State;
+use crate::database::Database;
+use crate::error::XPassError;
+use crate::secrets::SecretString;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct PseudoModeSettings {
@@ -40,59 +41,37 @@ pub struct PseudoPasswordInfo {
     pub length: Option<usize>,
 }
 
-/// Initializes the pseudo-mode settings table
-pub fn init_pseudo_mode_table(conn: &Connection) -> RusqliteResult<()> {
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS pseudo_mode_settings (
-            user_id INTEGER PRIMARY KEY,
-            enabled BOOLEAN DEFAULT 0,
-            hide_activity_logs BOOLEAN DEFAULT 0,
-            hide_failed_login_photos BOOLEAN DEFAULT 0,
-            hide_security_settings BOOLEAN DEFAULT 0,
-            show_fake_entries BOOLEAN DEFAULT 0,
-            hide_pseudo_mode_card BOOLEAN DEFAULT 0,
-            FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
-        )",
-        [],
-    )?;
-    
-    Ok(())
-}
-
 /// Adds a pseudo-password for the user with validation
 #[tauri::command]
-pub fn add_pseudo_password(user_id: i64, password: String) -> Result<(), String> {
-    use crate::password_manager::get_conn;
-    
-    let conn = get_conn().map_err(|e| e.to_string())?;
+pub fn add_pseudo_password(db: State<'_, Database>, user_id: i64, password: SecretString) -> Result<(), XPassError> {
+    let conn = db.conn()?;
     
     // IMPORTANT: Check that pseudo-password does not match the main password
-    let (salt_str, hash): (String, String) = conn.query_row(
-        "SELECT salt, hash FROM users WHERE id = ?1",
+    let (username, salt_str, hash): (String, String, String) = conn.query_row(
+        "SELECT username, salt, hash FROM users WHERE id = ?1",
         params![user_id],
-        |row| Ok((row.get(0)?, row.get(1)?))
-    ).map_err(|_| "User not found".to_string())?;
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+    ).map_err(|_| XPassError::UserNotFound)?;
+    
+    // A pseudo password has to hold up to guessing as well as the real one
+    crate::strength::require_master_strength(&password, &[&username])?;
     
-    let salt = SaltString::from_b64(&salt_str).map_err(|_| "Invalid salt")?;
-    let parsed_hash = PasswordHash::new(&hash).map_err(|_| "Invalid hash")?;
+    let salt = SaltString::from_b64(&salt_str).map_err(|_| XPassError::CorruptedKeyMaterial { what: "salt" })?;
+    let parsed_hash = PasswordHash::new(&hash).map_err(|_| XPassError::CorruptedKeyMaterial { what: "password hash" })?;
     let argon2 = Argon2::default();
     
     // If password matches the main one, return error
-    if argon2.verify_password(password.as_bytes(), &parsed_hash).is_ok() {
-        return Err("Cannot use main password as pseudo password!".to_string());
+    if argon2.verify_password(password.expose().as_bytes(), &parsed_hash).is_ok() {
+        return Err(XPassError::PseudoMatchesMaster);
     }
     
-    // Generate hash for pseudo-password
-    let pseudo_salt = SaltString::generate(&mut OsRng);
-    let pseudo_hash = argon2
-        .hash_password(password.as_bytes(), &pseudo_salt)
-        .map_err(|e| e.to_string())?
-        .to_string();
+    // Generate hash for pseudo-password (same cost as the real one)
+    let (pseudo_salt, pseudo_hash) = crate::keys::hash_password(&conn, &password)?;
     
     conn.execute(
         "INSERT INTO pseudo_passwords (user_id, salt, hash) VALUES (?1, ?2, ?3)",
         params![user_id, pseudo_salt.as_str(), pseudo_hash],
-    ).map_err(|e| e.to_string())?;
+    )?;
     
     // Log the addition of pseudo-password
     crate::activity_logger::log_activity(
@@ -107,31 +86,27 @@ pub fn add_pseudo_password(user_id: i64, password: String) -> Result<(), String>
 
 /// Deletes all pseudo-passwords for the user
 #[tauri::command]
-pub fn delete_all_pseudo_passwords(user_id: i64) -> Result<(), String> {
-    use crate::password_manager::get_conn;
-    
-    let conn = get_conn().map_err(|e| e.to_string())?;
+pub fn delete_all_pseudo_passwords(db: State<'_, Database>, user_id: i64) -> Result<(), XPassError> {
+    let conn = db.conn()?;
     
     conn.execute(
         "DELETE FROM pseudo_passwords WHERE user_id = ?1",
         params![user_id],
-    ).map_err(|e| e.to_string())?;
+    )?;
     
     Ok(())
 }
 
 /// Returns the count of pseudo-passwords for the user
 #[tauri::command]
-pub fn get_pseudo_passwords_count(user_id: i64) -> Result<i64, String> {
-    use crate::password_manager::get_conn;
-    
-    let conn = get_conn().map_err(|e| e.to_string())?;
+pub fn get_pseudo_passwords_count(db: State<'_, Database>, user_id: i64) -> Result<i64, XPassError> {
+    let conn = db.conn()?;
     
     let count: i64 = conn.query_row(
         "SELECT COUNT(*) FROM pseudo_passwords WHERE user_id = ?1",
         params![user_id],
         |row| row.get(0)
-    ).map_err(|e| e.to_string())?;
+    )?;
     
     Ok(count)
 }
@@ -139,6 +114,7 @@ pub fn get_pseudo_passwords_count(user_id: i64) -> Result<i64, String> {
 /// Saves pseudo-mode settings
 #[tauri::command]
 pub fn save_pseudo_mode_settings(
+    db: State<'_, Database>,
     user_id: i64,
     enabled: bool,
     hide_activity_logs: bool,
@@ -146,10 +122,8 @@ pub fn save_pseudo_mode_settings(
     hide_security_settings: bool,
     show_fake_entries: bool,
     hide_pseudo_mode_card: bool,
-) -> Result<(), String> {
-    use crate::password_manager::get_conn;
-    
-    let conn = get_conn().map_err(|e| e.to_string())?;
+) -> Result<(), XPassError> {
+    let conn = db.conn()?;
     
     conn.execute(
         "INSERT OR REPLACE INTO pseudo_mode_settings 
@@ -165,7 +139,7 @@ pub fn save_pseudo_mode_settings(
             show_fake_entries,
             hide_pseudo_mode_card
         ],
-    ).map_err(|e| e.to_string())?;
+    )?;
     
     // Log settings update
     crate::activity_logger::log_activity(
@@ -180,10 +154,8 @@ pub fn save_pseudo_mode_settings(
 
 /// Retrieves pseudo-mode settings
 #[tauri::command]
-pub fn get_pseudo_mode_settings(user_id: i64) -> Result<PseudoModeSettings, String> {
-    use crate::password_manager::get_conn;
-    
-    let conn = get_conn().map_err(|e| e.to_string())?;
+pub fn get_pseudo_mode_settings(db: State<'_, Database>, user_id: i64) -> Result<PseudoModeSettings, XPassError> {
+    let conn = db.conn()?;
     
     // Get settings
     let (enabled, hide_activity_logs, hide_failed_login_photos, 
@@ -210,14 +182,14 @@ pub fn get_pseudo_mode_settings(user_id: i64) -> Result<PseudoModeSettings, Stri
     // Get list of pseudo-passwords (only IDs)
     let mut stmt = conn.prepare(
         "SELECT id FROM pseudo_passwords WHERE user_id = ?1"
-    ).map_err(|e| e.to_string())?;
+    )?;
     
     let passwords: Vec<PseudoPasswordInfo> = stmt.query_map(params![user_id], |row| {
         Ok(PseudoPasswordInfo {
             id: row.get(0)?,
             length: None, // Do not send password length for security reasons
         })
-    }).map_err(|e| e.to_string())?
+    })?
     .filter_map(|r| r.ok())
     .collect();
     
@@ -234,10 +206,8 @@ pub fn get_pseudo_mode_settings(user_id: i64) -> Result<PseudoModeSettings, Stri
 
 /// Checks if the user is currently in pseudo-mode
 #[tauri::command]
-pub fn is_pseudo_mode_active(user_id: i64) -> Result<bool, String> {
-    use crate::password_manager::get_conn;
-    
-    let conn = get_conn().map_err(|e| e.to_string())?;
+pub fn is_pseudo_mode_active(db: State<'_, Database>, user_id: i64) -> Result<bool, XPassError> {
+    let conn = db.conn()?;
     
     let is_pseudo: bool = conn.query_row(
         "SELECT CASE 
@@ -258,18 +228,16 @@ pub fn is_pseudo_mode_active(user_id: i64) -> Result<bool, String> {
 
 /// Deletes a specific pseudo-password
 #[tauri::command]
-pub fn delete_pseudo_password(user_id: i64, pseudo_id: i64) -> Result<(), String> {
-    use crate::password_manager::get_conn;
-    
-    let conn = get_conn().map_err(|e| e.to_string())?;
+pub fn delete_pseudo_password(db: State<'_, Database>, user_id: i64, pseudo_id: i64) -> Result<(), XPassError> {
+    let conn = db.conn()?;
     
     let count = conn.execute(
         "DELETE FROM pseudo_passwords WHERE id = ?1 AND user_id = ?2",
         params![pseudo_id, user_id],
-    ).map_err(|e| e.to_string())?;
+    )?;
     
     if count == 0 {
-        return Err("Pseudo password not found".to_string());
+        return Err(XPassError::NotFound { what: "Pseudo password" });
     }
     
     Ok(())
@@ -0,0 +1,495 @@
+// ============================================================================
+// X-PASS Password Manager
+// Copyright (C) 2026 ar3love
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See LICENSE.
+// ============================================================================
+
+// src-tauri/src/search.rs
+//
+// In-memory search over the decrypted vault, so the UI no longer has to load
+// every entry to filter them. The index is built at login and kept in the
+// session, which wipes it together with the key on lock or logout. It holds
+// lowercased copies of service, login, note, website and URL field addresses,
+// tag names and the visible custom fields and kind details; passwords and
+// hidden values are never indexed, and hits are decrypted from the database
+// again.
+//
+// The index remembers SQLite's `total_changes()` at build time and is rebuilt
+// by the next search after anything has been written.
+//
+// Query syntax: every word must match. A word matches anywhere in the entry
+// unless qualified with `service:`, `login:`, `note:`, `url:`, `tag:` or
+// `field:`, and "quotes" keep a phrase together. Matches rank exact, prefix,
+// word start, substring, then fuzzy (one typo, two in long words), weighted
+// by the field they were found in.
+use aes_gcm::Aes256Gcm;
+use log::{info, warn};
+use rusqlite::Connection;
+use serde::Serialize;
+use tauri::State;
+
+use crate::database::Database;
+use crate::error::XPassError;
+use crate::fields::FieldType;
+use crate::keys;
+use crate::password_manager::{self, Entry};
+use crate::secrets::SecretString;
+use crate::session::SessionStore;
+use crate::tags::{self, Tag};
+use crate::urls::MatchMode;
+
+const DEFAULT_LIMIT: usize = 50;
+const MAX_LIMIT: usize = 500;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Field {
+    Service,
+    Login,
+    Note,
+    Url,
+    Tag,
+    Custom,
+}
+
+impl Field {
+    fn parse(qualifier: &str) -> Option<Field> {
+        Some(match qualifier {
+            "service" => Field::Service,
+            "login" => Field::Login,
+            "note" => Field::Note,
+            "url" => Field::Url,
+            "tag" => Field::Tag,
+            "field" => Field::Custom,
+            _ => return None,
+        })
+    }
+
+    fn weight(self) -> f32 {
+        match self {
+            Field::Service => 3.0,
+            Field::Tag | Field::Url => 2.0,
+            Field::Login => 1.5,
+            Field::Custom => 1.0,
+            Field::Note => 0.5,
+        }
+    }
+}
+
+struct Document {
+    entry_id: i64,
+    values: Vec<(Field, SecretString)>,
+}
+
+impl Document {
+    fn from_entry(entry: &Entry, tags: &[Tag]) -> Document {
+        let mut values = Vec::new();
+        let mut push = |field: Field, value: &str| {
+            if !value.trim().is_empty() {
+                values.push((field, SecretString::new(value.to_lowercase())));
+            }
+        };
+
+        push(Field::Service, &entry.service);
+        push(Field::Login, &entry.login);
+        push(Field::Note, entry.note.expose());
+        // Regex rules are patterns, not addresses anyone would type
+        for url in entry.urls.iter().filter(|url| url.match_mode != MatchMode::Regex) {
+            push(Field::Url, url.url.expose());
+        }
+        for tag in tags.iter().filter(|tag| entry.tag_ids.contains(&tag.id)) {
+            push(Field::Tag, tag.name.expose());
+        }
+        for field in &entry.fields {
+            push(Field::Custom, field.name.expose());
+            match field.field_type {
+                FieldType::Url => push(Field::Url, field.value.expose()),
+                FieldType::Hidden | FieldType::Totp => {}
+                _ => push(Field::Custom, field.value.expose()),
+            }
+        }
+        for detail in entry.kind.schema().iter().filter(|detail| !detail.hidden) {
+            if let Some(value) = entry.details.get(detail.key) {
+                push(Field::Custom, value.expose());
+            }
+        }
+
+        Document { entry_id: entry.id, values }
+    }
+}
+
+pub struct SearchIndex {
+    documents: Vec<Document>,
+    /// Entries a pseudo session sees; they are not in the database, so hits
+    /// are returned from here
+    fake_entries: Option<Vec<Entry>>,
+    /// `total_changes()` of the connection when the index was built
+    changes: i64,
+}
+
+fn total_changes(conn: &Connection) -> Result<i64, XPassError> {
+    Ok(conn.query_row("SELECT total_changes()", [], |row| row.get(0))?)
+}
+
+impl SearchIndex {
+    /// Indexes the live entries of the user. A pseudo session gets an index
+    /// of the same fake entries `get_entries` shows it.
+    pub fn build(conn: &Connection, user_id: i64, cipher: &Aes256Gcm) -> Result<SearchIndex, XPassError> {
+        let changes = total_changes(conn)?;
+
+        if !keys::is_vault_key(conn, user_id, cipher)? {
+            let entries = password_manager::pseudo_entries(conn, user_id);
+            let documents = entries.iter().map(|entry| Document::from_entry(entry, &[])).collect();
+            return Ok(SearchIndex { documents, fake_entries: Some(entries), changes });
+        }
+
+        let tags = tags::load_tags(conn, user_id, cipher)?;
+        let documents = password_manager::load_entries(conn, user_id, cipher)?
+            .iter()
+            .map(|entry| Document::from_entry(entry, &tags))
+            .collect();
+        Ok(SearchIndex { documents, fake_entries: None, changes })
+    }
+
+    fn is_stale(&self, conn: &Connection) -> Result<bool, XPassError> {
+        Ok(self.fake_entries.is_none() && total_changes(conn)? != self.changes)
+    }
+
+    /// Positions of the matching documents with their score, best first
+    fn search(&self, terms: &[Term]) -> Vec<(usize, f32)> {
+        let mut hits: Vec<(usize, f32)> = self.documents.iter()
+            .enumerate()
+            .filter_map(|(position, document)| {
+                let mut total = 0.0;
+                for term in terms {
+                    let best = document.values.iter()
+                        .filter(|(field, _)| term.field.is_none_or(|f| f == *field))
+                        .map(|(field, value)| field.weight() * match_score(&term.text, value.expose()))
+                        .fold(0.0, f32::max);
+                    if best == 0.0 {
+                        return None;
+                    }
+                    total += best;
+                }
+                Some((position, total))
+            })
+            .collect();
+
+        hits.sort_by(|a, b| b.1.total_cmp(&a.1));
+        hits
+    }
+}
+
+struct Term {
+    field: Option<Field>,
+    text: String,
+}
+
+/// Splits a query into terms, keeping "quoted phrases" together
+fn parse_query(query: &str) -> Vec<Term> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    for c in query.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+
+    tokens.into_iter()
+        .filter_map(|token| {
+            let token = token.to_lowercase();
+            let term = match token.split_once(':') {
+                Some((qualifier, text)) if !text.is_empty() => match Field::parse(qualifier) {
+                    Some(field) => Term { field: Some(field), text: text.to_string() },
+                    // Not a qualifier, e.g. a URL like https://...
+                    None => Term { field: None, text: token.clone() },
+                },
+                _ => Term { field: None, text: token.clone() },
+            };
+            (!term.text.is_empty()).then_some(term)
+        })
+        .collect()
+}
+
+/// Edit distance between two words, None once it exceeds `max`
+fn levenshtein(a: &[char], b: &[char], max: usize) -> Option<usize> {
+    if a.len().abs_diff(b.len()) > max {
+        return None;
+    }
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        if current.iter().min().is_some_and(|&m| m > max) {
+            return None;
+        }
+        previous = current;
+    }
+    previous.last().copied().filter(|&d| d <= max)
+}
+
+/// How well a lowercased term matches a lowercased value, 0 for no match
+fn match_score(term: &str, value: &str) -> f32 {
+    if value == term {
+        return 1.0;
+    }
+    if value.starts_with(term) {
+        return 0.8;
+    }
+
+    let mut words = value.split(|c: char| !c.is_alphanumeric()).filter(|w| !w.is_empty());
+    if value.contains(term) {
+        return if words.any(|w| w.starts_with(term)) { 0.6 } else { 0.4 };
+    }
+
+    let term: Vec<char> = term.chars().collect();
+    let max_typos = match term.len() {
+        0..=3 => return 0.0,
+        4..=7 => 1,
+        _ => 2,
+    };
+    words
+        .filter_map(|word| levenshtein(&term, &word.chars().collect::<Vec<_>>(), max_typos))
+        .min()
+        .map_or(0.0, |typos| 0.3 - 0.1 * (typos - 1) as f32)
+}
+
+/// Builds the search index of a freshly opened session. A failure only
+/// costs the first search a rebuild, so it does not fail the login.
+pub fn index_session(conn: &Connection, sessions: &SessionStore, session_id: &str, user_id: i64) {
+    let built = sessions.cipher(session_id, user_id)
+        .and_then(|cipher| SearchIndex::build(conn, user_id, &cipher))
+        .and_then(|index| sessions.with_index(session_id, user_id, |slot| *slot = Some(index)));
+    if let Err(e) = built {
+        warn!("Search index not built for user_id {}: {}", user_id, e);
+    }
+}
+
+#[derive(Serialize)]
+pub struct SearchResult {
+    #[serde(flatten)]
+    pub entry: Entry,
+    pub score: f32,
+}
+
+/// Searches the vault, best matches first. See the top of this file for
+/// the query syntax.
+#[tauri::command]
+pub fn search_entries(
+    db: State<'_, Database>,
+    sessions: State<'_, SessionStore>,
+    user_id: i64,
+    session_id: String,
+    query: String,
+    limit: Option<usize>
+) -> Result<Vec<SearchResult>, XPassError> {
+    let cipher = sessions.cipher(&session_id, user_id)?;
+    let terms = parse_query(&query);
+    if terms.is_empty() {
+        return Ok(vec![]);
+    }
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+
+    let conn = db.conn()?;
+    sessions.with_index(&session_id, user_id, |slot| {
+        let stale = match slot.as_ref() {
+            Some(index) => index.is_stale(&conn)?,
+            None => true,
+        };
+        if stale {
+            *slot = Some(SearchIndex::build(&conn, user_id, &cipher)?);
+            info!("Search index rebuilt for user_id {}", user_id);
+        }
+        let Some(index) = slot.as_ref() else {
+            return Ok(vec![]);
+        };
+
+        index.search(&terms)
+            .into_iter()
+            .take(limit)
+            .map(|(position, score)| {
+                let entry = match &index.fake_entries {
+                    Some(entries) => entries[position].clone(),
+                    None => password_manager::load_entry(&conn, user_id, index.documents[position].entry_id, &cipher)?,
+                };
+                Ok(SearchResult { entry, score })
+            })
+            .collect()
+    })?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fields::CustomField;
+    use crate::kinds::EntryKind;
+    use crate::urls::EntryUrl;
+
+    fn entry(id: i64, service: &str, login: &str) -> Entry {
+        Entry {
+            id,
+            service: service.to_string(),
+            login: login.to_string(),
+            password: SecretString::new("correct horse battery".to_string()),
+            note: SecretString::new(String::new()),
+            kind: EntryKind::Login,
+            details: Default::default(),
+            fields: vec![],
+            urls: vec![],
+            folder_id: None,
+            tag_ids: vec![],
+            strength: None,
+        }
+    }
+
+    fn url(address: &str) -> EntryUrl {
+        EntryUrl { url: SecretString::new(address.to_string()), match_mode: MatchMode::BaseDomain }
+    }
+
+    fn field(name: &str, field_type: FieldType, value: &str) -> CustomField {
+        CustomField {
+            name: SecretString::new(name.to_string()),
+            field_type,
+            value: SecretString::new(value.to_string()),
+        }
+    }
+
+    fn index(entries: &[Entry], tags: &[Tag]) -> SearchIndex {
+        let documents = entries.iter().map(|entry| Document::from_entry(entry, tags)).collect();
+        SearchIndex { documents, fake_entries: None, changes: 0 }
+    }
+
+    /// Ids of the entries a query finds, best first
+    fn find(index: &SearchIndex, query: &str) -> Vec<i64> {
+        index.search(&parse_query(query))
+            .into_iter()
+            .map(|(position, _)| index.documents[position].entry_id)
+            .collect()
+    }
+
+    #[test]
+    fn qualifiers_restrict_the_field() {
+        let tags = [
+            Tag { id: 1, name: SecretString::new("Work".to_string()) },
+            Tag { id: 2, name: SecretString::new("Home".to_string()) },
+        ];
+        let mut work_github = entry(1, "GitHub", "me@work.example");
+        work_github.urls = vec![url("https://github.com")];
+        work_github.tag_ids = vec![1];
+        let mut work_gitlab = entry(2, "GitLab", "me@work.example");
+        work_gitlab.urls = vec![url("https://gitlab.com")];
+        work_gitlab.tag_ids = vec![1];
+        let mut home_github = entry(3, "GitHub", "me@home.example");
+        home_github.urls = vec![url("https://github.com")];
+        home_github.tag_ids = vec![2];
+        let mut github_note = entry(4, "Forum", "me");
+        github_note.note = SecretString::new("work account, same as github".to_string());
+        let index = index(&[work_github, work_gitlab, home_github, github_note], &tags);
+
+        assert_eq!(find(&index, "tag:work url:github"), vec![1]);
+        assert_eq!(find(&index, "tag:work"), vec![1, 2]);
+        assert_eq!(find(&index, "service:forum"), vec![4]);
+        assert_eq!(find(&index, "login:home"), vec![3]);
+        assert_eq!(find(&index, "note:github"), vec![4]);
+        // A URL is not read as a qualifier
+        assert_eq!(find(&index, "https://gitlab.com"), vec![2]);
+    }
+
+    #[test]
+    fn service_matches_rank_first() {
+        let mut in_note = entry(1, "Forum", "me");
+        in_note.note = SecretString::new("github".to_string());
+        let exact = entry(2, "GitHub", "me");
+        let prefix = entry(3, "GitHub Enterprise", "me");
+        let index = index(&[in_note, exact, prefix], &[]);
+
+        assert_eq!(find(&index, "github"), vec![2, 3, 1]);
+    }
+
+    #[test]
+    fn quoted_phrases_stay_together() {
+        let mut phrase = entry(1, "Router", "admin");
+        phrase.note = SecretString::new("Shared account for the team".to_string());
+        let mut scattered = entry(2, "Printer", "admin");
+        scattered.note = SecretString::new("Account shared with the team".to_string());
+        let index = index(&[phrase, scattered], &[]);
+
+        assert_eq!(find(&index, "\"shared account\""), vec![1]);
+        assert_eq!(find(&index, "shared account").len(), 2);
+        assert_eq!(find(&index, "note:\"account for\""), vec![1]);
+    }
+
+    #[test]
+    fn fuzzy_matches_allow_few_typos() {
+        let index = index(&[entry(1, "GitHub", "me"), entry(2, "Bitwarden", "me")], &[]);
+
+        // One typo in a short word, two in a long one
+        assert_eq!(find(&index, "githib"), vec![1]);
+        assert!(find(&index, "gothib").is_empty());
+        assert_eq!(find(&index, "bitwerdan"), vec![2]);
+        assert!(find(&index, "bytwerdan").is_empty());
+        // Short words have to match exactly
+        assert!(find(&index, "gut").is_empty());
+    }
+
+    #[test]
+    fn secrets_are_never_matched() {
+        let mut card = entry(1, "Visa", "");
+        card.password = SecretString::new("hunter2hunter2".to_string());
+        card.fields = vec![
+            field("Recovery code", FieldType::Hidden, "quokkabackup"),
+            field("Authenticator", FieldType::Totp, "JBSWY3DPEHPK3PXP"),
+            field("Branch", FieldType::Text, "Downtown"),
+        ];
+        card.kind = EntryKind::CreditCard;
+        card.details = [
+            ("cardholder_name", "Jane Roe"),
+            ("card_number", "4111111111111111"),
+            ("cvv", "737"),
+            ("pin", "4921"),
+        ]
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), SecretString::new(value.to_string())))
+            .collect();
+        let index = index(&[card], &[]);
+
+        for secret in ["hunter2hunter2", "hunter2", "quokkabackup", "jbswy3dpehpk3pxp", "4111111111111111", "737", "4921"] {
+            assert!(find(&index, secret).is_empty(), "{} was matched", secret);
+        }
+        // Names of hidden fields and visible values still are
+        assert_eq!(find(&index, "recovery"), vec![1]);
+        assert_eq!(find(&index, "downtown"), vec![1]);
+        assert_eq!(find(&index, "jane"), vec![1]);
+    }
+
+    #[test]
+    fn regex_rules_are_not_indexed() {
+        let mut rule = entry(1, "Intranet", "me");
+        rule.urls = vec![EntryUrl {
+            url: SecretString::new(r"^https://(www\.)?corp\.example/.*$".to_string()),
+            match_mode: MatchMode::Regex,
+        }];
+        let index = index(&[rule], &[]);
+
+        assert!(find(&index, "corp").is_empty());
+    }
+}
@@ -0,0 +1,237 @@
+// ============================================================================
+// X-PASS Password Manager
+// Copyright (C) 2026 ar3love
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See LICENSE.
+// ============================================================================
+
+// src-tauri/src/secrets.rs
+//
+// Containers for anything that must not outlive its use: passwords, decrypted
+// entry fields, plaintext backup payloads and key material. All of them are
+// wiped when dropped and print as `[REDACTED]`, so a stray `{:?}` in a log
+// line cannot leak them.
+//
+// Keys additionally live in their own guarded allocation that is locked in
+// RAM (mlock / VirtualLock) so they are not written to swap. Locking is best
+// effort: if the OS refuses, the key still works and is still wiped.
+//
+// Secrets arriving from or returned to the webview necessarily pass through
+// serde's buffers, which we cannot wipe; the goal is that nothing we own
+// keeps a copy longer than needed.
+use std::fmt;
+use std::ptr::NonNull;
+
+use aes_gcm::{aead::KeyInit, Aes256Gcm};
+use rand::RngCore;
+use rusqlite::types::{FromSql, FromSqlResult, ValueRef};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use zeroize::Zeroize;
+
+pub const KEY_LEN: usize = 32;
+
+const REDACTED: &str = "[REDACTED]";
+
+/// A UTF-8 secret, such as a password or a decrypted note
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct SecretString(String);
+
+impl SecretString {
+    pub fn new(value: String) -> Self {
+        SecretString(value)
+    }
+
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Takes over decrypted bytes without copying them; the bytes are wiped
+    /// if they are not valid UTF-8
+    pub fn from_utf8(mut bytes: SecretBytes) -> Option<Self> {
+        match String::from_utf8(std::mem::take(&mut bytes.0)) {
+            Ok(value) => Some(SecretString(value)),
+            Err(e) => {
+                e.into_bytes().zeroize();
+                None
+            }
+        }
+    }
+}
+
+impl From<String> for SecretString {
+    fn from(value: String) -> Self {
+        SecretString(value)
+    }
+}
+
+impl From<&str> for SecretString {
+    fn from(value: &str) -> Self {
+        SecretString(value.to_string())
+    }
+}
+
+impl Drop for SecretString {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl fmt::Debug for SecretString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl fmt::Display for SecretString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+/// Sent to the webview as a plain string: entries have to be shown
+impl Serialize for SecretString {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for SecretString {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(SecretString)
+    }
+}
+
+/// Lets secrets stored in plain columns (OTP secrets) be read straight into
+/// a `SecretString`
+impl FromSql for SecretString {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        String::column_result(value).map(SecretString)
+    }
+}
+
+/// Secret binary data, such as a plaintext backup payload
+#[derive(Default)]
+pub struct SecretBytes(Vec<u8>);
+
+impl SecretBytes {
+    pub fn expose(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl From<Vec<u8>> for SecretBytes {
+    fn from(value: Vec<u8>) -> Self {
+        SecretBytes(value)
+    }
+}
+
+impl Drop for SecretBytes {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl fmt::Debug for SecretBytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+enum KeyBuf {
+    /// Guarded pages locked in RAM
+    Locked(NonNull<[u8; KEY_LEN]>),
+    /// Used if the guarded allocation itself failed
+    Heap(Box<[u8; KEY_LEN]>),
+}
+
+/// A 256-bit key kept out of swap and wiped on drop
+pub struct SecretKey(KeyBuf);
+
+// SAFETY: the locked buffer is owned exclusively by this value, exactly like
+// the boxed fallback; nothing else holds the pointer.
+unsafe impl Send for SecretKey {}
+unsafe impl Sync for SecretKey {}
+
+impl SecretKey {
+    /// A zeroed key, to be filled by a KDF or RNG
+    pub fn zeroed() -> Self {
+        // SAFETY: memsec::malloc returns a fresh, correctly sized and aligned
+        // allocation for one `[u8; KEY_LEN]`, which is initialized right away.
+        let locked = unsafe { memsec::malloc::<[u8; KEY_LEN]>() };
+        match locked {
+            Some(ptr) => {
+                unsafe { ptr.as_ptr().write([0u8; KEY_LEN]) };
+                SecretKey(KeyBuf::Locked(ptr))
+            }
+            None => {
+                log::warn!("Could not allocate locked memory for a key, using the heap");
+                SecretKey(KeyBuf::Heap(Box::new([0u8; KEY_LEN])))
+            }
+        }
+    }
+
+    /// A fresh random key
+    pub fn random() -> Self {
+        let mut key = Self::zeroed();
+        rand::thread_rng().fill_bytes(key.expose_mut());
+        key
+    }
+
+    /// Copies a key out of a buffer; None if it is not `KEY_LEN` bytes
+    pub fn from_slice(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != KEY_LEN {
+            return None;
+        }
+        let mut key = Self::zeroed();
+        key.expose_mut().copy_from_slice(bytes);
+        Some(key)
+    }
+
+    pub fn expose(&self) -> &[u8; KEY_LEN] {
+        match &self.0 {
+            // SAFETY: the pointer is valid and initialized until drop
+            KeyBuf::Locked(ptr) => unsafe { ptr.as_ref() },
+            KeyBuf::Heap(key) => key,
+        }
+    }
+
+    pub fn expose_mut(&mut self) -> &mut [u8; KEY_LEN] {
+        match &mut self.0 {
+            // SAFETY: as above, and `&mut self` guarantees exclusive access
+            KeyBuf::Locked(ptr) => unsafe { ptr.as_mut() },
+            KeyBuf::Heap(key) => key,
+        }
+    }
+
+    /// AES-256-GCM cipher keyed with this key
+    pub fn cipher(&self) -> Aes256Gcm {
+        Aes256Gcm::new(aes_gcm::Key::<Aes256Gcm>::from_slice(self.expose()))
+    }
+}
+
+impl Drop for SecretKey {
+    fn drop(&mut self) {
+        self.expose_mut().zeroize();
+        if let KeyBuf::Locked(ptr) = self.0 {
+            // SAFETY: allocated by memsec::malloc and freed exactly once;
+            // free unlocks and wipes the pages again before releasing them
+            unsafe { memsec::free(ptr) };
+        }
+    }
+}
+
+impl fmt::Debug for SecretKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
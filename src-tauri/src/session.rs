@@ -0,0 +1,170 @@
+// ============================================================================
+// X-PASS Password Manager
+// Copyright (C) 2026 ar3love
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See LICENSE.
+// ============================================================================
+
+// src-tauri/src/session.rs
+//
+// Unlock sessions. The vault key never leaves the backend: login stores it
+// here and hands the webview an opaque handle, which every vault command
+// resolves back into a cipher. The session also owns its search index
+// (search.rs) and its compiled URL rules (urls.rs), so locking discards them
+// together with the key.
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use aes_gcm::Aes256Gcm;
+use base64::{engine::general_purpose, Engine as _};
+use log::info;
+use rand::RngCore;
+use tauri::State;
+
+use crate::database::Database;
+use crate::error::XPassError;
+use crate::search::SearchIndex;
+use crate::secrets::SecretKey;
+use crate::urls::PatternCache;
+
+/// A single unlocked vault session. The key is wiped when the session is
+/// dropped (see secrets.rs).
+pub struct Session {
+    user_id: i64,
+    key: SecretKey,
+    index: Option<SearchIndex>,
+    patterns: PatternCache,
+}
+
+/// All sessions opened since the last lock, keyed by their opaque handle
+#[derive(Default)]
+pub struct SessionStore {
+    sessions: Mutex<HashMap<String, Session>>,
+}
+
+impl SessionStore {
+    /// Stores the vault key for a freshly authenticated user and returns the session handle
+    pub fn open(&self, user_id: i64, key: SecretKey) -> String {
+        let mut handle_bytes = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut handle_bytes);
+        let handle = general_purpose::URL_SAFE_NO_PAD.encode(handle_bytes);
+
+        let session = Session { user_id, key, index: None, patterns: PatternCache::default() };
+
+        self.sessions.lock().unwrap().insert(handle.clone(), session);
+        handle
+    }
+
+    /// Resolves a session handle into a cipher for the given user
+    pub fn cipher(&self, session_id: &str, user_id: i64) -> Result<Aes256Gcm, XPassError> {
+        let sessions = self.sessions.lock().unwrap();
+        let session = Self::owned(&sessions, session_id, user_id)?;
+
+        Ok(session.key.cipher())
+    }
+
+    /// Checks that the handle is a live session of the given user
+    pub fn verify(&self, session_id: &str, user_id: i64) -> Result<(), XPassError> {
+        let sessions = self.sessions.lock().unwrap();
+        Self::owned(&sessions, session_id, user_id).map(|_| ())
+    }
+
+    /// Runs `f` on the search index slot of a live session of the given user
+    pub fn with_index<R>(
+        &self,
+        session_id: &str,
+        user_id: i64,
+        f: impl FnOnce(&mut Option<SearchIndex>) -> R
+    ) -> Result<R, XPassError> {
+        let mut sessions = self.sessions.lock().unwrap();
+        let session = sessions
+            .get_mut(session_id)
+            .ok_or(XPassError::SessionExpired)?;
+
+        if session.user_id != user_id {
+            return Err(XPassError::SessionMismatch);
+        }
+        Ok(f(&mut session.index))
+    }
+
+    /// Runs `f` on the compiled URL rules of a live session of the given user
+    pub fn with_patterns<R>(
+        &self,
+        session_id: &str,
+        user_id: i64,
+        f: impl FnOnce(&mut PatternCache) -> R
+    ) -> Result<R, XPassError> {
+        let mut sessions = self.sessions.lock().unwrap();
+        let session = sessions
+            .get_mut(session_id)
+            .ok_or(XPassError::SessionExpired)?;
+
+        if session.user_id != user_id {
+            return Err(XPassError::SessionMismatch);
+        }
+        Ok(f(&mut session.patterns))
+    }
+
+    fn owned<'a>(sessions: &'a HashMap<String, Session>, session_id: &str, user_id: i64) -> Result<&'a Session, XPassError> {
+        let session = sessions
+            .get(session_id)
+            .ok_or(XPassError::SessionExpired)?;
+
+        if session.user_id != user_id {
+            return Err(XPassError::SessionMismatch);
+        }
+        Ok(session)
+    }
+
+    /// Closes a single session, returns false if it was already gone
+    pub fn close(&self, session_id: &str) -> bool {
+        self.sessions.lock().unwrap().remove(session_id).is_some()
+    }
+
+    /// Closes every session that belongs to the given user
+    pub fn close_user(&self, user_id: i64) {
+        self.sessions.lock().unwrap().retain(|_, session| session.user_id != user_id);
+    }
+
+    /// Invalidates every open session
+    pub fn lock_all(&self) -> usize {
+        let mut sessions = self.sessions.lock().unwrap();
+        let count = sessions.len();
+        sessions.clear();
+        count
+    }
+}
+
+/// Locks the vault: every open session is invalidated and an encrypted
+/// database is sealed again
+#[tauri::command]
+pub fn lock_vault(db: State<'_, Database>, sessions: State<'_, SessionStore>) -> Result<(), XPassError> {
+    let closed = sessions.lock_all();
+    db.seal();
+    info!("Vault locked, {} session(s) invalidated", closed);
+    Ok(())
+}
+
+/// Ends the given session and logs the logout
+#[tauri::command]
+pub fn logout(db: State<'_, Database>, sessions: State<'_, SessionStore>, session_id: String, user_id: i64) -> Result<(), XPassError> {
+    // Check ownership before closing so one user cannot log another out
+    sessions.verify(&session_id, user_id)?;
+    sessions.close(&session_id);
+
+    let conn = db.conn()?;
+    crate::activity_logger::log_activity(
+        &conn,
+        user_id,
+        "logout",
+        "User logged out"
+    ).ok();
+
+    Ok(())
+}
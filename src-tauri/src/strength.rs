@@ -0,0 +1,884 @@
+// ============================================================================
+// X-PASS Password Manager
+// Copyright (C) 2026 ar3love
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See LICENSE.
+// ============================================================================
+
+// src-tauri/src/strength.rs
+//
+// Password strength estimation after zxcvbn (Wheeler, USENIX Security 2016).
+// The password is matched against common passwords, the bundled wordlists and
+// user inputs such as the username (also reversed and with l33t
+// substitutions), keyboard walks, repeats, sequences, years and dates. The
+// cheapest way to cover the whole password with those matches and brute
+// force gives the number of guesses, scored 0-4 on zxcvbn's thresholds.
+//
+// Warnings and suggestions are codes the UI translates
+// (`strength.warning.<code>`, `strength.suggestion.<code>`). Only the first
+// 100 characters are analysed, every character after them counts as brute
+// force. Intermediate copies of the password are wiped.
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{Datelike, Utc};
+use serde::Serialize;
+use zeroize::Zeroizing;
+
+use crate::error::XPassError;
+use crate::passphrase;
+use crate::secrets::SecretString;
+
+/// Master and pseudo passwords must reach this score
+pub const MIN_MASTER_SCORE: u8 = 3;
+
+const MAX_ANALYZED_CHARS: usize = 100;
+const MIN_WORD_CHARS: usize = 3;
+const MAX_WORD_CHARS: usize = 32;
+/// Guesses per brute-forced character, as log10
+const BRUTEFORCE_LOG10: f64 = 1.0;
+/// Penalty for every extra match in a sequence, as log10 (10 000 guesses)
+const MIN_GUESSES_BEFORE_GROWING_SEQUENCE_LOG10: f64 = 4.0;
+const MIN_SUBMATCH_GUESSES_SINGLE_CHAR: f64 = 10.0;
+const MIN_SUBMATCH_GUESSES_MULTI_CHAR: f64 = 50.0;
+const MIN_YEAR_SPACE: i32 = 20;
+const DATE_MIN_YEAR: i32 = 1000;
+const DATE_MAX_YEAR: i32 = 2050;
+const DATE_SEPARATORS: &str = " /\\_.-";
+/// Ways to split 4 to 8 digits into day, month and year
+const DATE_SPLITS: [&[(usize, usize)]; 5] = [
+    &[(1, 2), (2, 3)],
+    &[(1, 3), (2, 3)],
+    &[(1, 2), (2, 4), (4, 5)],
+    &[(1, 3), (2, 3), (4, 5), (4, 6)],
+    &[(2, 4), (4, 6)],
+];
+/// Letters and the characters commonly written for them
+const L33T_TABLE: [(char, &str); 12] = [
+    ('a', "4@"),
+    ('b', "8"),
+    ('c', "({[<"),
+    ('e', "3"),
+    ('g', "69"),
+    ('i', "1!|"),
+    ('l', "1|7"),
+    ('o', "0"),
+    ('s', "$5"),
+    ('t', "+7"),
+    ('x', "%"),
+    ('z', "2"),
+];
+/// Unshifted and shifted rows of a US keyboard with their horizontal offset
+const QWERTY: [(&str, &str, f32); 4] = [
+    ("`1234567890-=", "~!@#$%^&*()_+", 0.0),
+    ("qwertyuiop[]\\", "QWERTYUIOP{}|", 1.5),
+    ("asdfghjkl;'", "ASDFGHJKL:\"", 1.75),
+    ("zxcvbnm,./", "ZXCVBNM<>?", 2.25),
+];
+
+#[derive(Serialize, Debug, Clone)]
+pub struct CrackTimes {
+    /// Online attack limited to 100 guesses per hour
+    pub online_throttling: f64,
+    /// Online attack at 10 guesses per second
+    pub online_no_throttling: f64,
+    /// Offline attack on a slow hash such as Argon2, 10⁴ guesses per second
+    pub offline_slow_hashing: f64,
+    /// Offline attack on a fast hash, 10¹⁰ guesses per second
+    pub offline_fast_hashing: f64,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct Feedback {
+    pub warning: Option<&'static str>,
+    pub suggestions: Vec<&'static str>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct Strength {
+    /// 0 (too guessable) to 4 (very unguessable)
+    pub score: u8,
+    pub guesses_log10: f64,
+    /// Seconds to crack under each attack scenario
+    pub crack_times_seconds: CrackTimes,
+    pub feedback: Feedback,
+}
+
+/// What entries carry about their password
+#[derive(Serialize, Debug, Clone)]
+pub struct StrengthSummary {
+    pub score: u8,
+    pub guesses_log10: f64,
+    pub warning: Option<&'static str>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Dictionary {
+    Passwords,
+    Words,
+    UserInputs,
+}
+
+#[derive(Clone, Debug)]
+enum Pattern {
+    Dictionary { dictionary: Dictionary, rank: usize, reversed: bool, l33t: bool },
+    Spatial { turns: usize },
+    Repeat { base_len: usize },
+    Sequence,
+    Year,
+    Date,
+    Bruteforce,
+}
+
+/// A match over the characters `i..=j`
+#[derive(Clone, Debug)]
+struct Match {
+    i: usize,
+    j: usize,
+    pattern: Pattern,
+    guesses_log10: f64,
+}
+
+struct Key {
+    row: usize,
+    x: f32,
+    shifted: bool,
+}
+
+struct Keyboard {
+    keys: HashMap<char, Key>,
+    starting_positions: f64,
+    average_degree: f64,
+}
+
+impl Keyboard {
+    fn qwerty() -> Keyboard {
+        let mut keys = HashMap::new();
+        for (row, (plain, shifted, offset)) in QWERTY.iter().enumerate() {
+            for (column, (p, s)) in plain.chars().zip(shifted.chars()).enumerate() {
+                let x = offset + column as f32;
+                keys.insert(p, Key { row, x, shifted: false });
+                keys.insert(s, Key { row, x, shifted: true });
+            }
+        }
+        let degrees: usize = keys.values()
+            .map(|a| keys.values().filter(|b| !b.shifted && Keyboard::adjacent(a, b)).count())
+            .sum();
+        Keyboard {
+            starting_positions: keys.len() as f64,
+            average_degree: degrees as f64 / keys.len() as f64,
+            keys,
+        }
+    }
+
+    fn adjacent(a: &Key, b: &Key) -> bool {
+        let dx = (a.x - b.x).abs();
+        match a.row.abs_diff(b.row) {
+            0 => dx == 1.0,
+            1 => dx < 1.0,
+            _ => false,
+        }
+    }
+
+    /// Row step and horizontal direction from one key to the next
+    fn direction(a: &Key, b: &Key) -> (i8, i8) {
+        (b.row as i8 - a.row as i8, (b.x - a.x).signum() as i8)
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref PASSWORDS: HashMap<&'static str, usize> =
+        passphrase::parse_wordlist(include_str!("../assets/common_passwords.txt"))
+            .into_iter()
+            .enumerate()
+            .map(|(position, word)| (word, position + 1))
+            .collect();
+    /// Every bundled wordlist. They are not ranked by frequency, so each
+    /// word counts as one guess out of its list.
+    static ref WORDS: HashMap<&'static str, usize> = passphrase::WORDLISTS.iter()
+        .flat_map(|(_, list)| {
+            let words = passphrase::parse_wordlist(list);
+            let size = words.len();
+            words.into_iter().map(move |word| (word, size))
+        })
+        .collect();
+    static ref KEYBOARD: Keyboard = Keyboard::qwerty();
+}
+
+fn log10_binomial(n: usize, k: usize) -> f64 {
+    (0..k.min(n)).map(|i| ((n - i) as f64 / (i + 1) as f64).log10()).sum()
+}
+
+/// log10 of the sum of the binomials C(n, 1) to C(n, k)
+fn log10_binomial_sum(n: usize, k: usize) -> f64 {
+    (1..=k).map(|i| log10_binomial(n, i)).fold(f64::NEG_INFINITY, log10_add)
+}
+
+/// log10(10^a + 10^b)
+fn log10_add(a: f64, b: f64) -> f64 {
+    if a == f64::NEG_INFINITY {
+        return b;
+    }
+    let high = a.max(b);
+    high + (10f64.powf(a - high) + 10f64.powf(b - high)).log10()
+}
+
+fn log10_factorial(n: usize) -> f64 {
+    (2..=n).map(|k| (k as f64).log10()).sum()
+}
+
+/// Capitalization an attacker tries on a dictionary word
+fn uppercase_variations(token: &[char]) -> f64 {
+    let upper = token.iter().filter(|c| c.is_uppercase()).count();
+    let lower = token.iter().filter(|c| c.is_lowercase()).count();
+    if upper == 0 {
+        return 0.0;
+    }
+    let first_only = token[0].is_uppercase() && upper == 1;
+    let last_only = token[token.len() - 1].is_uppercase() && upper == 1;
+    if first_only || last_only || lower == 0 {
+        return 2f64.log10();
+    }
+    log10_binomial_sum(upper + lower, upper.min(lower))
+}
+
+/// Dictionary words in a lowercased password as (i, j, dictionary, rank)
+fn find_words(lower: &[char], user_inputs: &HashMap<String, usize>) -> Vec<(usize, usize, Dictionary, usize)> {
+    let mut found = Vec::new();
+    // Never grows past its capacity, so no copy is left behind
+    let mut token = Zeroizing::new(String::with_capacity(MAX_WORD_CHARS * 4));
+    for i in 0..lower.len() {
+        token.clear();
+        for (j, c) in lower.iter().enumerate().skip(i).take(MAX_WORD_CHARS) {
+            token.push(*c);
+            if j + 1 - i < MIN_WORD_CHARS {
+                continue;
+            }
+            for (dictionary, rank) in [
+                (Dictionary::Passwords, PASSWORDS.get(token.as_str())),
+                (Dictionary::Words, WORDS.get(token.as_str())),
+                (Dictionary::UserInputs, user_inputs.get(token.as_str())),
+            ] {
+                if let Some(rank) = rank {
+                    found.push((i, j, dictionary, *rank));
+                }
+            }
+        }
+    }
+    found
+}
+
+fn dictionary_matches(chars: &[char], lower: &[char], user_inputs: &HashMap<String, usize>, matches: &mut Vec<Match>) {
+    let word_guesses = |i: usize, j: usize, rank: usize| (rank as f64).log10() + uppercase_variations(&chars[i..=j]);
+
+    for (i, j, dictionary, rank) in find_words(lower, user_inputs) {
+        let pattern = Pattern::Dictionary { dictionary, rank, reversed: false, l33t: false };
+        matches.push(Match { i, j, pattern, guesses_log10: word_guesses(i, j, rank) });
+    }
+
+    let n = lower.len();
+    let reversed: Zeroizing<Vec<char>> = Zeroizing::new(lower.iter().rev().copied().collect());
+    for (i, j, dictionary, rank) in find_words(&reversed, user_inputs) {
+        let (i, j) = (n - 1 - j, n - 1 - i);
+        let pattern = Pattern::Dictionary { dictionary, rank, reversed: true, l33t: false };
+        matches.push(Match { i, j, pattern, guesses_log10: word_guesses(i, j, rank) + 2f64.log10() });
+    }
+
+    // Two readings of the ambiguous substitutions: 1 and | as i or as l,
+    // 7 as t or as l
+    for pick_last in [false, true] {
+        let unleeted: Zeroizing<Vec<char>> = Zeroizing::new(lower.iter()
+            .map(|&c| {
+                let mut letters = L33T_TABLE.iter().filter(|(_, subs)| subs.contains(c)).map(|(letter, _)| *letter);
+                let letter = if pick_last { letters.next_back() } else { letters.next() };
+                letter.unwrap_or(c)
+            })
+            .collect());
+        if unleeted.as_slice() == lower {
+            continue;
+        }
+        for (i, j, dictionary, rank) in find_words(&unleeted, user_inputs) {
+            let token = &lower[i..=j];
+            let word = &unleeted[i..=j];
+            if token == word {
+                continue;
+            }
+            let mut variations = 0.0;
+            let mut letters: Vec<char> = word.iter().zip(token).filter(|(w, t)| w != t).map(|(w, _)| *w).collect();
+            letters.sort_unstable();
+            letters.dedup();
+            for letter in letters {
+                let subbed = word.iter().zip(token).filter(|(w, t)| **w == letter && **t != letter).count();
+                let unsubbed = token.iter().filter(|t| **t == letter).count();
+                variations += if unsubbed == 0 {
+                    2f64.log10()
+                } else {
+                    log10_binomial_sum(subbed + unsubbed, subbed.min(unsubbed))
+                };
+            }
+            let pattern = Pattern::Dictionary { dictionary, rank, reversed: false, l33t: true };
+            matches.push(Match { i, j, pattern, guesses_log10: word_guesses(i, j, rank) + variations });
+        }
+    }
+}
+
+fn spatial_guesses(length: usize, turns: usize, shifted: usize) -> f64 {
+    let keyboard = &*KEYBOARD;
+    let mut guesses = f64::NEG_INFINITY;
+    for i in 2..=length {
+        for j in 1..=turns.min(i - 1) {
+            let possibilities = log10_binomial(i - 1, j - 1)
+                + keyboard.starting_positions.log10()
+                + j as f64 * keyboard.average_degree.log10();
+            guesses = log10_add(guesses, possibilities);
+        }
+    }
+    let unshifted = length - shifted;
+    if shifted > 0 {
+        guesses += if unshifted == 0 { 2f64.log10() } else { log10_binomial_sum(length, shifted.min(unshifted)) };
+    }
+    guesses
+}
+
+fn spatial_matches(chars: &[char], matches: &mut Vec<Match>) {
+    let keys = &KEYBOARD.keys;
+    let mut i = 0;
+    while i + 2 < chars.len() {
+        let mut j = i;
+        let mut turns = 0;
+        let mut last_direction = None;
+        while let (Some(a), Some(b)) = (keys.get(&chars[j]), chars.get(j + 1).and_then(|c| keys.get(c))) {
+            if !Keyboard::adjacent(a, b) {
+                break;
+            }
+            let direction = Keyboard::direction(a, b);
+            if last_direction != Some(direction) {
+                turns += 1;
+                last_direction = Some(direction);
+            }
+            j += 1;
+        }
+        if j - i + 1 >= 3 {
+            let shifted = chars[i..=j].iter().filter(|c| keys.get(c).is_some_and(|k| k.shifted)).count();
+            let guesses_log10 = spatial_guesses(j - i + 1, turns, shifted);
+            matches.push(Match { i, j, pattern: Pattern::Spatial { turns }, guesses_log10 });
+        }
+        i = j.max(i + 1);
+    }
+}
+
+fn repeat_matches(chars: &[char], user_inputs: &HashMap<String, usize>, matches: &mut Vec<Match>) {
+    let n = chars.len();
+    let mut i = 0;
+    while i < n {
+        let mut best: Option<(usize, usize)> = None;
+        for base_len in 1..=(n - i) / 2 {
+            let base = &chars[i..i + base_len];
+            let mut repeats = 1;
+            while i + (repeats + 1) * base_len <= n && &chars[i + repeats * base_len..i + (repeats + 1) * base_len] == base {
+                repeats += 1;
+            }
+            if repeats >= 2 && best.is_none_or(|(length, _)| repeats * base_len > length) {
+                best = Some((repeats * base_len, base_len));
+            }
+        }
+        let Some((length, base_len)) = best else {
+            i += 1;
+            continue;
+        };
+        let (base_guesses, _) = most_guessable(&chars[i..i + base_len], user_inputs);
+        matches.push(Match {
+            i,
+            j: i + length - 1,
+            pattern: Pattern::Repeat { base_len },
+            guesses_log10: base_guesses + ((length / base_len) as f64).log10(),
+        });
+        i += length;
+    }
+}
+
+fn sequence_matches(chars: &[char], matches: &mut Vec<Match>) {
+    let class = |c: char| {
+        if c.is_ascii_lowercase() {
+            Some(0)
+        } else if c.is_ascii_uppercase() {
+            Some(1)
+        } else if c.is_ascii_digit() {
+            Some(2)
+        } else {
+            None
+        }
+    };
+    let mut i = 0;
+    while i + 1 < chars.len() {
+        let delta = chars[i + 1] as i32 - chars[i] as i32;
+        let mut j = i + 1;
+        while j + 1 < chars.len() && chars[j + 1] as i32 - chars[j] as i32 == delta {
+            j += 1;
+        }
+        let token = &chars[i..=j];
+        let same_class = class(token[0]).is_some() && token.iter().all(|&c| class(c) == class(token[0]));
+        if token.len() >= 3 && delta != 0 && delta.abs() <= 5 && same_class {
+            let base: f64 = if "aAzZ019".contains(token[0]) {
+                4.0
+            } else if token[0].is_ascii_digit() {
+                10.0
+            } else {
+                26.0
+            };
+            let direction: f64 = if delta > 0 { 1.0 } else { 2.0 };
+            let guesses_log10 = (base * direction * token.len() as f64).log10();
+            matches.push(Match { i, j, pattern: Pattern::Sequence, guesses_log10 });
+        }
+        i = j;
+    }
+}
+
+fn two_to_four_digit_year(year: i32) -> i32 {
+    match year {
+        100.. => year,
+        51..=99 => year + 1900,
+        _ => year + 2000,
+    }
+}
+
+fn ints_to_day_month(a: i32, b: i32) -> bool {
+    ((1..=31).contains(&a) && (1..=12).contains(&b)) || ((1..=31).contains(&b) && (1..=12).contains(&a))
+}
+
+/// The year of three numbers read as a date in any common order
+fn date_year(ints: [i32; 3]) -> Option<i32> {
+    if ints[1] > 31 || ints[1] <= 0 {
+        return None;
+    }
+    if ints.iter().any(|&n| (100..DATE_MIN_YEAR).contains(&n) || n > DATE_MAX_YEAR) {
+        return None;
+    }
+    let over_31 = ints.iter().filter(|&&n| n > 31).count();
+    let over_12 = ints.iter().filter(|&&n| n > 12).count();
+    let under_1 = ints.iter().filter(|&&n| n <= 0).count();
+    if over_31 >= 2 || over_12 == 3 || under_1 >= 2 {
+        return None;
+    }
+
+    let splits = [(ints[2], ints[0], ints[1]), (ints[0], ints[1], ints[2])];
+    for (year, a, b) in splits {
+        if (DATE_MIN_YEAR..=DATE_MAX_YEAR).contains(&year) {
+            return ints_to_day_month(a, b).then_some(year);
+        }
+    }
+    splits.iter()
+        .find(|(_, a, b)| ints_to_day_month(*a, *b))
+        .map(|(year, _, _)| two_to_four_digit_year(*year))
+}
+
+fn year_space(year: i32, reference: i32) -> f64 {
+    (year - reference).abs().max(MIN_YEAR_SPACE) as f64
+}
+
+fn parse_digits(digits: &[char]) -> i32 {
+    digits.iter().fold(0, |n, c| n * 10 + c.to_digit(10).unwrap_or(0) as i32)
+}
+
+fn date_matches(chars: &[char], matches: &mut Vec<Match>) {
+    let reference = Utc::now().year();
+    let n = chars.len();
+    let is_digit = |c: &char| c.is_ascii_digit();
+
+    // Recent years on their own
+    for i in 0..n.saturating_sub(3) {
+        let token = &chars[i..i + 4];
+        if token.iter().all(is_digit) && matches!(token[..2], ['1', '9'] | ['2', '0']) {
+            let guesses_log10 = year_space(parse_digits(token), reference).log10();
+            matches.push(Match { i, j: i + 3, pattern: Pattern::Year, guesses_log10 });
+        }
+    }
+
+    // Dates without separators, 4 to 8 digits
+    for length in 4..=8 {
+        for i in 0..(n + 1).saturating_sub(length) {
+            let token = &chars[i..i + length];
+            if !token.iter().all(is_digit) {
+                continue;
+            }
+            let closest = DATE_SPLITS[length - 4].iter()
+                .filter_map(|&(k, l)| date_year([parse_digits(&token[..k]), parse_digits(&token[k..l]), parse_digits(&token[l..])]))
+                .min_by_key(|year| (year - reference).abs());
+            if let Some(year) = closest {
+                let guesses_log10 = (year_space(year, reference) * 365.0).log10();
+                matches.push(Match { i, j: i + length - 1, pattern: Pattern::Date, guesses_log10 });
+            }
+        }
+    }
+
+    // Dates with separators: 1-4 digits, separator, 1-2 digits, the same separator, 1-4 digits
+    for length in 5..=10 {
+        for i in 0..(n + 1).saturating_sub(length) {
+            let token = &chars[i..i + length];
+            let Some(first) = token.iter().position(|c| !c.is_ascii_digit()) else {
+                continue;
+            };
+            let separator = token[first];
+            if !DATE_SEPARATORS.contains(separator) || !(1..=4).contains(&first) {
+                continue;
+            }
+            let Some(second) = token[first + 1..].iter().position(|c| !c.is_ascii_digit()).map(|p| p + first + 1) else {
+                continue;
+            };
+            let middle = &token[first + 1..second];
+            let last = &token[second + 1..];
+            if token[second] != separator || !(1..=2).contains(&middle.len()) || !(1..=4).contains(&last.len())
+                || !last.iter().all(is_digit) {
+                continue;
+            }
+            let ints = [parse_digits(&token[..first]), parse_digits(middle), parse_digits(last)];
+            if let Some(year) = date_year(ints) {
+                let guesses_log10 = (year_space(year, reference) * 365.0 * 4.0).log10();
+                matches.push(Match { i, j: i + length - 1, pattern: Pattern::Date, guesses_log10 });
+            }
+        }
+    }
+}
+
+fn omnimatch(chars: &[char], user_inputs: &HashMap<String, usize>) -> Vec<Match> {
+    let lower: Zeroizing<Vec<char>> = Zeroizing::new(chars.iter().flat_map(|c| c.to_lowercase()).collect());
+    let mut matches = Vec::new();
+    // Lowercasing rarely changes the length (İ is one); skip words then
+    if lower.len() == chars.len() {
+        dictionary_matches(chars, &lower, user_inputs, &mut matches);
+    }
+    spatial_matches(chars, &mut matches);
+    repeat_matches(chars, user_inputs, &mut matches);
+    sequence_matches(chars, &mut matches);
+    date_matches(chars, &mut matches);
+    matches
+}
+
+struct Step {
+    m: Match,
+    /// log10 of the product of the guesses of the matches so far
+    product: f64,
+    /// log10 of the guesses for the whole sequence so far
+    total: f64,
+}
+
+/// The cheapest covering of the password by matches and brute force, as in
+/// zxcvbn: guesses = l! × the product of the match guesses + 10 000^(l-1)
+/// for a sequence of l matches
+fn most_guessable(chars: &[char], user_inputs: &HashMap<String, usize>) -> (f64, Vec<Match>) {
+    let n = chars.len();
+    if n == 0 {
+        return (0.0, Vec::new());
+    }
+
+    let mut by_end: Vec<Vec<Match>> = vec![Vec::new(); n];
+    for mut m in omnimatch(chars, user_inputs) {
+        let length = m.j - m.i + 1;
+        if length < n {
+            let floor = if length == 1 { MIN_SUBMATCH_GUESSES_SINGLE_CHAR } else { MIN_SUBMATCH_GUESSES_MULTI_CHAR };
+            m.guesses_log10 = m.guesses_log10.max(floor.log10());
+        }
+        by_end[m.j].push(m);
+    }
+
+    // optimal[k][l]: best sequence of l matches covering 0..=k
+    let mut optimal: Vec<BTreeMap<usize, Step>> = (0..n).map(|_| BTreeMap::new()).collect();
+    let update = |optimal: &mut Vec<BTreeMap<usize, Step>>, m: Match, l: usize| {
+        let previous = if l > 1 { optimal[m.i - 1][&(l - 1)].product } else { 0.0 };
+        let product = m.guesses_log10 + previous;
+        let total = log10_add(log10_factorial(l) + product, MIN_GUESSES_BEFORE_GROWING_SEQUENCE_LOG10 * (l - 1) as f64);
+        let k = m.j;
+        if optimal[k].iter().any(|(&other, step)| other <= l && step.total <= total) {
+            return;
+        }
+        optimal[k].insert(l, Step { m, product, total });
+    };
+    let bruteforce = |i: usize, k: usize| {
+        let length = k - i + 1;
+        let mut guesses_log10 = length as f64 * BRUTEFORCE_LOG10;
+        if length < n {
+            let floor = if length == 1 { MIN_SUBMATCH_GUESSES_SINGLE_CHAR } else { MIN_SUBMATCH_GUESSES_MULTI_CHAR };
+            guesses_log10 = guesses_log10.max((floor + 1.0).log10());
+        }
+        Match { i, j: k, pattern: Pattern::Bruteforce, guesses_log10 }
+    };
+
+    for (k, ending) in by_end.iter().enumerate() {
+        for m in ending {
+            if m.i == 0 {
+                update(&mut optimal, m.clone(), 1);
+            } else {
+                let lengths: Vec<usize> = optimal[m.i - 1].keys().copied().collect();
+                for l in lengths {
+                    update(&mut optimal, m.clone(), l + 1);
+                }
+            }
+        }
+
+        update(&mut optimal, bruteforce(0, k), 1);
+        for i in 1..=k {
+            // Two brute-force runs in a row are never better than one
+            let lengths: Vec<usize> = optimal[i - 1].iter()
+                .filter(|(_, step)| !matches!(step.m.pattern, Pattern::Bruteforce))
+                .map(|(&l, _)| l)
+                .collect();
+            for l in lengths {
+                update(&mut optimal, bruteforce(i, k), l + 1);
+            }
+        }
+    }
+
+    let Some((&best, last)) = optimal[n - 1].iter().min_by(|a, b| a.1.total.total_cmp(&b.1.total)) else {
+        return (n as f64 * BRUTEFORCE_LOG10, Vec::new());
+    };
+    let guesses_log10 = last.total;
+    let mut sequence = Vec::new();
+    let (mut k, mut l) = (n - 1, best);
+    loop {
+        let step = &optimal[k][&l];
+        sequence.push(step.m.clone());
+        if step.m.i == 0 || l == 1 {
+            break;
+        }
+        k = step.m.i - 1;
+        l -= 1;
+    }
+    sequence.reverse();
+    (guesses_log10, sequence)
+}
+
+fn score(guesses_log10: f64) -> u8 {
+    // zxcvbn's thresholds, each plus a margin of 5 guesses
+    [1e3, 1e6, 1e8, 1e10]
+        .iter()
+        .take_while(|&&threshold| guesses_log10 >= (threshold + 5.0f64).log10())
+        .count() as u8
+}
+
+fn feedback(score: u8, sequence: &[Match], chars: &[char]) -> Feedback {
+    if sequence.is_empty() {
+        return Feedback { warning: None, suggestions: vec!["use_few_words", "no_need_for_symbols"] };
+    }
+    if score > 2 {
+        return Feedback { warning: None, suggestions: vec![] };
+    }
+
+    let Some(longest) = sequence.iter().max_by_key(|m| m.j - m.i) else {
+        return Feedback { warning: None, suggestions: vec![] };
+    };
+    let mut suggestions = vec!["add_another_word"];
+    let warning = match &longest.pattern {
+        Pattern::Dictionary { dictionary, rank, reversed, l33t } => {
+            let sole_match = sequence.len() == 1;
+            let token = &chars[longest.i..=longest.j];
+            if token[0].is_uppercase() && token.iter().skip(1).all(|c| !c.is_uppercase()) {
+                suggestions.push("capitalization_doesnt_help");
+            } else if token.iter().all(|c| !c.is_lowercase()) && token.iter().any(|c| c.is_uppercase()) {
+                suggestions.push("all_uppercase_doesnt_help");
+            }
+            if *reversed && token.len() >= 4 {
+                suggestions.push("reversed_words_arent_hard");
+            }
+            if *l33t {
+                suggestions.push("predictable_substitutions");
+            }
+            match dictionary {
+                Dictionary::Passwords if sole_match && !l33t && !reversed => Some(match rank {
+                    ..=10 => "top10_password",
+                    ..=100 => "top100_password",
+                    _ => "common_password",
+                }),
+                Dictionary::Passwords => (longest.guesses_log10 <= 4.0).then_some("similar_to_common_password"),
+                Dictionary::Words => sole_match.then_some("word_by_itself"),
+                Dictionary::UserInputs => Some("user_input"),
+            }
+        }
+        Pattern::Spatial { turns } => {
+            suggestions.push("longer_keyboard_pattern");
+            Some(if *turns == 1 { "straight_row" } else { "keyboard_pattern" })
+        }
+        Pattern::Repeat { base_len } => {
+            suggestions.push("avoid_repeats");
+            Some(if *base_len == 1 { "repeated_char" } else { "repeated_pattern" })
+        }
+        Pattern::Sequence => {
+            suggestions.push("avoid_sequences");
+            Some("sequence")
+        }
+        Pattern::Year => {
+            suggestions.push("avoid_recent_years");
+            Some("recent_year")
+        }
+        Pattern::Date => {
+            suggestions.push("avoid_dates");
+            Some("date")
+        }
+        Pattern::Bruteforce => None,
+    };
+    Feedback { warning, suggestions }
+}
+
+/// User inputs and their words as a ranked dictionary
+fn user_dictionary(user_inputs: &[&str]) -> HashMap<String, usize> {
+    let mut dictionary = HashMap::new();
+    let words = user_inputs.iter().flat_map(|input| {
+        std::iter::once(input.trim()).chain(input.split(|c: char| !c.is_alphanumeric()))
+    });
+    for word in words {
+        let word = word.to_lowercase();
+        if word.chars().count() >= MIN_WORD_CHARS {
+            let rank = dictionary.len() + 1;
+            dictionary.entry(word).or_insert(rank);
+        }
+    }
+    dictionary
+}
+
+/// Estimates how hard a password is to guess. `user_inputs` are strings an
+/// attacker would try first: the username, the service, the login.
+pub fn estimate(password: &SecretString, user_inputs: &[&str]) -> Strength {
+    let chars: Zeroizing<Vec<char>> = Zeroizing::new(password.expose().chars().collect());
+    let analyzed = &chars[..chars.len().min(MAX_ANALYZED_CHARS)];
+    let (mut guesses_log10, sequence) = most_guessable(analyzed, &user_dictionary(user_inputs));
+    guesses_log10 += (chars.len() - analyzed.len()) as f64 * BRUTEFORCE_LOG10;
+
+    let score = score(guesses_log10);
+    let seconds = |per_second: f64| 10f64.powf(guesses_log10) / per_second;
+    Strength {
+        score,
+        guesses_log10,
+        crack_times_seconds: CrackTimes {
+            online_throttling: seconds(100.0 / 3600.0),
+            online_no_throttling: seconds(10.0),
+            offline_slow_hashing: seconds(1e4),
+            offline_fast_hashing: seconds(1e10),
+        },
+        feedback: feedback(score, &sequence, analyzed),
+    }
+}
+
+/// Strength shown with an entry, None for an entry without a password
+pub fn summary(password: &SecretString, user_inputs: &[&str]) -> Option<StrengthSummary> {
+    if password.is_empty() {
+        return None;
+    }
+    let strength = estimate(password, user_inputs);
+    Some(StrengthSummary {
+        score: strength.score,
+        guesses_log10: strength.guesses_log10,
+        warning: strength.feedback.warning,
+    })
+}
+
+/// Rejects master and pseudo passwords below `MIN_MASTER_SCORE`
+pub fn require_master_strength(password: &SecretString, user_inputs: &[&str]) -> Result<(), XPassError> {
+    if password.is_empty() {
+        return Err(XPassError::EmptyPassword);
+    }
+    let score = estimate(password, user_inputs).score;
+    if score < MIN_MASTER_SCORE {
+        return Err(XPassError::WeakPassword { score, min_score: MIN_MASTER_SCORE });
+    }
+    Ok(())
+}
+
+/// Estimates the strength of a password, e.g. while it is being typed
+#[tauri::command]
+pub fn estimate_password_strength(password: SecretString, user_inputs: Option<Vec<String>>) -> Strength {
+    let user_inputs = user_inputs.unwrap_or_default();
+    let user_inputs: Vec<&str> = user_inputs.iter().map(String::as_str).collect();
+    estimate(&password, &user_inputs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    fn check(password: &str) -> Strength {
+        estimate(&SecretString::new(password.to_string()), &[])
+    }
+
+    fn dictionary_match(password: &str) -> Option<Pattern> {
+        let chars: Vec<char> = password.chars().collect();
+        let (_, sequence) = most_guessable(&chars, &HashMap::new());
+        match sequence.as_slice() {
+            [m] if m.i == 0 && m.j == chars.len() - 1 => Some(m.pattern.clone()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn guessable_passwords_score_low() {
+        for password in ["a", "password1", "qwerty", "1234", "19.05.1987"] {
+            let strength = check(password);
+            assert!(strength.score <= 1, "{} scored {}", password, strength.score);
+        }
+        assert_eq!(check("qwerty").feedback.warning, Some("top10_password"));
+        assert_eq!(check("19.05.1987").feedback.warning, Some("date"));
+    }
+
+    #[test]
+    fn diceware_passphrase_scores_4() {
+        for _ in 0..20 {
+            let passphrase = passphrase::generate(&passphrase::PassphrasePolicy::default()).unwrap().passphrase;
+            let strength = estimate(&passphrase, &[]);
+            assert_eq!(strength.score, 4, "{:.1} guesses (log10)", strength.guesses_log10);
+        }
+    }
+
+    #[test]
+    fn reversed_words_are_detected() {
+        let pattern = dictionary_match("drowssap");
+        assert!(matches!(pattern, Some(Pattern::Dictionary { reversed: true, l33t: false, .. })), "{:?}", pattern);
+        assert!(check("drowssap").feedback.suggestions.contains(&"reversed_words_arent_hard"));
+    }
+
+    #[test]
+    fn l33t_words_are_detected() {
+        for password in ["p4ssw0rd", "P@ssw0rd", "m0nk3y"] {
+            let pattern = dictionary_match(password);
+            assert!(matches!(pattern, Some(Pattern::Dictionary { l33t: true, .. })), "{}: {:?}", password, pattern);
+            assert!(check(password).score <= 1, "{}", password);
+        }
+        assert!(check("p4ssw0rd").feedback.suggestions.contains(&"predictable_substitutions"));
+    }
+
+    #[test]
+    fn user_inputs_are_detected() {
+        let strength = estimate(&SecretString::new("Alexander1990".to_string()), &["alexander@example.com"]);
+        assert!(strength.score <= 2);
+        assert_eq!(strength.feedback.warning, Some("user_input"));
+    }
+
+    /// The backtrack in `most_guessable` follows `optimal[k][&l]`; every
+    /// step it reaches must exist and the sequence must cover the password
+    #[test]
+    fn best_sequence_covers_the_password() {
+        const PIECES: [&str; 12] = ["password", "qwerty", "1987", "19.05.", "abc", "aaaa", "drow", "p4ss", "!", "x", "-", "Zebra"];
+        let mut rng = rand::thread_rng();
+        for _ in 0..500 {
+            let password: String = (0..rng.gen_range(1..8)).map(|_| PIECES[rng.gen_range(0..PIECES.len())]).collect();
+            let chars: Vec<char> = password.chars().collect();
+            let (_, sequence) = most_guessable(&chars, &HashMap::new());
+            let mut next = 0;
+            for m in &sequence {
+                assert_eq!(m.i, next, "{}: {:?}", password, sequence);
+                next = m.j + 1;
+            }
+            assert_eq!(next, chars.len(), "{}", password);
+        }
+    }
+
+    #[test]
+    fn long_passwords_are_cut_for_analysis() {
+        let password = "a".repeat(MAX_ANALYZED_CHARS * 3);
+        let strength = check(&password);
+        assert!(strength.guesses_log10 >= (MAX_ANALYZED_CHARS * 2) as f64 * BRUTEFORCE_LOG10);
+    }
+}
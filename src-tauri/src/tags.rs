@@ -0,0 +1,256 @@
+// ============================================================================
+// X-PASS Password Manager
+// Copyright (C) 2026 ar3love
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See LICENSE.
+// ============================================================================
+
+// src-tauri/src/tags.rs
+//
+// Free-form tags for entries. Like folder names, tag names are encrypted
+// with the data key and kept unique (case-insensitively) by comparing the
+// decrypted names. An entry can carry any number of tags.
+//
+// A pseudo session sees no tags; its changes are accepted but not stored,
+// and a tag it creates gets a negative id.
+use aes_gcm::Aes256Gcm;
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use tauri::State;
+
+use crate::database::Database;
+use crate::error::XPassError;
+use crate::folders::clean_name;
+use crate::keys;
+use crate::secrets::SecretString;
+use crate::session::SessionStore;
+
+const MAX_TAGS_PER_ENTRY: usize = 50;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct Tag {
+    pub id: i64,
+    pub name: SecretString,
+}
+
+/// All tags of the user, decrypted
+pub fn load_tags(conn: &Connection, user_id: i64, cipher: &Aes256Gcm) -> Result<Vec<Tag>, XPassError> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT id, enc_name, name_nonce FROM tags WHERE user_id = ?1 ORDER BY id"
+    )?;
+    let rows = stmt.query_map(params![user_id], |row| {
+        Ok((row.get::<_, i64>(0)?, row.get::<_, Vec<u8>>(1)?, row.get::<_, Vec<u8>>(2)?))
+    })?;
+
+    let mut tags = Vec::new();
+    for row in rows {
+        let (id, enc_name, name_nonce) = row?;
+        tags.push(Tag { id, name: keys::decrypt_secret(cipher, &enc_name, &name_nonce)? });
+    }
+    Ok(tags)
+}
+
+/// Ids of the tags on an entry
+pub fn entry_tag_ids(conn: &Connection, entry_id: i64) -> Result<Vec<i64>, XPassError> {
+    let mut stmt = conn.prepare_cached("SELECT tag_id FROM entry_tags WHERE entry_id = ?1 ORDER BY tag_id")?;
+    let ids = stmt.query_map(params![entry_id], |row| row.get(0))?
+        .collect::<Result<_, _>>()?;
+    Ok(ids)
+}
+
+fn find_by_name<'a>(tags: &'a [Tag], name: &SecretString) -> Option<&'a Tag> {
+    let name = name.expose().to_lowercase();
+    tags.iter().find(|t| t.name.expose().to_lowercase() == name)
+}
+
+fn insert_tag(conn: &Connection, user_id: i64, name: &SecretString, cipher: &Aes256Gcm) -> Result<i64, XPassError> {
+    let (enc_name, name_nonce) = keys::encrypt_secret(cipher, name)?;
+    conn.execute(
+        "INSERT INTO tags (user_id, enc_name, name_nonce, created_at) VALUES (?1, ?2, ?3, ?4)",
+        params![user_id, enc_name, name_nonce, Utc::now().to_rfc3339()],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Replaces the tags on an entry
+pub fn assign(conn: &Connection, entry_id: i64, tag_ids: &[i64]) -> Result<(), XPassError> {
+    conn.execute("DELETE FROM entry_tags WHERE entry_id = ?1", params![entry_id])?;
+    let mut stmt = conn.prepare_cached("INSERT OR IGNORE INTO entry_tags (entry_id, tag_id) VALUES (?1, ?2)")?;
+    for tag_id in tag_ids {
+        stmt.execute(params![entry_id, tag_id])?;
+    }
+    Ok(())
+}
+
+/// Tags an imported entry by name, creating tags that do not exist yet
+pub fn assign_names(conn: &Connection, user_id: i64, entry_id: i64, names: &[SecretString], cipher: &Aes256Gcm) -> Result<(), XPassError> {
+    let mut tags = load_tags(conn, user_id, cipher)?;
+    let mut ids = Vec::new();
+    for name in names.iter().take(MAX_TAGS_PER_ENTRY) {
+        let name = clean_name(name)?;
+        let id = match find_by_name(&tags, &name) {
+            Some(tag) => tag.id,
+            None => {
+                let id = insert_tag(conn, user_id, &name, cipher)?;
+                tags.push(Tag { id, name });
+                id
+            }
+        };
+        ids.push(id);
+    }
+    assign(conn, entry_id, &ids)
+}
+
+/// Lists the user's tags
+#[tauri::command]
+pub fn list_tags(
+    db: State<'_, Database>,
+    sessions: State<'_, SessionStore>,
+    user_id: i64,
+    session_id: String
+) -> Result<Vec<Tag>, XPassError> {
+    let cipher = sessions.cipher(&session_id, user_id)?;
+    let conn = db.conn()?;
+
+    if !keys::is_vault_key(&conn, user_id, &cipher)? {
+        return Ok(vec![]);
+    }
+    load_tags(&conn, user_id, &cipher)
+}
+
+/// Creates a tag, or returns the existing one with the same name
+#[tauri::command]
+pub fn create_tag(
+    db: State<'_, Database>,
+    sessions: State<'_, SessionStore>,
+    user_id: i64,
+    session_id: String,
+    name: SecretString
+) -> Result<Tag, XPassError> {
+    let cipher = sessions.cipher(&session_id, user_id)?;
+    let conn = db.conn()?;
+    let name = clean_name(&name)?;
+
+    if !keys::is_vault_key(&conn, user_id, &cipher)? {
+        return Ok(Tag { id: -Utc::now().timestamp_millis(), name });
+    }
+    let tags = load_tags(&conn, user_id, &cipher)?;
+    if let Some(tag) = find_by_name(&tags, &name) {
+        return Ok(tag.clone());
+    }
+
+    let id = insert_tag(&conn, user_id, &name, &cipher)?;
+    Ok(Tag { id, name })
+}
+
+/// Renames a tag
+#[tauri::command]
+pub fn rename_tag(
+    db: State<'_, Database>,
+    sessions: State<'_, SessionStore>,
+    user_id: i64,
+    session_id: String,
+    tag_id: i64,
+    name: SecretString
+) -> Result<(), XPassError> {
+    let cipher = sessions.cipher(&session_id, user_id)?;
+    let conn = db.conn()?;
+    let name = clean_name(&name)?;
+
+    if !keys::is_vault_key(&conn, user_id, &cipher)? {
+        return Ok(());
+    }
+    let tags = load_tags(&conn, user_id, &cipher)?;
+    if !tags.iter().any(|t| t.id == tag_id) {
+        return Err(XPassError::NotFound { what: "Tag" });
+    }
+    if find_by_name(&tags, &name).is_some_and(|t| t.id != tag_id) {
+        return Err(XPassError::invalid_input("name", "a tag with this name already exists"));
+    }
+
+    let (enc_name, name_nonce) = keys::encrypt_secret(&cipher, &name)?;
+    conn.execute(
+        "UPDATE tags SET enc_name = ?1, name_nonce = ?2 WHERE id = ?3 AND user_id = ?4",
+        params![enc_name, name_nonce, tag_id, user_id],
+    )?;
+    Ok(())
+}
+
+/// Deletes a tag and removes it from every entry
+#[tauri::command]
+pub fn delete_tag(
+    db: State<'_, Database>,
+    sessions: State<'_, SessionStore>,
+    user_id: i64,
+    session_id: String,
+    tag_id: i64
+) -> Result<(), XPassError> {
+    let cipher = sessions.cipher(&session_id, user_id)?;
+    let conn = db.conn()?;
+
+    if !keys::is_vault_key(&conn, user_id, &cipher)? {
+        return Ok(());
+    }
+    let count = conn.execute(
+        "DELETE FROM tags WHERE id = ?1 AND user_id = ?2",
+        params![tag_id, user_id],
+    )?;
+    if count == 0 {
+        return Err(XPassError::NotFound { what: "Tag" });
+    }
+    Ok(())
+}
+
+/// Replaces the tags on an entry
+#[tauri::command]
+pub fn set_entry_tags(
+    db: State<'_, Database>,
+    sessions: State<'_, SessionStore>,
+    user_id: i64,
+    session_id: String,
+    entry_id: i64,
+    tag_ids: Vec<i64>
+) -> Result<(), XPassError> {
+    let cipher = sessions.cipher(&session_id, user_id)?;
+    // Fake pseudo-mode entries have negative ids and are not in the database
+    if entry_id < 0 {
+        return Ok(());
+    }
+    if tag_ids.len() > MAX_TAGS_PER_ENTRY {
+        return Err(XPassError::invalid_input("tag_ids", format!("at most {} tags per entry", MAX_TAGS_PER_ENTRY)));
+    }
+
+    let mut conn = db.conn()?;
+    if !keys::is_vault_key(&conn, user_id, &cipher)? {
+        return Ok(());
+    }
+    conn.query_row(
+        "SELECT 1 FROM entries WHERE id = ?1 AND user_id = ?2 AND deleted_at IS NULL",
+        params![entry_id, user_id],
+        |_| Ok(())
+    ).optional()?.ok_or(XPassError::NotFound { what: "Entry" })?;
+
+    let owned: i64 = {
+        let mut stmt = conn.prepare_cached("SELECT COUNT(*) FROM tags WHERE id = ?1 AND user_id = ?2")?;
+        let mut count = 0;
+        for tag_id in &tag_ids {
+            count += stmt.query_row(params![tag_id, user_id], |row| row.get::<_, i64>(0))?;
+        }
+        count
+    };
+    if owned != tag_ids.len() as i64 {
+        return Err(XPassError::NotFound { what: "Tag" });
+    }
+
+    let tx = conn.transaction()?;
+    assign(&tx, entry_id, &tag_ids)?;
+    tx.commit()?;
+    Ok(())
+}
@@ -0,0 +1,327 @@
+// ============================================================================
+// X-PASS Password Manager
+// Copyright (C) 2026 ar3love
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See LICENSE.
+// ============================================================================
+
+// src-tauri/src/throttle.rs
+//
+// Brute-force protection for every way of proving an account is yours:
+// master password, pseudo passwords and OTP codes all share one counter per
+// user, stored in `login_attempts` so restarting the app does not reset it.
+//
+// The first `free_attempts` failures cost nothing. After that each failure
+// doubles the wait before the next attempt (up to `max_delay_secs`), and once
+// `lockout_threshold` failures are reached every further failure locks the
+// account for `lockout_secs`. Any successful unlock, including a pseudo
+// password, clears the counter so the two stay indistinguishable.
+use chrono::{DateTime, Duration, Utc};
+use log::{info, warn};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::database::Database;
+use crate::error::XPassError;
+use crate::keys;
+use crate::password_manager;
+use crate::secrets::SecretString;
+use crate::session::SessionStore;
+
+/// Longest backoff and lockout that can be configured, one day
+const MAX_CONFIGURABLE_SECS: u64 = 24 * 60 * 60;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ThrottlePolicy {
+    /// Failures allowed before any delay applies
+    pub free_attempts: u32,
+    /// Delay after the first throttled failure, doubled for every further one
+    pub base_delay_secs: u64,
+    pub max_delay_secs: u64,
+    /// Failures after which the account is locked, 0 disables lockout
+    pub lockout_threshold: u32,
+    pub lockout_secs: u64,
+}
+
+impl ThrottlePolicy {
+    pub const DEFAULT: ThrottlePolicy = ThrottlePolicy {
+        free_attempts: 3,
+        base_delay_secs: 2,
+        max_delay_secs: 300,
+        lockout_threshold: 10,
+        lockout_secs: 15 * 60,
+    };
+
+    /// Wait imposed after the given number of consecutive failures
+    fn delay_after(&self, failures: u32) -> u64 {
+        if failures <= self.free_attempts {
+            return 0;
+        }
+        let doublings = (failures - self.free_attempts - 1).min(63);
+        self.base_delay_secs
+            .saturating_mul(1u64 << doublings)
+            .min(self.max_delay_secs)
+    }
+
+    fn locks_after(&self, failures: u32) -> bool {
+        self.lockout_threshold > 0 && failures >= self.lockout_threshold
+    }
+
+    fn validate(&self) -> Result<(), XPassError> {
+        if self.base_delay_secs > self.max_delay_secs {
+            return Err(XPassError::invalid_input("base_delay_secs", "must not exceed max_delay_secs"));
+        }
+        if self.max_delay_secs > MAX_CONFIGURABLE_SECS {
+            return Err(XPassError::invalid_input("max_delay_secs", "must be at most one day"));
+        }
+        if self.lockout_threshold > 0 {
+            if self.lockout_threshold <= self.free_attempts {
+                return Err(XPassError::invalid_input("lockout_threshold", "must be greater than free_attempts"));
+            }
+            if self.lockout_secs == 0 || self.lockout_secs > MAX_CONFIGURABLE_SECS {
+                return Err(XPassError::invalid_input("lockout_secs", "must be between 1 second and one day"));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// What the login screen needs to know about an account before an attempt
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LockoutStatus {
+    pub failed_attempts: u32,
+    pub locked: bool,
+    /// Seconds until the next attempt is accepted, 0 if it is accepted now
+    pub retry_after_secs: u64,
+    /// Failures left before the account is locked, None if lockout is disabled
+    pub attempts_before_lockout: Option<u32>,
+}
+
+#[derive(Default)]
+struct AttemptState {
+    failed_count: u32,
+    next_attempt_at: Option<DateTime<Utc>>,
+    locked_until: Option<DateTime<Utc>>,
+}
+
+/// Current vault policy, the defaults if none was saved
+pub fn load_policy(conn: &Connection) -> Result<ThrottlePolicy, XPassError> {
+    let policy = conn.query_row(
+        "SELECT free_attempts, base_delay_secs, max_delay_secs, lockout_threshold, lockout_secs
+         FROM throttle_policy WHERE id = 1",
+        [],
+        |row| Ok(ThrottlePolicy {
+            free_attempts: row.get(0)?,
+            base_delay_secs: row.get(1)?,
+            max_delay_secs: row.get(2)?,
+            lockout_threshold: row.get(3)?,
+            lockout_secs: row.get(4)?,
+        })
+    ).optional()?;
+    Ok(policy.unwrap_or(ThrottlePolicy::DEFAULT))
+}
+
+fn save_policy(conn: &Connection, policy: &ThrottlePolicy) -> Result<(), XPassError> {
+    conn.execute(
+        "INSERT INTO throttle_policy (id, free_attempts, base_delay_secs, max_delay_secs, lockout_threshold, lockout_secs, updated_at)
+         VALUES (1, ?1, ?2, ?3, ?4, ?5, ?6)
+         ON CONFLICT(id) DO UPDATE SET free_attempts = ?1, base_delay_secs = ?2, max_delay_secs = ?3,
+                                       lockout_threshold = ?4, lockout_secs = ?5, updated_at = ?6",
+        params![
+            policy.free_attempts,
+            policy.base_delay_secs,
+            policy.max_delay_secs,
+            policy.lockout_threshold,
+            policy.lockout_secs,
+            Utc::now().to_rfc3339()
+        ],
+    )?;
+    Ok(())
+}
+
+fn parse_time(value: Option<String>) -> Option<DateTime<Utc>> {
+    value
+        .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+        .map(|t| t.with_timezone(&Utc))
+}
+
+fn load_state(conn: &Connection, user_id: i64) -> Result<AttemptState, XPassError> {
+    let state = conn.query_row(
+        "SELECT failed_count, next_attempt_at, locked_until FROM login_attempts WHERE user_id = ?1",
+        params![user_id],
+        |row| Ok(AttemptState {
+            failed_count: row.get(0)?,
+            next_attempt_at: parse_time(row.get(1)?),
+            locked_until: parse_time(row.get(2)?),
+        })
+    ).optional()?;
+    Ok(state.unwrap_or_default())
+}
+
+fn seconds_until(at: Option<DateTime<Utc>>, now: DateTime<Utc>) -> u64 {
+    at.map(|at| (at - now).num_milliseconds())
+        .filter(|ms| *ms > 0)
+        .map(|ms| ((ms + 999) / 1000) as u64)
+        .unwrap_or(0)
+}
+
+/// Throttling state of an account as of now
+pub fn status(conn: &Connection, user_id: i64) -> Result<LockoutStatus, XPassError> {
+    let policy = load_policy(conn)?;
+    let state = load_state(conn, user_id)?;
+    let now = Utc::now();
+
+    let locked_for = seconds_until(state.locked_until, now);
+    let delayed_for = seconds_until(state.next_attempt_at, now);
+
+    Ok(LockoutStatus {
+        failed_attempts: state.failed_count,
+        locked: locked_for > 0,
+        retry_after_secs: locked_for.max(delayed_for),
+        attempts_before_lockout: (policy.lockout_threshold > 0)
+            .then(|| policy.lockout_threshold.saturating_sub(state.failed_count)),
+    })
+}
+
+/// Rejects the attempt if the account is locked or still backing off.
+/// Must be called before any password or code is checked.
+pub fn ensure_allowed(conn: &Connection, user_id: i64) -> Result<(), XPassError> {
+    let status = status(conn, user_id)?;
+    if status.locked {
+        return Err(XPassError::AccountLocked { retry_after_secs: status.retry_after_secs });
+    }
+    if status.retry_after_secs > 0 {
+        return Err(XPassError::TooManyAttempts { retry_after_secs: status.retry_after_secs });
+    }
+    Ok(())
+}
+
+/// Counts a failed attempt and schedules the next allowed one
+pub fn record_failure(conn: &Connection, user_id: i64, method: &str) -> Result<(), XPassError> {
+    let policy = load_policy(conn)?;
+    let failed_count = load_state(conn, user_id)?.failed_count.saturating_add(1);
+    let now = Utc::now();
+
+    let delay = policy.delay_after(failed_count);
+    let next_attempt_at = (delay > 0).then(|| now + Duration::seconds(delay as i64));
+    let locked_until = policy
+        .locks_after(failed_count)
+        .then(|| now + Duration::seconds(policy.lockout_secs as i64));
+
+    conn.execute(
+        "INSERT INTO login_attempts (user_id, failed_count, last_failed_at, next_attempt_at, locked_until)
+         VALUES (?1, ?2, ?3, ?4, ?5)
+         ON CONFLICT(user_id) DO UPDATE SET failed_count = ?2, last_failed_at = ?3,
+                                            next_attempt_at = ?4, locked_until = ?5",
+        params![
+            user_id,
+            failed_count,
+            now.to_rfc3339(),
+            next_attempt_at.map(|t| t.to_rfc3339()),
+            locked_until.map(|t| t.to_rfc3339())
+        ],
+    )?;
+
+    if locked_until.is_some() {
+        warn!("Account user_id {} locked after {} failed attempts", user_id, failed_count);
+        crate::activity_logger::log_activity(
+            conn,
+            user_id,
+            "account_locked",
+            &format!("Account locked for {} s after {} failed {} attempts", policy.lockout_secs, failed_count, method)
+        ).ok();
+    }
+    Ok(())
+}
+
+/// Clears the failure counter after a successful unlock
+pub fn record_success(conn: &Connection, user_id: i64) -> Result<(), XPassError> {
+    conn.execute("DELETE FROM login_attempts WHERE user_id = ?1", params![user_id])?;
+    Ok(())
+}
+
+/// Runs `attempt` under throttling: rejected outright while the account is
+/// backing off, counted as a failure if it fails with a wrong password or
+/// code, and clears the counter if it succeeds
+pub fn guard<T>(
+    conn: &Connection,
+    user_id: i64,
+    method: &str,
+    attempt: impl FnOnce() -> Result<T, XPassError>
+) -> Result<T, XPassError> {
+    ensure_allowed(conn, user_id)?;
+    match attempt() {
+        Ok(value) => {
+            record_success(conn, user_id)?;
+            Ok(value)
+        }
+        Err(e @ (XPassError::InvalidPassword | XPassError::InvalidOtp)) => {
+            record_failure(conn, user_id, method)?;
+            Err(e)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Lockout state for the login screen, looked up by username
+#[tauri::command]
+pub fn get_lockout_status(db: State<'_, Database>, username: String) -> Result<LockoutStatus, XPassError> {
+    let conn = db.conn()?;
+    let user_id: i64 = conn.query_row(
+        "SELECT id FROM users WHERE username = ?1",
+        params![username],
+        |row| row.get(0)
+    ).map_err(|_| XPassError::UserNotFound)?;
+    status(&conn, user_id)
+}
+
+/// Returns the vault throttling policy
+#[tauri::command]
+pub fn get_throttle_policy(db: State<'_, Database>) -> Result<ThrottlePolicy, XPassError> {
+    let conn = db.conn()?;
+    load_policy(&conn)
+}
+
+/// Replaces the vault throttling policy. It protects every account, so it
+/// takes a session holding the real vault key and the master password: it
+/// can be switched off neither from the login screen nor in pseudo mode.
+#[tauri::command]
+pub fn set_throttle_policy(
+    db: State<'_, Database>,
+    sessions: State<'_, SessionStore>,
+    user_id: i64,
+    session_id: String,
+    master_password: SecretString,
+    policy: ThrottlePolicy
+) -> Result<ThrottlePolicy, XPassError> {
+    let cipher = sessions.cipher(&session_id, user_id)?;
+    policy.validate()?;
+
+    let conn = db.conn()?;
+    // Whatever a pseudo session is given, it is not the master password
+    if !keys::is_vault_key(&conn, user_id, &cipher)? {
+        return Err(XPassError::InvalidPassword);
+    }
+    password_manager::check_master_password(&conn, user_id, &master_password)?;
+    save_policy(&conn, &policy)?;
+    crate::activity_logger::log_activity(
+        &conn,
+        user_id,
+        "throttle_policy_changed",
+        &format!(
+            "Login throttling set to {} free attempts, {}-{} s backoff, lockout after {} for {} s",
+            policy.free_attempts, policy.base_delay_secs, policy.max_delay_secs,
+            policy.lockout_threshold, policy.lockout_secs
+        )
+    ).ok();
+
+    info!("Throttle policy updated by user_id {}", user_id);
+    Ok(policy)
+}
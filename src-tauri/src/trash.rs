@@ -0,0 +1,271 @@
+// ============================================================================
+// X-PASS Password Manager
+// Copyright (C) 2026 ar3love
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See LICENSE.
+// ============================================================================
+
+// src-tauri/src/trash.rs
+//
+// Trash bin for deleted entries. `delete_entry` only stamps `deleted_at`;
+// such entries are hidden everywhere else and can be restored until they
+// are purged, either after the user's retention period (checked at login and
+// whenever the trash is listed) or explicitly.
+//
+// Service and login are stored in clear, so every trash command first checks
+// that the session key actually opens this vault. A pseudo session sees an
+// empty trash and cannot restore or purge anything.
+use chrono::{DateTime, Duration, Utc};
+use log::info;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use tauri::State;
+
+use crate::database::Database;
+use crate::error::XPassError;
+use crate::keys;
+use crate::session::SessionStore;
+
+pub const DEFAULT_RETENTION_DAYS: u32 = 30;
+/// Longest retention that can be configured, one year
+const MAX_RETENTION_DAYS: u32 = 365;
+
+#[derive(Serialize, Debug)]
+pub struct TrashedEntry {
+    pub id: i64,
+    pub service: String,
+    pub login: String,
+    pub deleted_at: String,
+    /// When the entry will be purged, None if the trash is only emptied by hand
+    pub purge_at: Option<String>,
+}
+
+/// Days a deleted entry is kept, 0 means until the trash is emptied
+pub fn retention_days(conn: &Connection, user_id: i64) -> Result<u32, XPassError> {
+    let days = conn.query_row(
+        "SELECT retention_days FROM trash_settings WHERE user_id = ?1",
+        params![user_id],
+        |row| row.get(0)
+    ).optional()?;
+    Ok(days.unwrap_or(DEFAULT_RETENTION_DAYS))
+}
+
+/// Moves an entry to the trash, returns its service for logging
+pub fn move_to_trash(conn: &Connection, user_id: i64, entry_id: i64) -> Result<String, XPassError> {
+    let service: String = conn.query_row(
+        "UPDATE entries SET deleted_at = ?1
+         WHERE id = ?2 AND user_id = ?3 AND deleted_at IS NULL
+         RETURNING service",
+        params![Utc::now().to_rfc3339(), entry_id, user_id],
+        |row| row.get(0)
+    ).optional()?.ok_or(XPassError::NotFound { what: "Entry" })?;
+    Ok(service)
+}
+
+/// Permanently deletes trashed entries older than the retention period
+pub fn purge_expired(conn: &Connection, user_id: i64) -> Result<usize, XPassError> {
+    let days = retention_days(conn, user_id)?;
+    if days == 0 {
+        return Ok(0);
+    }
+
+    let cutoff = Utc::now() - Duration::days(days as i64);
+    let purged = conn.execute(
+        "DELETE FROM entries WHERE user_id = ?1 AND deleted_at IS NOT NULL AND deleted_at < ?2",
+        params![user_id, cutoff.to_rfc3339()],
+    )?;
+
+    if purged > 0 {
+        info!("Purged {} expired trash entries for user_id {}", purged, user_id);
+        crate::activity_logger::log_activity(
+            conn,
+            user_id,
+            "trash_purged",
+            &format!("{} entries older than {} days removed from trash", purged, days)
+        ).ok();
+    }
+    Ok(purged)
+}
+
+/// Lists the user's trash, most recently deleted first
+#[tauri::command]
+pub fn list_trash(
+    db: State<'_, Database>,
+    sessions: State<'_, SessionStore>,
+    user_id: i64,
+    session_id: String
+) -> Result<Vec<TrashedEntry>, XPassError> {
+    let cipher = sessions.cipher(&session_id, user_id)?;
+    let conn = db.conn()?;
+
+    if !keys::is_vault_key(&conn, user_id, &cipher)? {
+        return Ok(vec![]);
+    }
+    purge_expired(&conn, user_id)?;
+
+    let days = retention_days(&conn, user_id)?;
+    let mut stmt = conn.prepare_cached(
+        "SELECT id, service, login, deleted_at FROM entries
+         WHERE user_id = ?1 AND deleted_at IS NOT NULL
+         ORDER BY deleted_at DESC"
+    )?;
+    let rows = stmt.query_map(params![user_id], |row| {
+        Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?, row.get::<_, String>(3)?))
+    })?;
+
+    let mut entries = Vec::new();
+    for row in rows {
+        let (id, service, login, deleted_at) = row?;
+        let purge_at = (days > 0)
+            .then(|| DateTime::parse_from_rfc3339(&deleted_at).ok())
+            .flatten()
+            .map(|t| (t + Duration::days(days as i64)).with_timezone(&Utc).to_rfc3339());
+        entries.push(TrashedEntry { id, service, login, deleted_at, purge_at });
+    }
+    Ok(entries)
+}
+
+/// Puts a trashed entry back into the vault
+#[tauri::command]
+pub fn restore_from_trash(
+    db: State<'_, Database>,
+    sessions: State<'_, SessionStore>,
+    user_id: i64,
+    session_id: String,
+    entry_id: i64
+) -> Result<(), XPassError> {
+    let cipher = sessions.cipher(&session_id, user_id)?;
+    let conn = db.conn()?;
+
+    if !keys::is_vault_key(&conn, user_id, &cipher)? {
+        return Err(XPassError::NotFound { what: "Entry" });
+    }
+
+    let service: String = conn.query_row(
+        "UPDATE entries SET deleted_at = NULL
+         WHERE id = ?1 AND user_id = ?2 AND deleted_at IS NOT NULL
+         RETURNING service",
+        params![entry_id, user_id],
+        |row| row.get(0)
+    ).optional()?.ok_or(XPassError::NotFound { what: "Entry" })?;
+
+    crate::activity_logger::log_activity(
+        &conn,
+        user_id,
+        "entry_restored_from_trash",
+        &format!("Restored entry for service: {} from trash", service)
+    )?;
+    Ok(())
+}
+
+/// Permanently deletes a single trashed entry
+#[tauri::command]
+pub fn purge_trash_entry(
+    db: State<'_, Database>,
+    sessions: State<'_, SessionStore>,
+    user_id: i64,
+    session_id: String,
+    entry_id: i64
+) -> Result<(), XPassError> {
+    let cipher = sessions.cipher(&session_id, user_id)?;
+    let conn = db.conn()?;
+
+    if !keys::is_vault_key(&conn, user_id, &cipher)? {
+        return Err(XPassError::NotFound { what: "Entry" });
+    }
+
+    let service: String = conn.query_row(
+        "DELETE FROM entries
+         WHERE id = ?1 AND user_id = ?2 AND deleted_at IS NOT NULL
+         RETURNING service",
+        params![entry_id, user_id],
+        |row| row.get(0)
+    ).optional()?.ok_or(XPassError::NotFound { what: "Entry" })?;
+
+    crate::activity_logger::log_activity(
+        &conn,
+        user_id,
+        "trash_purged",
+        &format!("Permanently deleted entry for service: {}", service)
+    )?;
+    Ok(())
+}
+
+/// Permanently deletes everything in the trash, returns how many entries
+#[tauri::command]
+pub fn empty_trash(
+    db: State<'_, Database>,
+    sessions: State<'_, SessionStore>,
+    user_id: i64,
+    session_id: String
+) -> Result<usize, XPassError> {
+    let cipher = sessions.cipher(&session_id, user_id)?;
+    let conn = db.conn()?;
+
+    if !keys::is_vault_key(&conn, user_id, &cipher)? {
+        return Ok(0);
+    }
+
+    let purged = conn.execute(
+        "DELETE FROM entries WHERE user_id = ?1 AND deleted_at IS NOT NULL",
+        params![user_id],
+    )?;
+
+    crate::activity_logger::log_activity(
+        &conn,
+        user_id,
+        "trash_emptied",
+        &format!("Trash emptied, {} entries permanently deleted", purged)
+    )?;
+    Ok(purged)
+}
+
+/// Returns how many days deleted entries are kept
+#[tauri::command]
+pub fn get_trash_retention(db: State<'_, Database>, user_id: i64) -> Result<u32, XPassError> {
+    let conn = db.conn()?;
+    retention_days(&conn, user_id)
+}
+
+/// Sets how many days deleted entries are kept, 0 keeps them until the
+/// trash is emptied. Entries already past the new period are purged.
+#[tauri::command]
+pub fn set_trash_retention(
+    db: State<'_, Database>,
+    sessions: State<'_, SessionStore>,
+    user_id: i64,
+    session_id: String,
+    retention_days: u32
+) -> Result<u32, XPassError> {
+    let cipher = sessions.cipher(&session_id, user_id)?;
+    if retention_days > MAX_RETENTION_DAYS {
+        return Err(XPassError::invalid_input("retention_days", format!("must be at most {}", MAX_RETENTION_DAYS)));
+    }
+
+    let conn = db.conn()?;
+    // Pretend to a pseudo session, the real trash is left alone
+    if !keys::is_vault_key(&conn, user_id, &cipher)? {
+        return Ok(retention_days);
+    }
+    conn.execute(
+        "INSERT INTO trash_settings (user_id, retention_days) VALUES (?1, ?2)
+         ON CONFLICT(user_id) DO UPDATE SET retention_days = ?2",
+        params![user_id, retention_days],
+    )?;
+    crate::activity_logger::log_activity(
+        &conn,
+        user_id,
+        "trash_retention_changed",
+        &format!("Trash retention set to {} days", retention_days)
+    )?;
+    purge_expired(&conn, user_id)?;
+
+    info!("Trash retention set to {} days for user_id {}", retention_days, user_id);
+    Ok(retention_days)
+}
@@ -0,0 +1,518 @@
+// ============================================================================
+// X-PASS Password Manager
+// Copyright (C) 2026 ar3love
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See LICENSE.
+// ============================================================================
+
+// src-tauri/src/urls.rs
+//
+// Website URLs on entries and finding the entries for a page. An entry can
+// have several URLs, each encrypted like a custom field in `entry_urls` and
+// each with its own match mode. The default mode compares registrable
+// domains ("example.co.uk" for "login.example.co.uk"), worked out with the
+// public suffix list bundled in assets/public_suffix_list.dat. Refresh that
+// file from https://publicsuffix.org/list/ before a release. Regex rules are
+// compiled once per session and kept with it (see `PatternCache`).
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use aes_gcm::Aes256Gcm;
+use regex::{Regex, RegexBuilder};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use tauri::State;
+use url::{Host, Url};
+
+use crate::database::Database;
+use crate::error::XPassError;
+use crate::keys;
+use crate::password_manager::{self, Entry};
+use crate::secrets::SecretString;
+use crate::session::SessionStore;
+
+const MAX_URLS: usize = 20;
+const MAX_URL_LEN: usize = 2048;
+/// Compiled size limit for regex rules, so a pattern cannot eat memory
+const REGEX_SIZE_LIMIT: usize = 1 << 20;
+/// Compiled rules a session keeps before starting over
+const MAX_CACHED_PATTERNS: usize = 1000;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchMode {
+    /// Same registrable domain: login.example.com matches www.example.com
+    #[default]
+    BaseDomain,
+    /// Same host and port
+    Host,
+    /// The page URL starts with the stored URL
+    StartsWith,
+    /// The stored URL is a regular expression for the whole page URL
+    Regex,
+    /// Kept on the entry but never matched
+    Never,
+}
+
+impl MatchMode {
+    fn as_str(&self) -> &'static str {
+        match self {
+            MatchMode::BaseDomain => "base_domain",
+            MatchMode::Host => "host",
+            MatchMode::StartsWith => "starts_with",
+            MatchMode::Regex => "regex",
+            MatchMode::Never => "never",
+        }
+    }
+
+    fn parse(value: &str) -> MatchMode {
+        match value {
+            "base_domain" => MatchMode::BaseDomain,
+            "host" => MatchMode::Host,
+            "starts_with" => MatchMode::StartsWith,
+            "regex" => MatchMode::Regex,
+            // Modes from a newer version are not guessed at
+            _ => MatchMode::Never,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EntryUrl {
+    pub url: SecretString,
+    #[serde(default)]
+    pub match_mode: MatchMode,
+}
+
+/// How closely a page matched an entry; closer matches are listed first
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+enum Closeness {
+    Domain,
+    Host,
+    Exact,
+}
+
+struct SuffixList {
+    rules: HashSet<String>,
+    /// Parents of `*.` rules: "ck" for "*.ck"
+    wildcards: HashSet<String>,
+    /// `!` rules, which are not public suffixes themselves
+    exceptions: HashSet<String>,
+}
+
+lazy_static::lazy_static! {
+    static ref SUFFIXES: SuffixList = SuffixList::parse(include_str!("../assets/public_suffix_list.dat"));
+}
+
+impl SuffixList {
+    fn parse(list: &str) -> SuffixList {
+        let mut suffixes = SuffixList { rules: HashSet::new(), wildcards: HashSet::new(), exceptions: HashSet::new() };
+        for line in list.lines() {
+            let Some(rule) = line.split_whitespace().next() else {
+                continue;
+            };
+            if rule.starts_with("//") {
+                continue;
+            }
+            let (set, rule) = if let Some(rule) = rule.strip_prefix('!') {
+                (&mut suffixes.exceptions, rule)
+            } else if let Some(rule) = rule.strip_prefix("*.") {
+                (&mut suffixes.wildcards, rule)
+            } else {
+                (&mut suffixes.rules, rule)
+            };
+            // The list spells internationalized names in Unicode, hosts come in punycode
+            if let Ok(Host::Domain(ascii)) = Host::parse(rule) {
+                set.insert(ascii);
+            }
+        }
+        suffixes
+    }
+
+    /// The public suffix of a domain plus one label, None if the domain is
+    /// itself a public suffix. Unlisted top-level domains count as suffixes.
+    fn registrable_domain<'a>(&self, domain: &'a str) -> Option<&'a str> {
+        let domain = domain.trim_end_matches('.');
+        let starts: Vec<usize> = std::iter::once(0)
+            .chain(domain.match_indices('.').map(|(i, _)| i + 1))
+            .collect();
+
+        let suffix = (0..starts.len())
+            .find_map(|i| {
+                let candidate = &domain[starts[i]..];
+                if self.exceptions.contains(candidate) {
+                    Some(i + 1)
+                } else if self.rules.contains(candidate)
+                    || starts.get(i + 1).is_some_and(|&parent| self.wildcards.contains(&domain[parent..])) {
+                    Some(i)
+                } else {
+                    None
+                }
+            })
+            .unwrap_or(starts.len() - 1);
+
+        suffix.checked_sub(1).map(|label| &domain[starts[label]..])
+    }
+}
+
+/// Parses a page or stored URL; a bare "example.com/login" is taken as https
+fn parse_url(value: &str) -> Option<Url> {
+    let value = value.trim();
+    if value.contains("://") {
+        Url::parse(value).ok()
+    } else {
+        Url::parse(&format!("https://{}", value)).ok()
+    }
+}
+
+/// Regex rules compiled for one session, by pattern
+#[derive(Default)]
+pub struct PatternCache {
+    compiled: HashMap<String, Regex>,
+}
+
+impl PatternCache {
+    /// The compiled rule, None if the pattern does not compile
+    fn get(&mut self, pattern: &str) -> Option<&Regex> {
+        if !self.compiled.contains_key(pattern) {
+            let regex = compile_pattern(pattern).ok()?;
+            if self.compiled.len() >= MAX_CACHED_PATTERNS {
+                self.compiled.clear();
+            }
+            self.compiled.insert(pattern.to_string(), regex);
+        }
+        self.compiled.get(pattern)
+    }
+}
+
+impl EntryUrl {
+    /// How closely the URL matches a page, None if it does not
+    fn matches(&self, page: &Url, patterns: &mut PatternCache) -> Option<Closeness> {
+        let stored = self.url.expose().trim();
+        match self.match_mode {
+            MatchMode::BaseDomain => {
+                let stored = parse_url(stored)?;
+                if stored.host_str().is_some() && stored.host_str() == page.host_str() {
+                    return Some(Closeness::Host);
+                }
+                let base = SUFFIXES.registrable_domain(stored.domain()?)?;
+                (Some(base) == SUFFIXES.registrable_domain(page.domain()?)).then_some(Closeness::Domain)
+            }
+            MatchMode::Host => {
+                let stored = parse_url(stored)?;
+                (stored.host_str().is_some()
+                    && stored.host_str() == page.host_str()
+                    && stored.port_or_known_default() == page.port_or_known_default())
+                    .then_some(Closeness::Host)
+            }
+            MatchMode::StartsWith => {
+                let prefix = parse_url(stored).map(String::from).unwrap_or_else(|| stored.to_string());
+                page.as_str().starts_with(&prefix).then_some(Closeness::Exact)
+            }
+            MatchMode::Regex => {
+                patterns.get(stored)?.is_match(page.as_str()).then_some(Closeness::Exact)
+            }
+            MatchMode::Never => None,
+        }
+    }
+}
+
+/// Compiles a regex rule anchored at both ends, so it has to match the
+/// whole page URL and not just some part of it, like a query parameter
+fn compile_pattern(pattern: &str) -> Result<Regex, regex::Error> {
+    RegexBuilder::new(&format!("^(?:{})$", pattern)).size_limit(REGEX_SIZE_LIMIT).build()
+}
+
+/// Closest match of any of the URLs
+fn best_match(urls: &[EntryUrl], page: &Url, patterns: &mut PatternCache) -> Option<Closeness> {
+    urls.iter().filter_map(|url| url.matches(page, patterns)).max()
+}
+
+/// Rejects URL lists the UI should never send: too many or overlong URLs,
+/// URLs without a host for the host based modes and broken regexes
+pub fn validate(urls: &[EntryUrl]) -> Result<(), XPassError> {
+    if urls.len() > MAX_URLS {
+        return Err(XPassError::invalid_input("urls", format!("at most {} URLs per entry", MAX_URLS)));
+    }
+    for url in urls {
+        let value = url.url.expose().trim();
+        if value.is_empty() || value.len() > MAX_URL_LEN {
+            return Err(XPassError::invalid_input("urls", format!("URLs must be 1 to {} bytes", MAX_URL_LEN)));
+        }
+        match url.match_mode {
+            MatchMode::BaseDomain | MatchMode::Host => {
+                if parse_url(value).is_none_or(|parsed| parsed.host_str().is_none()) {
+                    return Err(XPassError::invalid_input("urls", "not a valid URL"));
+                }
+            }
+            MatchMode::Regex => {
+                compile_pattern(value)
+                    .map_err(|e| XPassError::invalid_input("urls", format!("invalid regular expression: {}", e)))?;
+            }
+            MatchMode::StartsWith | MatchMode::Never => {}
+        }
+    }
+    Ok(())
+}
+
+/// Replaces all URLs of an entry. Call inside the transaction that writes
+/// the entry.
+pub fn save_urls(conn: &Connection, entry_id: i64, urls: &[EntryUrl], cipher: &Aes256Gcm) -> Result<(), XPassError> {
+    conn.execute("DELETE FROM entry_urls WHERE entry_id = ?1", params![entry_id])?;
+    insert_urls(
+        conn,
+        "INSERT INTO entry_urls (entry_id, position, match_mode, enc_url, url_nonce) VALUES (?1, ?2, ?3, ?4, ?5)",
+        entry_id,
+        urls,
+        cipher
+    )
+}
+
+/// Stores the URLs of a revision imported from a backup
+pub fn save_revision_urls(conn: &Connection, revision_id: i64, urls: &[EntryUrl], cipher: &Aes256Gcm) -> Result<(), XPassError> {
+    insert_urls(
+        conn,
+        "INSERT INTO entry_history_urls (revision_id, position, match_mode, enc_url, url_nonce) VALUES (?1, ?2, ?3, ?4, ?5)",
+        revision_id,
+        urls,
+        cipher
+    )
+}
+
+fn insert_urls(conn: &Connection, sql: &str, owner_id: i64, urls: &[EntryUrl], cipher: &Aes256Gcm) -> Result<(), XPassError> {
+    let mut stmt = conn.prepare_cached(sql)?;
+    for (position, url) in urls.iter().enumerate() {
+        let (enc_url, url_nonce) = keys::encrypt_secret(cipher, &SecretString::new(url.url.expose().trim().to_string()))?;
+        stmt.execute(params![owner_id, position as i64, url.match_mode.as_str(), enc_url, url_nonce])?;
+    }
+    Ok(())
+}
+
+/// Copies the URLs of an entry, still encrypted, to a revision of it
+pub fn copy_to_revision(conn: &Connection, entry_id: i64, revision_id: i64) -> Result<(), XPassError> {
+    conn.execute(
+        "INSERT INTO entry_history_urls (revision_id, position, match_mode, enc_url, url_nonce)
+         SELECT ?2, position, match_mode, enc_url, url_nonce FROM entry_urls WHERE entry_id = ?1",
+        params![entry_id, revision_id],
+    )?;
+    Ok(())
+}
+
+/// Decrypted URLs of an entry, in the order they were saved
+pub fn load_urls(conn: &Connection, entry_id: i64, cipher: &Aes256Gcm) -> Result<Vec<EntryUrl>, XPassError> {
+    read_urls(
+        conn,
+        "SELECT match_mode, enc_url, url_nonce FROM entry_urls WHERE entry_id = ?1 ORDER BY position",
+        entry_id,
+        cipher
+    )
+}
+
+/// Decrypted URLs of a revision
+pub fn load_revision_urls(conn: &Connection, revision_id: i64, cipher: &Aes256Gcm) -> Result<Vec<EntryUrl>, XPassError> {
+    read_urls(
+        conn,
+        "SELECT match_mode, enc_url, url_nonce FROM entry_history_urls WHERE revision_id = ?1 ORDER BY position",
+        revision_id,
+        cipher
+    )
+}
+
+fn read_urls(conn: &Connection, sql: &str, owner_id: i64, cipher: &Aes256Gcm) -> Result<Vec<EntryUrl>, XPassError> {
+    let mut stmt = conn.prepare_cached(sql)?;
+    let rows = stmt.query_map(params![owner_id], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, Vec<u8>>(1)?, row.get::<_, Vec<u8>>(2)?))
+    })?;
+
+    let mut urls = Vec::new();
+    for row in rows {
+        let (match_mode, enc_url, url_nonce) = row?;
+        urls.push(EntryUrl {
+            url: keys::decrypt_secret(cipher, &enc_url, &url_nonce)?,
+            match_mode: MatchMode::parse(&match_mode),
+        });
+    }
+    Ok(urls)
+}
+
+/// Decrypted URLs of every live entry of the user, by entry id
+fn load_user_urls(conn: &Connection, user_id: i64, cipher: &Aes256Gcm) -> Result<BTreeMap<i64, Vec<EntryUrl>>, XPassError> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT u.entry_id, u.match_mode, u.enc_url, u.url_nonce
+         FROM entry_urls u JOIN entries e ON e.id = u.entry_id
+         WHERE e.user_id = ?1 AND e.deleted_at IS NULL
+         ORDER BY u.entry_id, u.position"
+    )?;
+    let rows = stmt.query_map(params![user_id], |row| {
+        Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, Vec<u8>>(2)?, row.get::<_, Vec<u8>>(3)?))
+    })?;
+
+    let mut urls: BTreeMap<i64, Vec<EntryUrl>> = BTreeMap::new();
+    for row in rows {
+        let (entry_id, match_mode, enc_url, url_nonce) = row?;
+        urls.entry(entry_id).or_default().push(EntryUrl {
+            url: keys::decrypt_secret(cipher, &enc_url, &url_nonce)?,
+            match_mode: MatchMode::parse(&match_mode),
+        });
+    }
+    Ok(urls)
+}
+
+/// Entries with a URL matching the page, closest matches first: exact
+/// (starts-with and regex rules), then same host, then same base domain
+#[tauri::command]
+pub fn find_entries_for_url(
+    db: State<'_, Database>,
+    sessions: State<'_, SessionStore>,
+    user_id: i64,
+    session_id: String,
+    url: String
+) -> Result<Vec<Entry>, XPassError> {
+    let cipher = sessions.cipher(&session_id, user_id)?;
+    let page = parse_url(&url).ok_or_else(|| XPassError::invalid_input("url", "not a valid URL"))?;
+    let conn = db.conn()?;
+
+    if !keys::is_vault_key(&conn, user_id, &cipher)? {
+        let entries = password_manager::pseudo_entries(&conn, user_id);
+        let mut matches: Vec<(Closeness, Entry)> = sessions.with_patterns(&session_id, user_id, |patterns| {
+            entries.into_iter()
+                .filter_map(|entry| Some((best_match(&entry.urls, &page, patterns)?, entry)))
+                .collect()
+        })?;
+        matches.sort_by_key(|m| Reverse(m.0));
+        return Ok(matches.into_iter().map(|(_, entry)| entry).collect());
+    }
+
+    let urls = load_user_urls(&conn, user_id, &cipher)?;
+    let mut matches: Vec<(Closeness, i64)> = sessions.with_patterns(&session_id, user_id, |patterns| {
+        urls.iter()
+            .filter_map(|(entry_id, urls)| Some((best_match(urls, &page, patterns)?, *entry_id)))
+            .collect()
+    })?;
+    matches.sort_by_key(|m| Reverse(m.0));
+
+    matches.into_iter()
+        .map(|(_, entry_id)| password_manager::load_entry(&conn, user_id, entry_id, &cipher))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn url(value: &str, match_mode: MatchMode) -> EntryUrl {
+        EntryUrl { url: SecretString::new(value.to_string()), match_mode }
+    }
+
+    fn matches(value: &str, match_mode: MatchMode, page: &str) -> Option<Closeness> {
+        url(value, match_mode).matches(&parse_url(page).unwrap(), &mut PatternCache::default())
+    }
+
+    #[test]
+    fn normal_wildcard_and_exception_rules() {
+        let list = SuffixList::parse("// comment\ncom\nco.uk\n*.ck\n!www.ck\n");
+        assert_eq!(list.registrable_domain("login.example.com"), Some("example.com"));
+        assert_eq!(list.registrable_domain("a.b.example.co.uk"), Some("example.co.uk"));
+        assert_eq!(list.registrable_domain("shop.site.bar.ck"), Some("site.bar.ck"));
+        assert_eq!(list.registrable_domain("www.ck"), Some("www.ck"));
+        assert_eq!(list.registrable_domain("mail.www.ck"), Some("www.ck"));
+        // Unlisted top-level domains count as suffixes
+        assert_eq!(list.registrable_domain("host.example.test"), Some("example.test"));
+        assert_eq!(list.registrable_domain("example.com."), Some("example.com"));
+    }
+
+    #[test]
+    fn bundled_list_has_wildcards_and_exceptions() {
+        assert_eq!(SUFFIXES.registrable_domain("login.example.co.uk"), Some("example.co.uk"));
+        assert_eq!(SUFFIXES.registrable_domain("a.b.c.ck"), Some("b.c.ck"));
+        assert_eq!(SUFFIXES.registrable_domain("www.ck"), Some("www.ck"));
+    }
+
+    #[test]
+    fn public_suffixes_have_no_registrable_domain() {
+        let list = SuffixList::parse("com\nco.uk\n*.ck\n");
+        assert_eq!(list.registrable_domain("com"), None);
+        assert_eq!(list.registrable_domain("co.uk"), None);
+        assert_eq!(list.registrable_domain("bar.ck"), None);
+        assert_eq!(matches("co.uk", MatchMode::BaseDomain, "https://other.co.uk"), None);
+    }
+
+    #[test]
+    fn punycode_hosts_match_unicode_rules() {
+        let list = SuffixList::parse("公司.cn\n");
+        let page = parse_url("https://www.例え.公司.cn/").unwrap();
+        let domain = page.domain().unwrap();
+        assert!(domain.is_ascii());
+        let registrable = list.registrable_domain(domain).unwrap();
+        assert!(registrable.ends_with(".xn--55qx5d.cn"), "{}", registrable);
+        assert_eq!(registrable.matches('.').count(), 2);
+        assert_eq!(matches("例え.公司.cn", MatchMode::BaseDomain, "https://login.例え.公司.cn"), Some(Closeness::Domain));
+    }
+
+    #[test]
+    fn ip_hosts_match_only_themselves() {
+        assert_eq!(matches("192.168.1.1", MatchMode::BaseDomain, "https://192.168.1.1/admin"), Some(Closeness::Host));
+        assert_eq!(matches("192.168.1.1", MatchMode::BaseDomain, "https://10.168.1.1/"), None);
+        assert_eq!(matches("192.168.1.1", MatchMode::BaseDomain, "https://1.1/"), None);
+        assert_eq!(matches("[::1]", MatchMode::Host, "http://[::1]:443/"), Some(Closeness::Host));
+    }
+
+    #[test]
+    fn base_domain_prefers_the_same_host() {
+        assert_eq!(matches("example.com", MatchMode::BaseDomain, "https://example.com/login"), Some(Closeness::Host));
+        assert_eq!(matches("example.com", MatchMode::BaseDomain, "https://login.example.com"), Some(Closeness::Domain));
+        assert_eq!(matches("example.com", MatchMode::BaseDomain, "https://example.com.evil.com"), None);
+    }
+
+    #[test]
+    fn host_mode_compares_ports() {
+        assert_eq!(matches("https://example.com", MatchMode::Host, "https://example.com:443/a"), Some(Closeness::Host));
+        assert_eq!(matches("example.com:8443", MatchMode::Host, "https://example.com/"), None);
+        assert_eq!(matches("example.com:8443", MatchMode::Host, "https://example.com:8443/"), Some(Closeness::Host));
+        assert_eq!(matches("http://example.com", MatchMode::Host, "https://example.com/"), None);
+        assert_eq!(matches("example.com", MatchMode::Host, "https://www.example.com/"), None);
+    }
+
+    #[test]
+    fn starts_with_does_not_match_longer_hosts() {
+        assert_eq!(matches("https://github.com", MatchMode::StartsWith, "https://github.com/login"), Some(Closeness::Exact));
+        assert_eq!(matches("https://github.com", MatchMode::StartsWith, "https://github.com.evil.com/"), None);
+        assert_eq!(matches("github.com/org", MatchMode::StartsWith, "https://github.com/org/repo"), Some(Closeness::Exact));
+    }
+
+    #[test]
+    fn regex_rules_match_the_whole_url() {
+        let rule = r"https://example\.com/.*";
+        assert_eq!(matches(rule, MatchMode::Regex, "https://example.com/login"), Some(Closeness::Exact));
+        assert_eq!(matches(rule, MatchMode::Regex, "https://evil.com/?next=https://example.com/"), None);
+        assert_eq!(matches("example", MatchMode::Regex, "https://example.com/"), None);
+        assert_eq!(matches("a|https://evil.com/", MatchMode::Regex, "https://evil.com/x"), None);
+        assert_eq!(matches("(", MatchMode::Regex, "https://example.com/"), None);
+        assert_eq!(matches("example.com", MatchMode::Never, "https://example.com/"), None);
+    }
+
+    #[test]
+    fn patterns_are_compiled_once() {
+        let mut patterns = PatternCache::default();
+        let page = parse_url("https://example.com/").unwrap();
+        let rule = url(r"https://example\.com/", MatchMode::Regex);
+        assert!(rule.matches(&page, &mut patterns).is_some());
+        assert!(rule.matches(&page, &mut patterns).is_some());
+        assert_eq!(patterns.compiled.len(), 1);
+    }
+
+    #[test]
+    fn validate_rejects_bad_urls() {
+        assert!(validate(&[url("example.com", MatchMode::BaseDomain), url(".*", MatchMode::Regex)]).is_ok());
+        assert!(validate(&[url("", MatchMode::StartsWith)]).is_err());
+        assert!(validate(&[url("https://", MatchMode::Host)]).is_err());
+        assert!(validate(&[url("(", MatchMode::Regex)]).is_err());
+        assert!(validate(&vec![url("example.com", MatchMode::BaseDomain); MAX_URLS + 1]).is_err());
+    }
+}
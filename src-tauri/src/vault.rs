@@ -0,0 +1,325 @@
+// ============================================================================
+// X-PASS Password Manager
+// Copyright (C) 2026 ar3love
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See LICENSE.
+// ============================================================================
+
+// src-tauri/src/vault.rs
+//
+// Vault file locations. The default vault lives in the Tauri app data
+// directory; additional vaults can be created or opened anywhere on disk and
+// are remembered in `vaults.json` next to the default one.
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+
+use crate::database::Database;
+use crate::error::XPassError;
+use crate::session::SessionStore;
+
+const DEFAULT_VAULT_FILE: &str = "passwords.db";
+const REGISTRY_FILE: &str = "vaults.json";
+/// Where earlier versions kept the vault (relative to the working directory)
+const LEGACY_VAULT_PATH: &str = "./passwords.db";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct KnownVault {
+    pub name: String,
+    pub path: PathBuf,
+    pub last_opened: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct VaultRegistry {
+    active: Option<PathBuf>,
+    vaults: Vec<KnownVault>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct VaultInfo {
+    pub name: String,
+    pub path: String,
+    pub is_active: bool,
+    pub is_default: bool,
+    pub exists: bool,
+    pub encrypted: bool,
+    pub last_opened: Option<String>,
+}
+
+pub fn app_data_dir(app: &AppHandle) -> Result<PathBuf, XPassError> {
+    let dir = app.path().app_data_dir().map_err(XPassError::internal)?;
+    fs::create_dir_all(&dir)?;
+    Ok(dir.canonicalize()?)
+}
+
+fn default_vault_path(app: &AppHandle) -> Result<PathBuf, XPassError> {
+    Ok(app_data_dir(app)?.join(DEFAULT_VAULT_FILE))
+}
+
+fn registry_path(app: &AppHandle) -> Result<PathBuf, XPassError> {
+    Ok(app_data_dir(app)?.join(REGISTRY_FILE))
+}
+
+fn load_registry(app: &AppHandle) -> Result<VaultRegistry, XPassError> {
+    let path = registry_path(app)?;
+    if !path.exists() {
+        return Ok(VaultRegistry::default());
+    }
+
+    let data = fs::read_to_string(&path)?;
+    match serde_json::from_str(&data) {
+        Ok(registry) => Ok(registry),
+        Err(e) => {
+            // A broken registry only loses the list of extra vaults, never the vaults themselves
+            warn!("Ignoring unreadable vault registry {}: {}", path.display(), e);
+            Ok(VaultRegistry::default())
+        }
+    }
+}
+
+fn save_registry(app: &AppHandle, registry: &VaultRegistry) -> Result<(), XPassError> {
+    let data = serde_json::to_string_pretty(registry).map_err(XPassError::internal)?;
+    fs::write(registry_path(app)?, data)?;
+    Ok(())
+}
+
+/// Makes a user-supplied path absolute so the same vault is never registered twice
+fn normalize_path(path: &str) -> Result<PathBuf, XPassError> {
+    let path = PathBuf::from(path.trim());
+    if path.as_os_str().is_empty() {
+        return Err(XPassError::invalid_input("path", "Vault path cannot be empty"));
+    }
+
+    if path.exists() {
+        return Ok(path.canonicalize()?);
+    }
+
+    let absolute = if path.is_absolute() {
+        path
+    } else {
+        std::env::current_dir()?.join(path)
+    };
+
+    // Canonicalize the parent so symlinked directories resolve like existing files do
+    match (absolute.parent(), absolute.file_name()) {
+        (Some(parent), Some(file)) if parent.exists() => {
+            Ok(parent.canonicalize()?.join(file))
+        }
+        _ => Ok(absolute),
+    }
+}
+
+fn vault_name(path: &Path) -> String {
+    path.file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "Vault".to_string())
+}
+
+fn remember_vault(registry: &mut VaultRegistry, path: &Path, name: Option<String>) {
+    let now = Utc::now().to_rfc3339();
+    match registry.vaults.iter_mut().find(|v| v.path == path) {
+        Some(known) => {
+            if let Some(name) = name {
+                known.name = name;
+            }
+            known.last_opened = Some(now);
+        }
+        None => registry.vaults.push(KnownVault {
+            name: name.unwrap_or_else(|| vault_name(path)),
+            path: path.to_path_buf(),
+            last_opened: Some(now),
+        }),
+    }
+    registry.active = Some(path.to_path_buf());
+}
+
+/// Checks that the file can be opened as an x-pass vault
+fn validate_vault_file(path: &Path) -> Result<(), XPassError> {
+    // Encrypted vaults cannot be inspected before they are unlocked
+    if crate::database::is_encrypted_file(path) {
+        return Ok(());
+    }
+
+    let conn = rusqlite::Connection::open_with_flags(path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+
+    let has_users: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'users')",
+        [],
+        |row| row.get(0)
+    ).map_err(|_| XPassError::NotAVault)?;
+
+    if !has_users {
+        return Err(XPassError::NotAVault);
+    }
+
+    Ok(())
+}
+
+/// Makes `path` the active vault: every open session belongs to the previous
+/// vault, so they are all invalidated
+fn activate(
+    app: &AppHandle,
+    db: &Database,
+    sessions: &SessionStore,
+    path: PathBuf,
+    name: Option<String>
+) -> Result<VaultInfo, XPassError> {
+    // Opening creates the schema for new vaults and migrates existing ones;
+    // if it fails the previous vault stays active
+    db.open(&path)?;
+    sessions.lock_all();
+
+    let mut registry = load_registry(app)?;
+    remember_vault(&mut registry, &path, name);
+    save_registry(app, &registry)?;
+
+    info!("Active vault switched to {}", path.display());
+    describe(app, db, &registry, &path)
+}
+
+fn describe(app: &AppHandle, db: &Database, registry: &VaultRegistry, path: &Path) -> Result<VaultInfo, XPassError> {
+    let default_path = default_vault_path(app)?;
+    let known = registry.vaults.iter().find(|v| v.path == path);
+
+    Ok(VaultInfo {
+        name: known.map(|v| v.name.clone()).unwrap_or_else(|| vault_name(path)),
+        path: path.to_string_lossy().into_owned(),
+        is_active: db.path().as_deref() == Some(path),
+        is_default: path == default_path,
+        exists: path.exists(),
+        encrypted: crate::database::is_encrypted_file(path),
+        last_opened: known.and_then(|v| v.last_opened.clone()),
+    })
+}
+
+/// Resolves the vault to use at startup: the last active one, or the default
+/// vault in the app data directory
+pub fn init(app: &AppHandle) -> Result<(), XPassError> {
+    let mut registry = load_registry(app)?;
+    let default_path = default_vault_path(app)?;
+
+    if registry.vaults.is_empty() {
+        // Earlier versions wrote the vault into the working directory; keep
+        // using it if it is there so upgrading does not "lose" the data
+        let legacy = Path::new(LEGACY_VAULT_PATH);
+        if !default_path.exists() && legacy.exists() {
+            let legacy = legacy.canonicalize()?;
+            info!("Found legacy vault at {}", legacy.display());
+            remember_vault(&mut registry, &legacy, Some("Legacy vault".to_string()));
+        } else {
+            remember_vault(&mut registry, &default_path, Some("Default".to_string()));
+        }
+        save_registry(app, &registry)?;
+    }
+
+    let active = match registry.active.clone() {
+        Some(path) if path.exists() || path == default_path => path,
+        Some(path) => {
+            warn!("Last active vault {} is missing, falling back to default", path.display());
+            default_path
+        }
+        None => default_path,
+    };
+
+    info!("Using vault {}", active.display());
+    Ok(app.state::<Database>().open(&active)?)
+}
+
+/// Lists every vault known to this installation
+#[tauri::command]
+pub fn list_vaults(app: AppHandle, db: State<'_, Database>) -> Result<Vec<VaultInfo>, XPassError> {
+    let mut registry = load_registry(&app)?;
+    let default_path = default_vault_path(&app)?;
+
+    if !registry.vaults.iter().any(|v| v.path == default_path) {
+        registry.vaults.insert(0, KnownVault {
+            name: "Default".to_string(),
+            path: default_path,
+            last_opened: None,
+        });
+    }
+
+    registry.vaults
+        .iter()
+        .map(|v| describe(&app, &db, &registry, &v.path))
+        .collect()
+}
+
+/// Returns the vault commands currently work against
+#[tauri::command]
+pub fn get_active_vault(app: AppHandle, db: State<'_, Database>) -> Result<VaultInfo, XPassError> {
+    let path = db.path().ok_or(XPassError::VaultNotOpen)?;
+    let registry = load_registry(&app)?;
+    describe(&app, &db, &registry, &path)
+}
+
+/// Creates a new empty vault file and makes it active
+#[tauri::command]
+pub fn create_vault(
+    app: AppHandle,
+    db: State<'_, Database>,
+    sessions: State<'_, SessionStore>,
+    path: String,
+    name: Option<String>
+) -> Result<VaultInfo, XPassError> {
+    let path = normalize_path(&path)?;
+    if path.exists() {
+        return Err(XPassError::VaultAlreadyExists);
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    activate(&app, &db, &sessions, path, name)
+}
+
+/// Opens an existing vault file from any location and makes it active
+#[tauri::command]
+pub fn open_vault(
+    app: AppHandle,
+    db: State<'_, Database>,
+    sessions: State<'_, SessionStore>,
+    path: String,
+    name: Option<String>
+) -> Result<VaultInfo, XPassError> {
+    let path = normalize_path(&path)?;
+    if !path.is_file() {
+        return Err(XPassError::VaultNotFound);
+    }
+    validate_vault_file(&path)?;
+
+    activate(&app, &db, &sessions, path, name)
+}
+
+/// Switches to a vault that was created or opened before
+#[tauri::command]
+pub fn switch_vault(
+    app: AppHandle,
+    db: State<'_, Database>,
+    sessions: State<'_, SessionStore>,
+    path: String
+) -> Result<VaultInfo, XPassError> {
+    let path = normalize_path(&path)?;
+    let registry = load_registry(&app)?;
+    let is_default = path == default_vault_path(&app)?;
+
+    if !is_default && !registry.vaults.iter().any(|v| v.path == path) {
+        return Err(XPassError::UnknownVault);
+    }
+    if !is_default && !path.is_file() {
+        return Err(XPassError::VaultNotFound);
+    }
+
+    activate(&app, &db, &sessions, path, None)
+}
@@ -30,17 +30,17 @@ type OverlayState = 'none' | 'unlocking'
 function App() {
   const [overlayState, setOverlayState] = useState<OverlayState>('none');
   const [isUnlockSuccess, setIsUnlockSuccess] = useState(false);
-  const [pendingAuth, setPendingAuth] = useState<{username: string, user_id: number, encKey: string} | null>(null);
+  const [pendingAuth, setPendingAuth] = useState<{username: string, user_id: number, sessionId: string} | null>(null);
   const { auth, setAuth } = useContext(AuthContext);
 
-  const handleLogin = (username: string, user_id: number, encKey: string) => {
-    setPendingAuth({ username, user_id, encKey });
+  const handleLogin = (username: string, user_id: number, sessionId: string) => {
+    setPendingAuth({ username, user_id, sessionId });
     setIsUnlockSuccess(true);
     setOverlayState('unlocking');
   };
 
   const handleLoginFailed = (username: string) => {
-    setPendingAuth({ username, user_id: 0, encKey: '' });
+    setPendingAuth({ username, user_id: 0, sessionId: '' });
     setIsUnlockSuccess(false);
     setOverlayState('unlocking');
   };
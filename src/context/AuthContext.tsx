@@ -12,7 +12,7 @@ import React, { createContext, useState, PropsWithChildren } from 'react';
 interface Auth {
   username: string;
   user_id: number;
-  encKey: string;
+  sessionId: string;
 }
 
 export const AuthContext = createContext<{
@@ -21,9 +21,22 @@ import { useTranslation } from 'react-i18next';
 import { motion, AnimatePresence } from 'framer-motion';
 import { ThemeContext } from '../context/theme/ThemeContext';
 import { SnackbarContext } from '../components/SnackbarProvider';
+import { errorCode, translateError } from '../utils/errors';
+
+interface LockoutStatus {
+  failed_attempts: number;
+  locked: boolean;
+  retry_after_secs: number;
+  attempts_before_lockout: number | null;
+}
+
+const isThrottled = (err: unknown) => {
+  const code = errorCode(err);
+  return code === 'too_many_attempts' || code === 'account_locked';
+};
 
 interface LoginProps {
-  setLogin: (username: string, user_id: number, encKey: string) => void;
+  setLogin: (username: string, user_id: number, sessionId: string) => void;
   onLoginFailed?: (username: string) => void;
 }
 
@@ -164,14 +177,26 @@ const Login: React.FC<LoginProps> = ({ setLogin }) => {
     setOtpCode('');
   };
 
+  const warnBeforeLockout = async (username: string) => {
+    try {
+      const status = await invoke<LockoutStatus>('get_lockout_status', { username });
+      const remaining = status.attempts_before_lockout;
+      if (remaining !== null && remaining > 0 && remaining <= 3) {
+        showMessage(t('login.attemptsBeforeLockout', { count: remaining }), 'warning');
+      }
+    } catch (err) {
+      console.error('Failed to get lockout status:', err);
+    }
+  };
+
   const handleLogin = async () => {
     if (selectedUser) {
       try {
-        const [user_id, enc_key] = await invoke<[number, string]>('login', { 
+        const [user_id, session_id] = await invoke<[number, string]>('login', { 
           username: selectedUser, 
           masterPass 
         });
-        setLogin(selectedUser, user_id, enc_key);
+        setLogin(selectedUser, user_id, session_id);
         setSelectedUser(null);
         setMasterPass('');
         setFailedAttempts(0);
@@ -180,11 +205,18 @@ const Login: React.FC<LoginProps> = ({ setLogin }) => {
         setPasswordError(false);
         setIsShaking(false);
       } catch (err) {
+        if (isThrottled(err)) {
+          // The attempt was rejected before the password was checked
+          showMessage(translateError(t, err, 'login.loginFailed'), 'warning');
+          return;
+        }
+
         setPasswordError(true);
         setIsShaking(true);
         showMessage(t('login.loginFailed'), 'error');
 
         await captureFailedLoginPhoto(selectedUser);
+        await warnBeforeLockout(selectedUser);
 
         setTimeout(() => {
           setIsShaking(false);
@@ -209,12 +241,12 @@ const Login: React.FC<LoginProps> = ({ setLogin }) => {
   const handleOTPLogin = async () => {
   if (selectedUser && otpCode) {
     try {
-      const [user_id, enc_key, _is_pseudo] = await invoke<[number, string, boolean]>('login_with_otp', { 
+      const [user_id, session_id, _is_pseudo] = await invoke<[number, string, boolean]>('login_with_otp', { 
         username: selectedUser,
         otpCode: otpCode
       });
       
-      setLogin(selectedUser, user_id, enc_key);
+      setLogin(selectedUser, user_id, session_id);
       setSelectedUser(null);
       setMasterPass('');
       setFailedAttempts(0);
@@ -226,11 +258,15 @@ const Login: React.FC<LoginProps> = ({ setLogin }) => {
     } catch (err: any) {
       console.error('OTP login error:', err);
       
-      if (err.includes('OTP recovery not set up')) {
+      const code = errorCode(err);
+      if (isThrottled(err)) {
+        showMessage(translateError(t, err, 'login.otpVerificationFailed'), 'warning');
+      } else if (code === 'otp_recovery_not_set_up') {
         showMessage(t('login.otpRecoveryNotSetup'), 'warning');
-      } else if (err.includes('Invalid OTP code')) {
+      } else if (code === 'invalid_otp') {
         showMessage(t('login.invalidOTP'), 'error');
         setOtpCode('');
+        await warnBeforeLockout(selectedUser);
       } else {
         showMessage(t('login.otpVerificationFailed'), 'error');
       }
@@ -245,11 +281,11 @@ const Login: React.FC<LoginProps> = ({ setLogin }) => {
     }
     try {
       await invoke('create_user', { username: newUser, masterPass: newPass, isSecret });
-      const [user_id, enc_key] = await invoke<[number, string]>('login', { 
+      const [user_id, session_id] = await invoke<[number, string]>('login', { 
         username: newUser, 
         masterPass: newPass 
       });
-      setLogin(newUser, user_id, enc_key);
+      setLogin(newUser, user_id, session_id);
       setIsCreating(false);
       setIsCreatingSecret(false);
       setNewUser('');
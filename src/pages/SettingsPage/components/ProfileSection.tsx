@@ -51,6 +51,7 @@ import { AuthContext } from '../../../context/AuthContext';
 import { invoke } from '@tauri-apps/api/core';
 import { SnackbarContext } from '../../../components/SnackbarProvider';
 import SettingsIcon from "@mui/icons-material/Settings"
+import { errorCode } from '../../../utils/errors';
 
 interface ProfileSectionProps {
   profilePics: Record<string, string>;
@@ -174,12 +175,10 @@ export const ProfileSection: React.FC<ProfileSectionProps> = memo(({
       handleCloseViewQrDialog();
       onOpenQrDialog();
     } catch (err: any) {
-      const errorMessage = typeof err === 'string' ? err : err?.message || String(err);
-      
       showMessage(
-        errorMessage.includes('Invalid password') 
-          ? t('settings.invalidPassword') 
-          : t('settings.verificationFailed'), 
+        errorCode(err) === 'invalid_password'
+          ? t('settings.invalidPassword')
+          : t('settings.verificationFailed'),
         'error'
       );
     } finally {
@@ -217,12 +216,10 @@ export const ProfileSection: React.FC<ProfileSectionProps> = memo(({
       setActiveStep(1);
       showMessage(t('settings.qrCodeGenerated'), 'success');
     } catch (err: any) {
-      const errorMessage = typeof err === 'string' ? err : err?.message || String(err);
-      
       showMessage(
-        errorMessage.includes('Invalid password') 
-          ? t('settings.invalidPassword') 
-          : t('settings.failedToGenerateOTP'), 
+        errorCode(err) === 'invalid_password'
+          ? t('settings.invalidPassword')
+          : t('settings.failedToGenerateOTP'),
         'error'
       );
     } finally {
@@ -11,6 +11,7 @@ import { useState, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import { useTranslation } from 'react-i18next';
 import { usePseudoModeContext } from '../../../context/PseudoModeContext';
+import { translateError } from '../../../utils/errors';
 
 interface PseudoPassword {
   id: number;
@@ -100,7 +101,7 @@ export const usePseudoMode = (userId: number | null) => {
       await loadSettings();
     } catch (error) {
       console.error('Failed to delete pseudo password:', error);
-      alert(t('settings.pseudoMode.deletePasswordError') + ': ' + error);
+      alert(t('settings.pseudoMode.deletePasswordError') + ': ' + translateError(t, error, 'errors.internal_error'));
     }
   };
 
@@ -133,7 +134,7 @@ export const usePseudoMode = (userId: number | null) => {
       alert(t('settings.pseudoMode.settingsSavedSuccessfully'));
     } catch (error) {
       console.error('Failed to save pseudo mode settings:', error);
-      alert(t('settings.pseudoMode.saveError') + ': ' + error);
+      alert(t('settings.pseudoMode.saveError') + ': ' + translateError(t, error, 'errors.internal_error'));
     }
   };
 
@@ -14,6 +14,7 @@ import { invoke } from '@tauri-apps/api/core';
 import { ThemeContext } from '../../../context/theme/ThemeContext';
 import { AuthContext } from '../../../context/AuthContext';
 import { SnackbarContext } from '../../../components/SnackbarProvider';
+import { translateError } from '../../../utils/errors';
 
 export const useSettings = () => {
   const { t, i18n } = useTranslation();
@@ -135,7 +136,7 @@ export const useSettings = () => {
         setAuth(null);
         showMessage(t('settings.deleteAccountSuccess'), 'success');
       } catch (err) {
-        showMessage(`${t('settings.deleteAccountFailed')}: ${err}`, 'error');
+        showMessage(`${t('settings.deleteAccountFailed')}: ${translateError(t, err, 'errors.internal_error')}`, 'error');
       }
       setOpenDeleteDialog(false);
       setDeletePassword('');
@@ -150,7 +151,7 @@ export const useSettings = () => {
         setHasOtp(true);
         showMessage(t('settings.otpGenerated'), 'success');
       } catch (err) {
-        showMessage(`${t('settings.otpGenerationFailed')}: ${err}`, 'error');
+        showMessage(`${t('settings.otpGenerationFailed')}: ${translateError(t, err, 'errors.internal_error')}`, 'error');
       }
     }
   };
@@ -66,6 +66,38 @@ import { SnackbarContext } from '../components/SnackbarProvider';
 import * as XLSX from 'xlsx';
 import { motion, AnimatePresence } from 'framer-motion';
 import { useNavigate } from 'react-router-dom';
+import { translateError } from '../utils/errors';
+
+type CustomFieldType = 'text' | 'hidden' | 'url' | 'email' | 'number' | 'date' | 'totp';
+
+interface CustomField {
+  name: string;
+  field_type: CustomFieldType;
+  value: string;
+}
+
+type UrlMatchMode = 'base_domain' | 'host' | 'starts_with' | 'regex' | 'never';
+
+interface EntryUrl {
+  url: string;
+  match_mode: UrlMatchMode;
+}
+
+interface StrengthSummary {
+  score: 0 | 1 | 2 | 3 | 4;
+  guesses_log10: number;
+  warning: string | null;
+}
+
+type EntryKind =
+  | 'login'
+  | 'credit_card'
+  | 'bank_account'
+  | 'identity'
+  | 'wifi_network'
+  | 'software_license'
+  | 'ssh_key'
+  | 'secure_note';
 
 interface Entry {
   id: number;
@@ -73,6 +105,13 @@ interface Entry {
   login: string;
   password: string;
   note: string;
+  kind: EntryKind;
+  details: Record<string, string>;
+  fields: CustomField[];
+  urls: EntryUrl[];
+  folder_id: number | null;
+  tag_ids: number[];
+  strength: StrengthSummary | null;
 }
 
 const Vault: React.FC = () => {
@@ -111,7 +150,7 @@ const [backupPassword, setBackupPassword] = useState('');
 
   useEffect(() => {
     if (auth) {
-      invoke<Entry[]>('get_entries', { userId: auth.user_id, encKey: auth.encKey })
+      invoke<Entry[]>('get_entries', { userId: auth.user_id, sessionId: auth.sessionId })
         .then((fetchedEntries) => {
           setEntries(fetchedEntries);
           setFilteredEntries(fetchedEntries);
@@ -166,9 +205,9 @@ const [backupPassword, setBackupPassword] = useState('');
           login,
           password,
           note,
-          encKey: auth.encKey,
+          sessionId: auth.sessionId,
         });
-        const updated = await invoke<Entry[]>('get_entries', { userId: auth.user_id, encKey: auth.encKey });
+        const updated = await invoke<Entry[]>('get_entries', { userId: auth.user_id, sessionId: auth.sessionId });
         setEntries(updated);
         setOpenAdd(false);
         resetForm();
@@ -189,9 +228,9 @@ const [backupPassword, setBackupPassword] = useState('');
           login,
           password,
           note,
-          encKey: auth.encKey,
+          sessionId: auth.sessionId,
         });
-        const updated = await invoke<Entry[]>('get_entries', { userId: auth.user_id, encKey: auth.encKey });
+        const updated = await invoke<Entry[]>('get_entries', { userId: auth.user_id, sessionId: auth.sessionId });
         setEntries(updated);
         setOpenEdit(false);
         resetForm();
@@ -205,8 +244,8 @@ const [backupPassword, setBackupPassword] = useState('');
   const handleDelete = async (entryId: number) => {
     if (auth) {
       try {
-        await invoke('delete_entry', { entryId, userId: auth.user_id });
-        const updated = await invoke<Entry[]>('get_entries', { userId: auth.user_id, encKey: auth.encKey });
+        await invoke('delete_entry', { entryId, userId: auth.user_id, sessionId: auth.sessionId });
+        const updated = await invoke<Entry[]>('get_entries', { userId: auth.user_id, sessionId: auth.sessionId });
         setEntries(updated);
         setOpenDelete(false);
         showMessage(t('vault.deleteSuccess'), 'success');
@@ -309,7 +348,7 @@ const [backupPassword, setBackupPassword] = useState('');
       const backupJson = await invoke<string>('export_vault_encrypted', {
         userId: auth.user_id,
         username: auth.username,
-        encKey: auth.encKey,
+        sessionId: auth.sessionId,
       });
       
       // Сохраняем файл
@@ -340,6 +379,7 @@ const [backupPassword, setBackupPassword] = useState('');
     
     const importedCount = await invoke<number>('import_vault_with_password', {
       userId: auth.user_id,
+      sessionId: auth.sessionId,
       currentUserPassword: importPassword, 
       backupPassword: backupPassword, 
       backupJson: fileContent,
@@ -348,7 +388,7 @@ const [backupPassword, setBackupPassword] = useState('');
     
     const updated = await invoke<Entry[]>('get_entries', {
       userId: auth.user_id,
-      encKey: auth.encKey
+      sessionId: auth.sessionId
     });
     setEntries(updated);
     
@@ -362,7 +402,7 @@ const [backupPassword, setBackupPassword] = useState('');
   } catch (err) {
     console.error('Import failed:', err);
     showMessage(
-      typeof err === 'string' ? err : t('vault.importFailed'),
+      translateError(t, err, 'vault.importFailed'),
       'error'
     );
   }
@@ -373,7 +413,12 @@ const [backupPassword, setBackupPassword] = useState('');
     setOpenMenu(false);
   };
 
-  const handleLogout = () => {
+  const handleLogout = async () => {
+    if (auth) {
+      await invoke('logout', { sessionId: auth.sessionId, userId: auth.user_id }).catch((err) => {
+        console.error('Logout failed:', err);
+      });
+    }
     setAuth(null);
   };
   
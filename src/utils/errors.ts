@@ -0,0 +1,38 @@
+/**
+ * ============================================================================
+ * X-PASS Password Manager
+ * Copyright (C) 2026 ar3love
+ *
+ * Licensed under GPL-3.0. See LICENSE file for details.
+ * ============================================================================
+ */
+import type { TFunction } from 'i18next';
+
+// Shape of every error rejected by a backend command (src-tauri/src/error.rs).
+// `code` is stable and maps to the `errors.<code>` translation key;
+// `message` is English and only meant for logs.
+export interface XPassError {
+  code: string;
+  category: 'auth' | 'crypto' | 'storage' | 'validation' | 'network' | 'internal';
+  message: string;
+  params: Record<string, unknown>;
+}
+
+export const isXPassError = (err: unknown): err is XPassError =>
+  typeof err === 'object' &&
+  err !== null &&
+  typeof (err as XPassError).code === 'string' &&
+  typeof (err as XPassError).category === 'string';
+
+export const errorCode = (err: unknown): string | undefined =>
+  isXPassError(err) ? err.code : undefined;
+
+// Localized message for a command error, `fallbackKey` if the code is not translated
+export const translateError = (t: TFunction, err: unknown, fallbackKey: string): string => {
+  if (!isXPassError(err)) {
+    return t(fallbackKey, { reason: String(err) });
+  }
+  const key = `errors.${err.code}`;
+  const translated = t(key, { ...err.params, defaultValue: '' });
+  return translated || t(fallbackKey, { reason: err.message });
+};